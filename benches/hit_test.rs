@@ -0,0 +1,55 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::hint::black_box;
+use iced::{Point, Rectangle, Size};
+
+#[path = "../src/quadtree.rs"]
+mod quadtree;
+
+use quadtree::QuadTree;
+
+/// A 40x25 grid of node-sized boxes, roughly a very busy graph
+fn rects() -> Vec<Rectangle> {
+    (0..1000)
+        .map(|i| {
+            let (col, row) = ((i % 40) as f32, (i / 40) as f32);
+            Rectangle::new(Point::new(col * 220.0, row * 160.0), Size::new(180.0, 120.0))
+        })
+        .collect()
+}
+
+/// Points spread over the whole grid, hits and gaps alike
+fn points() -> Vec<Point> {
+    (0..256)
+        .map(|i| Point::new((i * 37 % 8800) as f32, (i * 53 % 4000) as f32))
+        .collect()
+}
+
+fn hit_test(c: &mut Criterion) {
+    let rects = rects();
+    let points = points();
+    let bounds = rects.iter().copied().reduce(|a, b| a.union(&b)).unwrap();
+    let mut tree = QuadTree::new(bounds);
+    for (i, rect) in rects.iter().enumerate() {
+        tree.insert(*rect, i);
+    }
+
+    let mut group = c.benchmark_group("hit_test_1000");
+    group.bench_function("linear", |b| {
+        b.iter(|| {
+            for &point in &points {
+                black_box(rects.iter().position(|r| r.contains(black_box(point))));
+            }
+        })
+    });
+    group.bench_function("quadtree", |b| {
+        b.iter(|| {
+            for &point in &points {
+                black_box(tree.query_point(black_box(point)));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, hit_test);
+criterion_main!(benches);
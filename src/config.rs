@@ -1,11 +1,15 @@
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    /// Config file layout version (missing in files written before versioning)
+    #[serde(default)]
+    pub schema_version: u32,
+
     #[serde(default)]
     positions: HashMap<String, Position>,
 
@@ -17,6 +21,11 @@ pub struct Config {
     #[serde(default)]
     pub last_preset: Option<String>,
 
+    /// Unix time each preset was last applied (canonical preset path →
+    /// seconds), so the picker can list recent presets first
+    #[serde(default)]
+    pub preset_last_applied: HashMap<String, u64>,
+
     /// Whether exclusive mode is enabled
     #[serde(default)]
     pub exclusive_mode: bool,
@@ -36,6 +45,353 @@ pub struct Config {
     /// Saved ghost node positions per device (device.name → position)
     #[serde(default)]
     pub device_positions: HashMap<String, Position>,
+
+    /// Nodes collapsed to a header-only view (node keys)
+    #[serde(default)]
+    pub collapsed_nodes: HashSet<String>,
+
+    /// Nodes auto-layout leaves where they are (node keys)
+    #[serde(default)]
+    pub pinned_layout_nodes: HashSet<String>,
+
+    /// Nodes left out of the canvas; their links stay connected (node keys)
+    #[serde(default)]
+    pub hidden_nodes: HashSet<String>,
+
+    /// Horizontal distance between auto-layout columns
+    #[serde(default = "default_layout_col_width")]
+    pub layout_col_width: f32,
+
+    /// Vertical gap between nodes stacked in an auto-layout column
+    #[serde(default = "default_layout_row_gap")]
+    pub layout_row_gap: f32,
+
+    /// Grid size auto-layout positions are snapped to (0 disables snapping)
+    #[serde(default = "default_layout_grid_snap")]
+    pub layout_grid_snap: f32,
+
+    /// Algorithm used by the L auto-layout
+    #[serde(default)]
+    pub layout_algorithm: LayoutAlgorithm,
+
+    /// Signal flow direction of the column layout
+    #[serde(default)]
+    pub layout_direction: LayoutDirection,
+
+    /// Simulation steps for the force-directed layout
+    #[serde(default = "default_layout_iterations")]
+    pub layout_iterations: u32,
+
+    /// Pull of a link in the force-directed layout
+    #[serde(default = "default_spring_strength")]
+    pub spring_strength: f32,
+
+    /// Whether dragged nodes snap to the background grid
+    #[serde(default)]
+    pub snap_to_grid: bool,
+
+    /// Grid spacing used for drag snapping
+    #[serde(default = "default_grid_size")]
+    pub grid_size: f32,
+
+    /// Zoom level below which port labels are hidden
+    #[serde(default = "default_lod_zoom_threshold")]
+    pub lod_zoom_threshold: f32,
+
+    /// Label ports with their PipeWire alias when they have one
+    #[serde(default = "default_prefer_port_alias")]
+    pub prefer_port_alias: bool,
+
+    /// Leave monitor ports (read-only taps of a sink) off the canvas
+    #[serde(default)]
+    pub hide_monitor_ports: bool,
+
+    /// Ports shown per side before a node's port list scrolls
+    #[serde(default = "default_max_visible_ports")]
+    pub max_visible_ports: usize,
+
+    /// Upper bound on animation frames per second
+    #[serde(default = "default_max_fps")]
+    pub max_fps: u32,
+
+    /// How long (ms) a removed node lingers as a ghost, so an app that
+    /// reconnects right away keeps its place
+    #[serde(default = "default_node_grace_period_ms")]
+    pub node_grace_period_ms: u64,
+
+    /// How many actions undo remembers
+    #[serde(default = "default_undo_stack_limit")]
+    pub undo_stack_limit: usize,
+
+    /// Key bindings by action name; actions left out keep their default
+    #[serde(default = "default_shortcuts")]
+    pub shortcuts: HashMap<String, KeyBinding>,
+
+    /// Click distance (px) within which a link cable is hit
+    #[serde(default = "default_link_hit_radius")]
+    pub link_hit_radius: f32,
+
+    /// Click distance (px) within which a port is hit
+    #[serde(default = "default_port_hit_radius")]
+    pub port_hit_radius: f32,
+
+    /// Presets applied automatically whenever their nodes appear
+    #[serde(default)]
+    pub auto_preset_paths: Vec<String>,
+
+    /// Preset files bound to the 1-9 hotkeys
+    #[serde(default)]
+    pub preset_slots: [Option<PathBuf>; 9],
+
+    /// File format for newly created presets
+    #[serde(default)]
+    pub preset_format: PresetFormat,
+
+    /// Changed since the last save (written out by a periodic SaveConfig)
+    #[serde(skip)]
+    pub dirty: bool,
+
+    /// Profile this config was loaded from (selects the file it saves to)
+    #[serde(skip)]
+    pub profile: String,
+
+    /// Modification time of the config file when this process last read
+    /// or wrote it, so the watcher can tell our own saves from outside edits
+    #[serde(skip)]
+    pub file_modified: Option<std::time::SystemTime>,
+
+    /// Connections kept alive by node/port name
+    #[serde(default)]
+    pub pinned_specs: Vec<PinnedSpec>,
+}
+
+/// Auto-layout algorithms
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LayoutAlgorithm {
+    /// Columns by BFS depth from the sources (best for simple chains)
+    #[default]
+    ColumnBfs,
+    /// Spring embedder (best for meshes with many cross-connections)
+    ForceDirected,
+}
+
+/// On-disk format of a preset file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PresetFormat {
+    #[default]
+    Json,
+    /// Easier to edit by hand: fewer quotes, and comments are allowed
+    Yaml,
+}
+
+impl PresetFormat {
+    /// The format a preset path's extension implies (JSON unless `.yaml`/`.yml`)
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml" | "yml") => PresetFormat::Yaml,
+            _ => PresetFormat::Json,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            PresetFormat::Json => "json",
+            PresetFormat::Yaml => "yaml",
+        }
+    }
+}
+
+/// Which way signal flows in the column layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LayoutDirection {
+    /// Sources on the left, sinks on the right
+    #[default]
+    Horizontal,
+    /// Sources at the top, sinks at the bottom
+    Vertical,
+}
+
+/// A key plus the modifiers held with it. `key` is a character ("l", "?")
+/// or a named key ("F1", "Home")
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyBinding {
+    pub key: String,
+    #[serde(default)]
+    pub ctrl: bool,
+    #[serde(default)]
+    pub shift: bool,
+    #[serde(default)]
+    pub alt: bool,
+}
+
+impl KeyBinding {
+    fn new(key: &str, ctrl: bool, shift: bool) -> Self {
+        Self { key: key.to_string(), ctrl, shift, alt: false }
+    }
+
+    /// Shown in the help overlay, e.g. "Ctrl+Shift+Z"
+    pub fn label(&self) -> String {
+        let mut label = String::new();
+        for (held, name) in [(self.ctrl, "Ctrl+"), (self.alt, "Alt+"), (self.shift, "Shift+")] {
+            if held {
+                label.push_str(name);
+            }
+        }
+        if self.key.chars().count() == 1 {
+            label.push_str(&self.key.to_uppercase());
+        } else {
+            label.push_str(&self.key);
+        }
+        label
+    }
+}
+
+/// Actions that can be rebound in `Config::shortcuts`
+pub const SHORTCUT_ACTIONS: [&str; 7] =
+    ["auto_layout", "zoom_fit", "search", "undo", "redo", "toggle_help", "toggle_minimap"];
+
+fn default_shortcuts() -> HashMap<String, KeyBinding> {
+    [
+        ("auto_layout", KeyBinding::new("l", false, false)),
+        ("zoom_fit", KeyBinding::new("0", true, false)),
+        ("search", KeyBinding::new("f", true, false)),
+        ("undo", KeyBinding::new("z", true, false)),
+        ("redo", KeyBinding::new("z", true, true)),
+        ("toggle_help", KeyBinding::new("?", false, false)),
+        ("toggle_minimap", KeyBinding::new("m", false, false)),
+    ]
+    .into_iter()
+    .map(|(action, binding)| (action.to_string(), binding))
+    .collect()
+}
+
+/// A pinned connection identified by names rather than (volatile) port IDs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PinnedSpec {
+    pub output_node_name: String,
+    pub output_port_name: String,
+    pub input_node_name: String,
+    pub input_port_name: String,
+}
+
+/// Profile used when none is given on the command line
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Current config file layout version
+const SCHEMA_VERSION: u32 = 1;
+
+/// Allowed range for the hit radius settings
+const HIT_RADIUS_RANGE: (f32, f32) = (2.0, 50.0);
+
+/// Allowed range for max_fps; above 1000 the frame interval rounds to 0ms
+const MAX_FPS_RANGE: (u32, u32) = (1, 1000);
+
+fn default_layout_col_width() -> f32 {
+    250.0
+}
+
+fn default_layout_row_gap() -> f32 {
+    25.0
+}
+
+fn default_layout_grid_snap() -> f32 {
+    50.0
+}
+
+fn default_layout_iterations() -> u32 {
+    200
+}
+
+fn default_spring_strength() -> f32 {
+    0.05
+}
+
+fn default_grid_size() -> f32 {
+    40.0
+}
+
+fn default_lod_zoom_threshold() -> f32 {
+    0.6
+}
+
+fn default_prefer_port_alias() -> bool {
+    true
+}
+
+fn default_max_visible_ports() -> usize {
+    20
+}
+
+fn default_max_fps() -> u32 {
+    60
+}
+
+fn default_node_grace_period_ms() -> u64 {
+    3000
+}
+
+fn default_undo_stack_limit() -> usize {
+    50
+}
+
+fn default_link_hit_radius() -> f32 {
+    8.0
+}
+
+fn default_port_hit_radius() -> f32 {
+    15.0
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            positions: HashMap::new(),
+            node_renames: HashMap::new(),
+            last_preset: None,
+            preset_last_applied: HashMap::new(),
+            exclusive_mode: false,
+            auto_pin: false,
+            alsa_midi_enabled: false,
+            device_profiles: HashMap::new(),
+            device_positions: HashMap::new(),
+            collapsed_nodes: HashSet::new(),
+            pinned_layout_nodes: HashSet::new(),
+            hidden_nodes: HashSet::new(),
+            layout_col_width: default_layout_col_width(),
+            layout_row_gap: default_layout_row_gap(),
+            layout_grid_snap: default_layout_grid_snap(),
+            layout_algorithm: LayoutAlgorithm::default(),
+            layout_direction: LayoutDirection::default(),
+            layout_iterations: default_layout_iterations(),
+            spring_strength: default_spring_strength(),
+            snap_to_grid: false,
+            grid_size: default_grid_size(),
+            lod_zoom_threshold: default_lod_zoom_threshold(),
+            prefer_port_alias: default_prefer_port_alias(),
+            hide_monitor_ports: false,
+            max_visible_ports: default_max_visible_ports(),
+            max_fps: default_max_fps(),
+            node_grace_period_ms: default_node_grace_period_ms(),
+            undo_stack_limit: default_undo_stack_limit(),
+            shortcuts: default_shortcuts(),
+            link_hit_radius: default_link_hit_radius(),
+            port_hit_radius: default_port_hit_radius(),
+            pinned_specs: Vec::new(),
+            auto_preset_paths: Vec::new(),
+            preset_slots: Default::default(),
+            preset_format: PresetFormat::default(),
+            dirty: false,
+            profile: DEFAULT_PROFILE.to_string(),
+            file_modified: None,
+        }
+    }
+}
+
+/// Unversioned configs already use v1's node keys and field names; fields
+/// added since are filled in by their serde defaults
+fn migrate_v0_to_v1(config: Config) -> Config {
+    config
 }
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
@@ -63,26 +419,84 @@ impl NodeKey {
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Position {
     pub x: f32,
     pub y: f32,
 }
 
 impl Config {
-    pub fn load() -> Option<Self> {
-        let path = Self::config_path()?;
+    pub fn load(profile: &str) -> Option<Self> {
+        let path = Self::config_path_for_profile(profile)?;
+        let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
         let contents = fs::read_to_string(&path).ok()?;
-        serde_json::from_str(&contents).ok()
+        let config: Self = serde_json::from_str(&contents).ok()?;
+        let outdated = config.schema_version < SCHEMA_VERSION;
+        let mut config = config.migrate();
+        config.profile = profile.to_string();
+        config.file_modified = modified;
+        if outdated {
+            let _ = config.save_atomic();
+        }
+        config.validate();
+        Some(config)
     }
 
-    pub fn save(&self) -> Option<()> {
-        let path = Self::config_path()?;
+    /// Bring a config read from an older file up to SCHEMA_VERSION
+    fn migrate(mut self) -> Self {
+        if self.schema_version == 0 {
+            self = migrate_v0_to_v1(self);
+        }
+        self.schema_version = SCHEMA_VERSION;
+        self
+    }
+
+    /// Load a profile's config, or start a fresh one for a new profile
+    pub fn load_or_default(profile: &str) -> Self {
+        Self::load(profile).unwrap_or_else(|| Self {
+            profile: profile.to_string(),
+            ..Self::default()
+        })
+    }
+
+    /// Clamp hand-edited settings into their supported ranges
+    fn validate(&mut self) {
+        let (min, max) = HIT_RADIUS_RANGE;
+        self.link_hit_radius = self.link_hit_radius.clamp(min, max);
+        self.port_hit_radius = self.port_hit_radius.clamp(min, max);
+        let (min, max) = MAX_FPS_RANGE;
+        self.max_fps = self.max_fps.clamp(min, max);
+    }
+
+    /// Save via a temp file and rename, keeping the previous config as
+    /// config.json.bak so a crash mid-write can't lose it
+    pub fn save_atomic(&mut self) -> Option<()> {
+        let path = Self::config_path_for_profile(&self.profile)?;
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent).ok()?;
         }
         let contents = serde_json::to_string_pretty(self).ok()?;
-        fs::write(&path, &contents).ok()
+
+        let backup = path.with_extension("json.bak");
+        if path.exists() {
+            fs::copy(&path, &backup).ok()?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, &contents).ok()?;
+        if fs::rename(&tmp, &path).is_err() {
+            let _ = fs::remove_file(&tmp);
+            if backup.exists() {
+                let _ = fs::copy(&backup, &path);
+            }
+            return None;
+        }
+        self.file_modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+        Some(())
+    }
+
+    /// Schedule a save; changes are batched and written every couple of seconds
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
     }
 
     pub fn get_position(&self, key: &NodeKey) -> Option<Position> {
@@ -91,12 +505,73 @@ impl Config {
 
     pub fn set_position(&mut self, key: NodeKey, pos: Position) {
         self.positions.insert(key.to_string_key(), pos);
-        let _ = self.save();
+        self.mark_dirty();
+    }
+
+    /// Update several node positions with a single save
+    pub fn set_positions(&mut self, positions: impl IntoIterator<Item = (NodeKey, Position)>) {
+        for (key, pos) in positions {
+            self.positions.insert(key.to_string_key(), pos);
+        }
+        self.mark_dirty();
+    }
+
+    /// Take positions and renames from a reloaded config, without
+    /// overwriting the ones already known in memory
+    pub fn merge(&mut self, other: Config) {
+        for (key, pos) in other.positions {
+            self.positions.entry(key).or_insert(pos);
+        }
+        for (key, name) in other.node_renames {
+            self.node_renames.entry(key).or_insert(name);
+        }
+    }
+
+    /// Config file for a profile: ~/.config/solder/<profile>/config.json.
+    /// The default profile keeps using ~/.config/solder/config.json so
+    /// existing setups carry over.
+    pub fn config_path_for_profile(profile: &str) -> Option<PathBuf> {
+        let dirs = ProjectDirs::from("", "", "solder")?;
+        if profile == DEFAULT_PROFILE {
+            Some(dirs.config_dir().join("config.json"))
+        } else {
+            Some(dirs.config_dir().join(profile).join("config.json"))
+        }
+    }
+
+    /// All profiles with a config file, default first
+    pub fn profiles() -> Vec<String> {
+        let mut profiles = vec![DEFAULT_PROFILE.to_string()];
+        let Some(dirs) = ProjectDirs::from("", "", "solder") else {
+            return profiles;
+        };
+        let Ok(entries) = fs::read_dir(dirs.config_dir()) else {
+            return profiles;
+        };
+        let mut named: Vec<String> = entries
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().join("config.json").is_file())
+            .filter_map(|e| e.file_name().into_string().ok())
+            .filter(|name| name != DEFAULT_PROFILE)
+            .collect();
+        named.sort();
+        profiles.extend(named);
+        profiles
+    }
+
+    /// Profile used the last time solder ran
+    pub fn last_profile() -> Option<String> {
+        let dirs = ProjectDirs::from("", "", "solder")?;
+        let name = fs::read_to_string(dirs.config_dir().join("last_profile")).ok()?;
+        let name = name.trim();
+        (!name.is_empty()).then(|| name.to_string())
     }
 
-    fn config_path() -> Option<PathBuf> {
+    /// Remember the active profile for the next start
+    pub fn set_last_profile(profile: &str) -> Option<()> {
         let dirs = ProjectDirs::from("", "", "solder")?;
-        Some(dirs.config_dir().join("config.json"))
+        fs::create_dir_all(dirs.config_dir()).ok()?;
+        fs::write(dirs.config_dir().join("last_profile"), profile).ok()
     }
 
     /// Get custom name for a node
@@ -104,16 +579,81 @@ impl Config {
         self.node_renames.get(&key.to_string_key())
     }
 
+    /// Every rebindable action's binding, falling back to the default for
+    /// actions the config file doesn't mention
+    pub fn resolved_shortcuts(&self) -> HashMap<String, KeyBinding> {
+        let mut shortcuts = default_shortcuts();
+        for (action, binding) in &self.shortcuts {
+            if SHORTCUT_ACTIONS.contains(&action.as_str()) {
+                shortcuts.insert(action.clone(), binding.clone());
+            }
+        }
+        shortcuts
+    }
+
     /// Set custom name for a node
     pub fn set_node_rename(&mut self, key: NodeKey, name: String) {
         self.node_renames.insert(key.to_string_key(), name);
-        let _ = self.save();
+        self.mark_dirty();
     }
 
     /// Clear custom name for a node
     pub fn clear_node_rename(&mut self, key: &NodeKey) {
         self.node_renames.remove(&key.to_string_key());
-        let _ = self.save();
+        self.mark_dirty();
+    }
+
+    /// Whether a node is collapsed to its header
+    pub fn is_node_collapsed(&self, key: &NodeKey) -> bool {
+        self.collapsed_nodes.contains(&key.to_string_key())
+    }
+
+    /// Set or clear a node's collapsed state
+    pub fn set_node_collapsed(&mut self, key: &NodeKey, collapsed: bool) {
+        if collapsed {
+            self.collapsed_nodes.insert(key.to_string_key());
+        } else {
+            self.collapsed_nodes.remove(&key.to_string_key());
+        }
+        self.mark_dirty();
+    }
+
+    /// Whether auto-layout should leave a node in place
+    pub fn is_layout_pinned(&self, key: &NodeKey) -> bool {
+        self.pinned_layout_nodes.contains(&key.to_string_key())
+    }
+
+    /// Set or clear a node's layout pin
+    pub fn set_layout_pinned(&mut self, key: &NodeKey, pinned: bool) {
+        if pinned {
+            self.pinned_layout_nodes.insert(key.to_string_key());
+        } else {
+            self.pinned_layout_nodes.remove(&key.to_string_key());
+        }
+        self.mark_dirty();
+    }
+
+    /// Whether a node is hidden from the canvas
+    pub fn is_node_hidden(&self, key: &NodeKey) -> bool {
+        self.hidden_nodes.contains(&key.to_string_key())
+    }
+
+    /// Hide or show a node
+    pub fn set_node_hidden(&mut self, key: &NodeKey, hidden: bool) {
+        if hidden {
+            self.hidden_nodes.insert(key.to_string_key());
+        } else {
+            self.hidden_nodes.remove(&key.to_string_key());
+        }
+        self.mark_dirty();
+    }
+
+    /// Show every hidden node again
+    pub fn clear_hidden_nodes(&mut self) {
+        if !self.hidden_nodes.is_empty() {
+            self.hidden_nodes.clear();
+            self.mark_dirty();
+        }
     }
 
     /// Get the presets directory path
@@ -130,7 +670,7 @@ impl Config {
     /// Set last-used profile index for a device
     pub fn set_device_profile(&mut self, device_name: String, profile_index: u32) {
         self.device_profiles.insert(device_name, profile_index);
-        let _ = self.save();
+        self.mark_dirty();
     }
 
     /// Get saved ghost node position for a device
@@ -138,9 +678,118 @@ impl Config {
         self.device_positions.get(device_name).copied()
     }
 
+    /// Toggle drag snapping to the grid
+    pub fn toggle_snap_to_grid(&mut self) {
+        self.snap_to_grid = !self.snap_to_grid;
+        self.mark_dirty();
+    }
+
+    /// Switch the column layout between left-to-right and top-to-bottom
+    pub fn toggle_layout_direction(&mut self) {
+        self.layout_direction = match self.layout_direction {
+            LayoutDirection::Horizontal => LayoutDirection::Vertical,
+            LayoutDirection::Vertical => LayoutDirection::Horizontal,
+        };
+        self.mark_dirty();
+    }
+
+    /// Replace the name-based pinned connections
+    pub fn set_pinned_specs(&mut self, specs: Vec<PinnedSpec>) {
+        self.pinned_specs = specs;
+        self.mark_dirty();
+    }
+
+    /// Record that the preset at `path` was just applied
+    pub fn mark_preset_applied(&mut self, path: &Path) {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.preset_last_applied.insert(crate::preset::preset_key(path), now);
+        self.mark_dirty();
+    }
+
+    /// Bind a preset file to hotkey slot `slot` (1-9)
+    pub fn set_preset_slot(&mut self, slot: usize, path: PathBuf) {
+        if let Some(entry) = slot.checked_sub(1).and_then(|i| self.preset_slots.get_mut(i)) {
+            *entry = Some(path);
+            self.mark_dirty();
+        }
+    }
+
     /// Set ghost node position for a device
     pub fn set_device_position(&mut self, device_name: String, pos: Position) {
         self.device_positions.insert(device_name, pos);
-        let _ = self.save();
+        self.mark_dirty();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A config as written before schema versioning
+    const V0_CONFIG: &str = r#"{
+  "positions": {
+    "path:alsa:pcm:0:front:0:playback": { "x": 600.0, "y": 50.0 },
+    "Firefox|Firefox": { "x": 50.0, "y": 120.0 },
+    "Firefox|Firefox#1": { "x": 50.0, "y": 240.0 }
+  },
+  "node_renames": { "Firefox|Firefox": "Browser" },
+  "last_preset": "/home/user/.config/solder/presets/studio.json",
+  "exclusive_mode": true,
+  "auto_pin": false,
+  "alsa_midi_enabled": false,
+  "device_profiles": { "alsa_card.pci-0000_00_1f.3": 2 },
+  "device_positions": { "alsa_card.usb-Focusrite": { "x": 50.0, "y": 400.0 } }
+}"#;
+
+    fn key(node_name: &str, app_name: Option<&str>, object_path: Option<&str>, index: u32) -> NodeKey {
+        NodeKey {
+            node_name: node_name.to_string(),
+            app_name: app_name.map(str::to_string),
+            object_path: object_path.map(str::to_string),
+            index: Some(index),
+        }
+    }
+
+    #[test]
+    fn test_migrate_v0() {
+        let config: Config = serde_json::from_str(V0_CONFIG).unwrap();
+        assert_eq!(config.schema_version, 0);
+        let config = config.migrate();
+
+        assert_eq!(config.schema_version, SCHEMA_VERSION);
+        let sink = key("Built-in Audio", None, Some("alsa:pcm:0:front:0:playback"), 0);
+        assert_eq!(config.get_position(&sink).map(|p| (p.x, p.y)), Some((600.0, 50.0)));
+        let first = key("Firefox", Some("Firefox"), None, 0);
+        let second = key("Firefox", Some("Firefox"), None, 1);
+        assert_eq!(config.get_position(&first).map(|p| p.y), Some(120.0));
+        assert_eq!(config.get_position(&second).map(|p| p.y), Some(240.0));
+        assert_eq!(config.get_node_rename(&first).map(String::as_str), Some("Browser"));
+        assert!(config.exclusive_mode);
+        assert_eq!(config.get_device_profile("alsa_card.pci-0000_00_1f.3"), Some(2));
+        assert!(config.get_device_position("alsa_card.usb-Focusrite").is_some());
+        // Settings added after v0 take their defaults
+        assert_eq!(config.layout_col_width, default_layout_col_width());
+        assert_eq!(config.max_fps, default_max_fps());
+    }
+
+    #[test]
+    fn test_preset_applied_key_ignores_spelling() {
+        let dir = std::env::temp_dir().join(format!("solder-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("studio.json");
+        crate::preset::Preset::new("Studio").save(&path).unwrap();
+        crate::preset::Preset::new("Another").save(&dir.join("another.json")).unwrap();
+
+        // As an IPC client might spell it
+        let mut config = Config::default();
+        config.mark_preset_applied(&dir.join(".").join("studio.json"));
+        let listed = crate::preset::list_presets(&dir, &config.preset_last_applied);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let names: Vec<&str> = listed.iter().map(|(preset, _)| preset.name.as_str()).collect();
+        assert_eq!(names, ["Studio", "Another"]);
     }
 }
@@ -0,0 +1,607 @@
+use iced::{Color, Point};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use crate::preset::{NodeMatcher, Preset, PresetConnection};
+use crate::graph::{
+    link_control_points, palette, Graph, Link, Node, NodeCategory, PortDirection, PortType, PortWindow,
+    NODE_WIDTH, PORT_RADIUS,
+};
+
+/// Blank space around the graph in exported images
+const SVG_MARGIN: f32 = 20.0;
+
+/// Pick a fresh ~/Pictures/solder-<timestamp>.<ext> path
+fn export_path(extension: &str) -> Option<PathBuf> {
+    let dirs = directories::UserDirs::new()?;
+    let dir = dirs.picture_dir().unwrap_or(dirs.home_dir());
+    std::fs::create_dir_all(dir).ok()?;
+
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?
+        .as_secs();
+    Some(dir.join(format!("solder-{}.{}", timestamp, extension)))
+}
+
+/// Write a screenshot to ~/Pictures/solder-<timestamp>.png
+pub fn save_png(rgba: &[u8], width: u32, height: u32) -> Option<PathBuf> {
+    let path = export_path("png")?;
+    let file = std::fs::File::create(&path).ok()?;
+    let mut encoder = png::Encoder::new(std::io::BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().ok()?;
+    writer.write_image_data(rgba).ok()?;
+    Some(path)
+}
+
+/// Write an SVG document to ~/Pictures/solder-<timestamp>.svg
+pub fn save_svg(svg: &str) -> Option<PathBuf> {
+    let path = export_path("svg")?;
+    std::fs::write(&path, svg).ok()?;
+    Some(path)
+}
+
+/// Render the graph as an SVG 1.1 document in world coordinates.
+/// `zoom` only sets the document's display size; the viewBox is unscaled.
+pub fn export_svg(nodes: &HashMap<u32, Node>, links: &[Link], zoom: f32) -> String {
+    // Bounding box of all nodes
+    let (min, max) = nodes.values().fold(
+        (Point::new(f32::MAX, f32::MAX), Point::new(f32::MIN, f32::MIN)),
+        |(min, max), node| {
+            let height = Graph::node_height(node, PortWindow::ALL);
+            (
+                Point::new(min.x.min(node.position.x), min.y.min(node.position.y)),
+                Point::new(max.x.max(node.position.x + NODE_WIDTH), max.y.max(node.position.y + height)),
+            )
+        },
+    );
+    let (min, max) = if nodes.is_empty() {
+        (Point::ORIGIN, Point::ORIGIN)
+    } else {
+        (min, max)
+    };
+    let view_x = min.x - SVG_MARGIN;
+    let view_y = min.y - SVG_MARGIN;
+    let view_w = max.x - min.x + SVG_MARGIN * 2.0;
+    let view_h = max.y - min.y + SVG_MARGIN * 2.0;
+
+    let mut svg = String::new();
+    let _ = writeln!(svg, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" version="1.1" width="{:.0}" height="{:.0}" viewBox="{:.1} {:.1} {:.1} {:.1}">"#,
+        view_w * zoom, view_h * zoom, view_x, view_y, view_w, view_h,
+    );
+    let _ = writeln!(
+        svg,
+        r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}"/>"#,
+        view_x, view_y, view_w, view_h, hex(Color::from_rgb(0.075, 0.075, 0.085)),
+    );
+
+    // Links first so nodes sit on top of the cable ends
+    for link in links {
+        let out = nodes.get(&link.output_node)
+            .and_then(|n| n.output_ports.iter().find(|p| p.id == link.output_port).map(|p| (n, p)));
+        let inp = nodes.get(&link.input_node)
+            .and_then(|n| n.input_ports.iter().find(|p| p.id == link.input_port).map(|p| (n, p)));
+        let (Some((out_node, out_port)), Some((in_node, in_port))) = (out, inp) else {
+            continue;
+        };
+        let start = Graph::port_position(out_node, out_port, PortWindow::ALL);
+        let end = Graph::port_position(in_node, in_port, PortWindow::ALL);
+        let (c1, c2) = link_control_points(start, end);
+        let _ = writeln!(
+            svg,
+            r#"<path d="M{:.1},{:.1} C{:.1},{:.1} {:.1},{:.1} {:.1},{:.1}" fill="none" stroke="{}" stroke-width="2.5" stroke-linecap="round"/>"#,
+            start.x, start.y, c1.x, c1.y, c2.x, c2.y, end.x, end.y, hex(port_color(out_port.port_type)),
+        );
+    }
+
+    for node in nodes.values() {
+        let Point { x, y } = node.position;
+        let height = Graph::node_height(node, PortWindow::ALL);
+        let name = node.custom_name.as_ref().unwrap_or(&node.name);
+
+        let _ = writeln!(svg, r#"<g id="node-{}">"#, node.id);
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" rx="8" fill="{}"/>"#,
+            x, y, NODE_WIDTH, height, hex(palette::NODE_BG),
+        );
+        // Header: rounded on top, squared off where it meets the body
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" rx="8" fill="{}"/>"#,
+            x, y, NODE_WIDTH, Graph::node_header_height(node), hex(palette::NODE_HEADER),
+        );
+        if !node.collapsed {
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="8" fill="{}"/>"#,
+                x, y + Graph::node_header_height(node) - 8.0, NODE_WIDTH, hex(palette::NODE_HEADER),
+            );
+        }
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" rx="8" fill="none" stroke="{}" stroke-width="1"/>"#,
+            x, y, NODE_WIDTH, height, hex(palette::NODE_BORDER),
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" font-family="sans-serif" font-size="13" fill="{}">{}</text>"#,
+            x + 12.0, y + 19.0, hex(palette::TEXT_PRIMARY), escape(name),
+        );
+        if let Some(subtitle) = Graph::subtitle(node) {
+            let _ = writeln!(
+                svg,
+                r#"<text x="{:.1}" y="{:.1}" font-family="sans-serif" font-size="10" fill="{}">{}</text>"#,
+                x + 12.0, y + 33.0, hex(palette::TEXT_SECONDARY), escape(&subtitle),
+            );
+        }
+
+        if !node.collapsed {
+            for port in node.input_ports.iter().chain(node.output_ports.iter()) {
+                let pos = Graph::port_position(node, port, PortWindow::ALL);
+                let _ = writeln!(
+                    svg,
+                    r#"<circle cx="{:.1}" cy="{:.1}" r="{:.1}" fill="{}"/>"#,
+                    pos.x, pos.y, PORT_RADIUS, hex(port_color(port.port_type)),
+                );
+                let (label_x, anchor) = match port.direction {
+                    PortDirection::Input => (pos.x + PORT_RADIUS + 6.0, "start"),
+                    PortDirection::Output => (pos.x - PORT_RADIUS - 6.0, "end"),
+                };
+                let _ = writeln!(
+                    svg,
+                    r#"<text x="{:.1}" y="{:.1}" font-family="sans-serif" font-size="10" text-anchor="{}" fill="{}">{}</text>"#,
+                    label_x, pos.y + 3.5, anchor, hex(palette::TEXT_SECONDARY), escape(&port.name),
+                );
+            }
+        }
+        let _ = writeln!(svg, "</g>");
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Write a shell script and mark it executable
+pub fn save_script(path: &Path, script: &str) -> Option<()> {
+    std::fs::write(path, script).ok()?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755)).ok()
+}
+
+/// Render the live graph as a Graphviz digraph. Parallel links between the
+/// same two nodes collapse into one edge per port type.
+pub fn graph_to_dot(nodes: &HashMap<u32, Node>, links: &[Link]) -> String {
+    let mut ids: Vec<u32> = nodes.keys().copied().collect();
+    ids.sort_unstable();
+
+    let mut dot = String::new();
+    let _ = writeln!(dot, "digraph solder {{");
+    let _ = writeln!(dot, "    rankdir=LR;");
+    for id in &ids {
+        let node = &nodes[id];
+        let color = NodeCategory::from_node(node).map(|c| c.color()).unwrap_or(palette::NODE_BORDER);
+        let _ = writeln!(
+            dot,
+            "    n{} [label=\"{}\", color=\"{}\", shape=box];",
+            id,
+            dot_escape(node.custom_name.as_ref().unwrap_or(&node.name)),
+            hex(color),
+        );
+    }
+
+    // (output node, input node, type) -> link count, in a stable order
+    let mut edges: Vec<((u32, u32, &'static str), usize)> = Vec::new();
+    for link in links.iter().filter(|l| nodes.contains_key(&l.output_node) && nodes.contains_key(&l.input_node)) {
+        let port_type = nodes[&link.output_node].output_ports.iter()
+            .find(|p| p.id == link.output_port)
+            .map(|p| p.port_type)
+            .unwrap_or(PortType::Audio);
+        let key = (link.output_node, link.input_node, port_type_name(port_type));
+        match edges.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, count)) => *count += 1,
+            None => edges.push((key, 1)),
+        }
+    }
+    edges.sort_unstable_by_key(|&((out, inp, _), _)| (out, inp));
+    for ((out, inp, type_name), count) in &edges {
+        let label = if *count > 1 { format!("{} ×{}", type_name, count) } else { type_name.to_string() };
+        let _ = writeln!(dot, "    n{} -> n{} [label=\"{}\"];", out, inp, label);
+    }
+
+    // Unconnected nodes share a rank instead of scattering
+    let isolated: Vec<String> = ids.iter()
+        .filter(|&&id| !links.iter().any(|l| l.output_node == id || l.input_node == id))
+        .map(|id| format!("n{};", id))
+        .collect();
+    if !isolated.is_empty() {
+        let _ = writeln!(dot, "    {{ rank=same; {} }}", isolated.join(" "));
+    }
+    let _ = writeln!(dot, "}}");
+    dot
+}
+
+/// Render the live graph as a Mermaid `graph LR`, one box per node grouped
+/// into a subgraph per application. Parallel links collapse into one arrow.
+pub fn graph_to_mermaid(nodes: &HashMap<u32, Node>, links: &[Link]) -> String {
+    let mut mermaid = mermaid_nodes(nodes);
+    let mut edges: Vec<(u32, u32)> = links.iter()
+        .filter(|l| nodes.contains_key(&l.output_node) && nodes.contains_key(&l.input_node))
+        .map(|l| (l.output_node, l.input_node))
+        .collect();
+    edges.sort_unstable();
+    edges.dedup();
+    for (out, inp) in edges {
+        let _ = writeln!(mermaid, "    n{}-->n{}", out, inp);
+    }
+    mermaid
+}
+
+/// Like `graph_to_mermaid`, but with one arrow per link labelled with its ports
+pub fn graph_to_mermaid_flowchart(nodes: &HashMap<u32, Node>, links: &[Link]) -> String {
+    let mut mermaid = mermaid_nodes(nodes);
+    let port_name = |node_id: u32, port_id: u32| {
+        nodes.get(&node_id)
+            .and_then(|n| n.output_ports.iter().chain(n.input_ports.iter()).find(|p| p.id == port_id))
+            .map(|p| p.name.as_str())
+    };
+    let mut sorted: Vec<&Link> = links.iter().collect();
+    sorted.sort_unstable_by_key(|l| (l.output_node, l.input_node, l.output_port, l.input_port));
+    for link in sorted {
+        if let (Some(out), Some(inp)) = (port_name(link.output_node, link.output_port), port_name(link.input_node, link.input_port)) {
+            let label = mermaid_escape(&format!("{} → {}", out, inp));
+            let _ = writeln!(mermaid, "    n{}-->|\"{}\"|n{}", link.output_node, label, link.input_node);
+        }
+    }
+    mermaid
+}
+
+/// The diagram header and node boxes shared by both Mermaid exports
+fn mermaid_nodes(nodes: &HashMap<u32, Node>) -> String {
+    let mut ids: Vec<u32> = nodes.keys().copied().collect();
+    ids.sort_unstable();
+    // Application -> its nodes, in order of first appearance
+    let mut groups: Vec<(Option<&str>, Vec<u32>)> = Vec::new();
+    for id in ids {
+        let app = nodes[&id].app_name.as_deref();
+        match groups.iter_mut().find(|(a, _)| *a == app) {
+            Some((_, members)) => members.push(id),
+            None => groups.push((app, vec![id])),
+        }
+    }
+
+    let mut mermaid = String::new();
+    let _ = writeln!(mermaid, "graph LR");
+    let label = |id: u32| {
+        let node = &nodes[&id];
+        mermaid_escape(node.custom_name.as_ref().unwrap_or(&node.name))
+    };
+    for (index, (app, members)) in groups.iter().enumerate() {
+        match app {
+            Some(app) => {
+                let _ = writeln!(mermaid, "    subgraph app{}[\"{}\"]", index, mermaid_escape(app));
+                for &id in members {
+                    let _ = writeln!(mermaid, "        n{}[\"{}\"]", id, label(id));
+                }
+                let _ = writeln!(mermaid, "    end");
+            }
+            None => {
+                for &id in members {
+                    let _ = writeln!(mermaid, "    n{}[\"{}\"]", id, label(id));
+                }
+            }
+        }
+    }
+    mermaid
+}
+
+/// Write both Mermaid diagrams into a Markdown file, fenced so GitHub renders them
+pub fn save_mermaid(path: &Path, nodes: &HashMap<u32, Node>, links: &[Link]) -> std::io::Result<()> {
+    let markdown = format!(
+        "# Audio routing\n\n```mermaid\n{}```\n\n## Port connections\n\n```mermaid\n{}```\n",
+        graph_to_mermaid(nodes, links),
+        graph_to_mermaid_flowchart(nodes, links),
+    );
+    std::fs::write(path, markdown)
+}
+
+/// Write a DOT file, and a PNG beside it in the background if Graphviz is installed
+pub fn save_dot(path: &Path, dot: &str) -> Option<()> {
+    std::fs::write(path, dot).ok()?;
+    let (dot_path, png_path) = (path.to_path_buf(), path.with_extension("png"));
+    std::thread::spawn(move || {
+        let _ = std::process::Command::new("dot")
+            .arg("-Tpng")
+            .arg(&dot_path)
+            .arg("-o")
+            .arg(&png_path)
+            .output();
+    });
+    Some(())
+}
+
+/// A `pw-link` script recreating the current connections by PipeWire
+/// node.name and port name; the display name shown on the canvas is often a
+/// description pw-link can't resolve
+pub fn live_connections_to_script(nodes: &HashMap<u32, Node>, links: &[Link]) -> String {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+
+    let mut script = String::new();
+    let _ = writeln!(script, "#!/bin/sh");
+    let _ = writeln!(script, "# Generated by solder {} (unix time)", timestamp);
+    for link in links {
+        let out = nodes.get(&link.output_node)
+            .and_then(|n| n.output_ports.iter().find(|p| p.id == link.output_port).map(|p| (n, p)));
+        let inp = nodes.get(&link.input_node)
+            .and_then(|n| n.input_ports.iter().find(|p| p.id == link.input_port).map(|p| (n, p)));
+        match (out, inp) {
+            (Some((out_node, out_port)), Some((in_node, in_port))) => {
+                let _ = writeln!(
+                    script,
+                    "pw-link \"{}:{}\" \"{}:{}\"",
+                    shell_escape(out_node.node_name.as_deref().unwrap_or(&out_node.name)), shell_escape(&out_port.name),
+                    shell_escape(in_node.node_name.as_deref().unwrap_or(&in_node.name)), shell_escape(&in_port.name),
+                );
+            }
+            _ => {
+                // pw-link also accepts port IDs, though they won't survive a restart
+                let _ = writeln!(script, "# WARNING: node not found");
+                let _ = writeln!(script, "# pw-link {} {}", link.output_port, link.input_port);
+            }
+        }
+    }
+    script
+}
+
+/// Lua run by WirePlumber alongside the generated rule table: it links each
+/// rule's ports whenever both exist and aren't linked yet
+const WIREPLUMBER_LINKER: &str = r#"
+nodes_om = ObjectManager { Interest { type = "node" } }
+ports_om = ObjectManager { Interest { type = "port" } }
+links_om = ObjectManager { Interest { type = "link" } }
+
+-- solder names nodes by description, falling back to nick and node.name
+local function node_matches(node, name)
+  local props = node.properties
+  return props["node.description"] == name
+    or props["node.nick"] == name
+    or props["node.name"] == name
+end
+
+local function find_port(node_name, port_name, direction)
+  for node in nodes_om:iterate() do
+    if node_matches(node, node_name) then
+      local port = ports_om:lookup {
+        Constraint { "node.id", "=", tostring(node["bound-id"]) },
+        Constraint { "port.name", "=", port_name },
+        Constraint { "port.direction", "=", direction },
+      }
+      if port then
+        return port
+      end
+    end
+  end
+end
+
+local function linked(out_port, in_port)
+  return links_om:lookup {
+    Constraint { "link.output.port", "=", tostring(out_port["bound-id"]) },
+    Constraint { "link.input.port", "=", tostring(in_port["bound-id"]) },
+  } ~= nil
+end
+
+-- Keep the link proxies alive
+local created = {}
+
+local function apply_rules()
+  for _, rule in ipairs(rules) do
+    local out_port = find_port(rule.output_node, rule.output_port, "out")
+    local in_port = find_port(rule.input_node, rule.input_port, "in")
+    if out_port and in_port and not linked(out_port, in_port) then
+      local link = Link("link-factory", {
+        ["link.output.node"] = out_port.properties["node.id"],
+        ["link.output.port"] = tostring(out_port["bound-id"]),
+        ["link.input.node"] = in_port.properties["node.id"],
+        ["link.input.port"] = tostring(in_port["bound-id"]),
+        ["object.linger"] = true,
+      })
+      link:activate(Feature.Proxy.BOUND)
+      table.insert(created, link)
+    end
+  end
+end
+
+ports_om:connect("object-added", apply_rules)
+links_om:connect("object-removed", apply_rules)
+nodes_om:activate()
+links_om:activate()
+ports_om:activate()
+"#;
+
+/// Render a preset as a WirePlumber (0.4+) Lua script that keeps its
+/// connections made while solder isn't running. Nodes are matched by exact
+/// name; regex matchers have no Lua equivalent and are left as comments.
+pub fn preset_to_wireplumber_lua(preset: &Preset) -> String {
+    let mut lua = String::new();
+    let _ = writeln!(lua, "-- Generated by solder from preset \"{}\"", lua_escape(&preset.name));
+    let _ = writeln!(lua, "-- Copy to ~/.local/share/wireplumber/scripts/ and load it from your WirePlumber config");
+    let _ = writeln!(lua);
+    let _ = writeln!(lua, "rules = {{");
+    for conn in &preset.connections {
+        if conn.output_node.use_regex || conn.input_node.use_regex {
+            let _ = writeln!(
+                lua,
+                "  -- skipped (regex): {}:{} -> {}:{}",
+                conn.output_node.name, conn.output_port, conn.input_node.name, conn.input_port,
+            );
+            continue;
+        }
+        let _ = writeln!(
+            lua,
+            "  {{ output_node = \"{}\", output_port = \"{}\", input_node = \"{}\", input_port = \"{}\" }},",
+            lua_escape(&conn.output_node.name), lua_escape(&conn.output_port),
+            lua_escape(&conn.input_node.name), lua_escape(&conn.input_port),
+        );
+    }
+    let _ = writeln!(lua, "}}");
+    lua.push_str(WIREPLUMBER_LINKER);
+    lua
+}
+
+/// Render a preset as a qpwgraph patchbay (`.qpwgraph`). qpwgraph also
+/// records each port's type, which is looked up in the live graph.
+pub fn preset_to_qpwgraph_xml(preset: &Preset, nodes: &HashMap<u32, Node>) -> String {
+    let port_type = |conn: &PresetConnection| {
+        nodes.values()
+            .filter(|n| conn.output_node.matches(&n.name, n.app_name.as_deref(), n.object_path.as_deref()))
+            .find_map(|n| n.output_ports.iter().find(|p| p.name == conn.output_port))
+            .map(|p| p.port_type)
+            .unwrap_or(PortType::Audio)
+    };
+
+    let mut xml = String::new();
+    let _ = writeln!(xml, "<!DOCTYPE patchbay>");
+    let _ = writeln!(xml, r#"<patchbay name="{}">"#, escape(&preset.name));
+    let _ = writeln!(xml, " <items>");
+    for conn in &preset.connections {
+        let type_name = match port_type(conn) {
+            PortType::Audio => "pipewire-audio",
+            PortType::Midi => "pipewire-midi",
+            PortType::Video => "pipewire-video",
+        };
+        let _ = writeln!(xml, r#"  <item node-type="pipewire" port-type="{}">"#, type_name);
+        let _ = writeln!(
+            xml,
+            r#"   <output node="{}" port="{}"/>"#,
+            escape(&conn.output_node.name), escape(&conn.output_port),
+        );
+        let _ = writeln!(
+            xml,
+            r#"   <input node="{}" port="{}"/>"#,
+            escape(&conn.input_node.name), escape(&conn.input_port),
+        );
+        let _ = writeln!(xml, "  </item>");
+    }
+    let _ = writeln!(xml, " </items>");
+    let _ = writeln!(xml, "</patchbay>");
+    xml
+}
+
+/// Parse a qpwgraph patchbay into a preset. Items missing either end are skipped.
+pub fn preset_from_qpwgraph_xml(xml: &str) -> Option<Preset> {
+    use quick_xml::events::Event;
+
+    let mut reader = quick_xml::Reader::from_str(xml);
+    let mut preset = Preset::new("qpwgraph import");
+    let mut output: Option<(String, String)> = None;
+    let mut input: Option<(String, String)> = None;
+    let mut seen_root = false;
+
+    let endpoint = |e: &quick_xml::events::BytesStart| -> Option<(String, String)> {
+        let node = e.try_get_attribute("node").ok()??.unescape_value().ok()?.into_owned();
+        let port = e.try_get_attribute("port").ok()??.unescape_value().ok()?.into_owned();
+        Some((node, port))
+    };
+
+    loop {
+        match reader.read_event().ok()? {
+            Event::Start(e) | Event::Empty(e) => match e.name().as_ref() {
+                b"patchbay" => {
+                    seen_root = true;
+                    if let Ok(Some(name)) = e.try_get_attribute("name")
+                        && let Ok(name) = name.unescape_value()
+                        && !name.is_empty()
+                    {
+                        preset.name = name.into_owned();
+                    }
+                }
+                b"item" => {
+                    output = None;
+                    input = None;
+                }
+                b"output" => output = endpoint(&e),
+                b"input" => input = endpoint(&e),
+                _ => {}
+            },
+            Event::End(e) if e.name().as_ref() == b"item" => {
+                if let (Some((out_node, out_port)), Some((in_node, in_port))) = (output.take(), input.take()) {
+                    preset.connections.push(PresetConnection {
+                        output_node: NodeMatcher::new(out_node),
+                        output_port: out_port,
+                        input_node: NodeMatcher::new(in_node),
+                        input_port: in_port,
+                        pinned: false,
+                    });
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    seen_root.then_some(preset)
+}
+
+fn port_color(port_type: PortType) -> Color {
+    match port_type {
+        PortType::Audio => palette::PORT_AUDIO,
+        PortType::Midi => palette::PORT_MIDI,
+        PortType::Video => palette::PORT_VIDEO,
+    }
+}
+
+/// `#rrggbb` for an opaque color (SVG 1.1 has no alpha in color values)
+fn hex(color: Color) -> String {
+    let [r, g, b, _] = color.into_rgba8();
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+fn port_type_name(port_type: PortType) -> &'static str {
+    match port_type {
+        PortType::Audio => "audio",
+        PortType::Midi => "midi",
+        PortType::Video => "video",
+    }
+}
+
+/// Escape a string for use inside a DOT quoted identifier
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Escape a string for use inside a quoted Mermaid label
+fn mermaid_escape(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+/// Escape a string for use inside a double-quoted Lua string
+fn lua_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Escape a string for use inside double quotes in sh
+fn shell_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '"' | '\\' | '$' | '`') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -1,15 +1,20 @@
 use iced::mouse;
 use iced::widget::canvas::{self, Cache, Frame, Geometry, Path, Stroke, Text};
 use iced::{Color, Point, Rectangle, Size, Task, Vector};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 
-use crate::config::{Config, NodeKey, Position};
+use crate::config::{Config, KeyBinding, LayoutAlgorithm, LayoutDirection, NodeKey, PinnedSpec, Position};
 use crate::layout;
-use crate::pipewire_client::PipewireEvent;
+use crate::pipewire_client::{LinkState, NodeState, PipewireEvent};
+use crate::quadtree::QuadTree;
+use crate::spatial::SpatialItem;
+use crate::toast::Toast;
 use crate::Message;
 
 pub const NODE_WIDTH: f32 = 180.0;
 pub const NODE_HEADER_HEIGHT: f32 = 28.0;
+/// Extra header height for the app name line under the title
+const NODE_SUBTITLE_HEIGHT: f32 = 12.0;
 pub const PORT_HEIGHT: f32 = 22.0;
 pub const PORT_RADIUS: f32 = 6.0;
 pub const PORT_SPACING: f32 = 4.0;
@@ -19,6 +24,7 @@ pub const GHOST_NODE_HEIGHT: f32 = 52.0;
 pub enum GraphMessage {
     NodeDragged { node_id: u32, delta: Vector },
     NodeDragEnded { node_id: u32 },
+    MultiNodeDragEnded { node_ids: Vec<u32> },
     ConnectionStarted { node_id: u32, port_id: u32 },
     ConnectionEnded {
         from_node: u32,
@@ -27,12 +33,40 @@ pub enum GraphMessage {
         to_port: u32
     },
     ConnectionCancelled,
+    /// pw-link finished; `undo_pushed` means the request's undo entry is on top of the stack
+    ConnectionResult { success: bool, output_port: u32, input_port: u32, error: Option<String>, undo_pushed: bool },
+    /// A connect request for a pair that's already linked, never sent to pw-link
+    DuplicateLinkSkipped { output_port: u32, input_port: u32, undo_pushed: bool },
     DisconnectLink { link_id: u32, output_port: u32, input_port: u32 },
+    ReconnectLink { old_output: u32, old_input: u32, new_output: u32, new_input: u32 },
     Pan(Vector),
     Zoom { delta: f32, cursor: Point },
+    ZoomToFit { viewport: Size },
+    ToggleNodeCollapse { node_id: u32 },
+    FocusNode { node_id: u32, viewport: Size },
+    // Inline rename
+    StartRename { node_id: u32 },
+    RenameInput { text: String },
+    RenameBackspace,
+    CommitRename,
+    CancelRename,
+    ClearFocus,
+    HoverChanged { node_id: Option<u32>, port: Option<(u32, u32)> },
+    ToggleGridSnap,
+    ToggleMinimap,
+    SetPortTypeFilter(Option<PortType>),
+    SetMediaClassFilter(Option<MediaClass>),
+    CenterOn { world: Point, viewport: Size },
     AutoLayout,
+    ForceLayout,
+    ToggleLayoutDirection,
+    IncrementalLayout,
+    AnimationTick,
     Undo,
     Redo,
+    /// Undo this many actions at once, from the history panel
+    UndoSteps(usize),
+    ToggleHistoryPanel,
     ToggleHelp,
     // Search
     SearchActivate,
@@ -40,14 +74,94 @@ pub enum GraphMessage {
     SearchBackspace,
     SearchClear,
     SearchCommit,
+    SearchPrev,
     // Device activation/deactivation
     DeactivateDevice { device_id: u32 },
     ShowProfilePicker { ghost_index: usize },
     ActivateDeviceProfile { device_id: u32, device_name: String, profile_index: u32 },
     DismissProfilePicker,
     ProfilesLoaded { device_id: u32, profiles: Vec<DeviceProfile> },
+    /// Fetch a device's profiles, then list them in a context menu
+    LoadDeviceProfiles { device_id: u32 },
+    SetDeviceProfile { device_id: u32, profile_index: u32 },
     GhostDragged { ghost_index: usize, delta: Vector },
     GhostDragEnded { ghost_index: usize },
+    // Stereo pair prompt
+    ConfirmStereoPair { second_output: u32, second_input: u32 },
+    DismissStereoPair,
+    // Preset delta overlay
+    ShowPresetDelta,
+    SavePresetVersion,
+    OpenPresetPicker,
+    ClosePresetPicker,
+    /// Move the preset picker's highlight by this many rows
+    PresetPickerMove(i32),
+    PresetPickerSelect(usize),
+    PresetPickerApply,
+    /// Ask to delete the highlighted preset, or delete it if already asked
+    PresetPickerDelete,
+    PresetPickerNew,
+    PresetPickerBrowse,
+    PresetPickerPreview,
+    /// Show what applying a preset would change, without applying it
+    PreviewPreset { path: std::path::PathBuf },
+    ApplyPresetPreview,
+    CancelPresetPreview,
+    ApplyPreset { path: std::path::PathBuf },
+    LoadPresetSlot(usize),
+    AssignPresetSlot(usize),
+    // Selection
+    SelectInRect { from: Point, to: Point },
+    ClearSelection,
+    SelectAll,
+    DisconnectSelected,
+    BatchConnect { output_node: u32, input_node: u32 },
+    DisconnectNode { node_id: u32 },
+    /// Remove every link (undoable as one step)
+    DisconnectAll,
+    ToggleLayoutPin { node_id: u32 },
+    ToggleNodeHidden { node_id: u32 },
+    /// Make a sink the session's default output via wpctl
+    SetDefaultSink { node_id: u32 },
+    /// Set a node's volume via wpctl (1.0 = 100%)
+    SetNodeVolume { node_id: u32, volume: f32 },
+    ToggleMute { node_id: u32 },
+    /// Move a node's port lists by `rows` (negative shows earlier ports)
+    ScrollPorts { node_id: u32, rows: i32 },
+    ShowAllNodes,
+    // Pinned connections
+    PinLink { output_port: u32, input_port: u32 },
+    UnpinLink { output_port: u32, input_port: u32 },
+    ReconnectPinned,
+    ShowError(String),
+    // Export
+    // The screenshot is cropped to the canvas, `viewport` in logical pixels
+    ExportPng { viewport: Size },
+    ScreenshotTaken { shot: iced::window::Screenshot, viewport: Size },
+    ExportSvg,
+    SwitchProfile(String),
+    ExportScript,
+    SaveScript { path: std::path::PathBuf },
+    PickQpwgraphExport,
+    ExportQpwgraph { path: std::path::PathBuf },
+    PickQpwgraphImport,
+    ImportQpwgraph { path: std::path::PathBuf },
+    PickDotExport,
+    ExportDot { path: std::path::PathBuf },
+    PickWireplumberExport,
+    ExportWireplumberLua { path: std::path::PathBuf },
+    ExportMermaid,
+    SaveMermaid { path: std::path::PathBuf },
+    /// Save the loaded preset (or the live links) as a YAML preset
+    ExportPresetYaml,
+    SavePresetYaml { path: std::path::PathBuf },
+    // Canvas context menu
+    ShowContextMenu { position: Point, target: ContextMenuTarget, viewport: Size },
+    ContextMenuSelect { index: usize },
+    DismissContextMenu,
+    Tick,
+    /// Finish removals that no NodeAdded has reclaimed
+    PendingRemovalTick,
 }
 
 #[derive(Debug, Clone)]
@@ -64,25 +178,125 @@ pub struct Node {
     pub output_ports: Vec<Port>,
     /// Custom display name (from config or rename)
     pub custom_name: Option<String>,
-    /// Node source (PipeWire or ALSA MIDI)
-    pub source: NodeSource,
     /// Parent device ID (if this node belongs to a hardware device)
     pub device_id: Option<u32>,
+    /// PipeWire media.class, e.g. "Audio/Sink" or "Stream/Output/Audio"
+    pub media_class: Option<String>,
+    /// Processing state from the node's info events
+    pub state: NodeState,
+    /// Raw PipeWire node.name, which the default metadata refers to
+    pub node_name: Option<String>,
+    /// The client that created the node
+    pub client_id: Option<u32>,
+    /// The session's default sink or source
+    pub is_default: bool,
+    /// Volume from the node's Props (1.0 = 100%), if it has one
+    pub volume: Option<f32>,
+    pub muted: bool,
+    /// Collapsed to a header-only view
+    pub collapsed: bool,
+    /// Left in place by auto-layout
+    pub layout_pinned: bool,
+    /// Left out of the canvas; links are untouched
+    pub hidden: bool,
+    /// Where a layout animation is moving the node
+    pub target_position: Option<Point>,
+    /// Where the current drag picked the node up
+    pub drag_start_position: Option<Point>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Default)]
-pub enum NodeSource {
-    #[default]
-    PipeWire,
-    AlsaMidi,
+/// Which of a node's ports are on the canvas when a side has more than fit.
+/// One offset scrolls both sides; each side clamps it to its own length.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PortWindow {
+    /// Index of the first visible port
+    pub offset: usize,
+    /// Ports shown per side
+    pub max_visible: usize,
+}
+
+impl PortWindow {
+    /// Every port, for exports
+    pub const ALL: Self = Self { offset: 0, max_visible: usize::MAX };
+
+    fn overflows(&self, count: usize) -> bool {
+        count > self.max_visible
+    }
+
+    /// The offset clamped so a side of `count` ports stays full
+    fn side_offset(&self, count: usize) -> usize {
+        self.offset.min(count.saturating_sub(self.max_visible))
+    }
+
+    /// Rows taken by a side of `count` ports, including the two "hidden" rows
+    fn rows(&self, count: usize) -> usize {
+        if self.overflows(count) { self.max_visible + 2 } else { count }
+    }
+
+    /// Row of the port at `index`; scrolled-out ports share the "hidden" rows
+    fn row(&self, index: usize, count: usize) -> usize {
+        if !self.overflows(count) {
+            return index;
+        }
+        let offset = self.side_offset(count);
+        if index < offset {
+            0
+        } else if index >= offset + self.max_visible {
+            self.max_visible + 1
+        } else {
+            index - offset + 1
+        }
+    }
+
+    fn shows(&self, index: usize, count: usize) -> bool {
+        let offset = self.side_offset(count);
+        !self.overflows(count) || (offset..offset + self.max_visible).contains(&index)
+    }
+
+    /// Ports scrolled out above and below the visible ones
+    fn hidden(&self, count: usize) -> (usize, usize) {
+        if !self.overflows(count) {
+            return (0, 0);
+        }
+        let offset = self.side_offset(count);
+        (offset, count - offset - self.max_visible)
+    }
+}
+
+impl Node {
+    /// The node's media class, `Other` when PipeWire didn't report one
+    pub fn class(&self) -> MediaClass {
+        self.media_class.as_deref().map_or(MediaClass::Other, MediaClass::from_media_class)
+    }
+
+    /// Key used to persist per-node settings in the config
+    pub fn key(&self) -> NodeKey {
+        NodeKey {
+            node_name: self.name.clone(),
+            app_name: self.app_name.clone(),
+            object_path: self.object_path.clone(),
+            index: Some(self.index),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Port {
     pub id: u32,
     pub name: String,
+    /// Human-readable name some devices give their ports ("Analog Input 1")
+    pub alias: Option<String>,
+    /// Read-only tap of what a sink is playing
+    pub is_monitor: bool,
     pub direction: PortDirection,
     pub port_type: PortType,
+    /// Negotiated format ("PCM", "MIDI"), once PipeWire reports one
+    pub format: Option<String>,
+    pub channels: Option<u16>,
+    pub sample_rate: Option<u32>,
+    /// The name without its channel suffix ("playback" for "playback_FL"),
+    /// shared by the ports of one multi-channel stream
+    pub group: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -109,7 +323,7 @@ pub enum NodeCategory {
 }
 
 impl NodeCategory {
-    fn from_node(node: &Node) -> Option<Self> {
+    pub(crate) fn from_node(node: &Node) -> Option<Self> {
         let has_video = node.input_ports.iter().chain(node.output_ports.iter())
             .any(|p| p.port_type == PortType::Video);
         let has_midi = node.input_ports.iter().chain(node.output_ports.iter())
@@ -128,7 +342,7 @@ impl NodeCategory {
         }
     }
 
-    fn color(&self) -> Color {
+    pub(crate) fn color(&self) -> Color {
         match self {
             NodeCategory::AudioSource => palette::CAT_AUDIO_SOURCE,
             NodeCategory::AudioSink => palette::CAT_AUDIO_SINK,
@@ -150,6 +364,64 @@ impl NodeCategory {
     }
 }
 
+/// What a node does, from its PipeWire media.class
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaClass {
+    AudioSink,
+    AudioSource,
+    AudioDuplex,
+    VideoSink,
+    VideoSource,
+    MidiSink,
+    MidiSource,
+    Filter,
+    Other,
+}
+
+impl MediaClass {
+    /// Classify a media.class string. Application streams count as the
+    /// end they present to the graph: playback is a source, recording a sink.
+    pub fn from_media_class(class: &str) -> Self {
+        if class.contains("Filter") {
+            return MediaClass::Filter;
+        }
+        match class {
+            "Audio/Sink" | "Audio/Sink/Virtual" | "Stream/Input/Audio" => MediaClass::AudioSink,
+            "Audio/Source" | "Audio/Source/Virtual" | "Stream/Output/Audio" => MediaClass::AudioSource,
+            "Audio/Duplex" => MediaClass::AudioDuplex,
+            "Video/Sink" | "Stream/Input/Video" => MediaClass::VideoSink,
+            "Video/Source" | "Stream/Output/Video" => MediaClass::VideoSource,
+            "Midi/Sink" | "Stream/Input/Midi" => MediaClass::MidiSink,
+            "Midi/Source" | "Stream/Output/Midi" => MediaClass::MidiSource,
+            _ => MediaClass::Other,
+        }
+    }
+
+    pub fn is_video(self) -> bool {
+        matches!(self, MediaClass::VideoSink | MediaClass::VideoSource)
+    }
+
+    /// Whether a filter on this class keeps a node of `class`; video
+    /// filters keep both directions
+    pub fn includes(self, class: MediaClass) -> bool {
+        self == class || (self.is_video() && class.is_video())
+    }
+
+    /// Name shown for the class filter
+    pub fn label(self) -> &'static str {
+        match self {
+            MediaClass::AudioSink => "Audio sinks",
+            MediaClass::AudioSource => "Audio sources",
+            MediaClass::AudioDuplex => "Audio duplex",
+            MediaClass::VideoSink | MediaClass::VideoSource => "Video",
+            MediaClass::MidiSink => "MIDI sinks",
+            MediaClass::MidiSource => "MIDI sources",
+            MediaClass::Filter => "Filters",
+            MediaClass::Other => "Other",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Link {
     pub id: u32,
@@ -157,22 +429,252 @@ pub struct Link {
     pub output_port: u32,
     pub input_node: u32,
     pub input_port: u32,
+    /// Negotiation state reported by PipeWire
+    pub state: LinkState,
 }
 
-#[derive(Debug, Clone)]
+impl Link {
+    /// A link from a node back into itself (loopbacks, some filter chains),
+    /// drawn as an arc over the node instead of a cable
+    pub fn is_self_loop(&self) -> bool {
+        self.output_node == self.input_node
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum UndoAction {
     Connect { output_port: u32, input_port: u32 },
     Disconnect { output_port: u32, input_port: u32 },
+    /// Several links made in one step, as (output_port, input_port) pairs
+    BatchConnect { pairs: Vec<(u32, u32)> },
+    /// Several links removed in one step, as (output_port, input_port) pairs
+    BatchDisconnect { pairs: Vec<(u32, u32)> },
+    /// A preset applied in one step: the links it made and the ones it
+    /// removed, as (output_port, input_port) pairs
+    ApplyPreset { name: String, connected: Vec<(u32, u32)>, disconnected: Vec<(u32, u32)> },
+    /// A link moved from one (output_port, input_port) pair to another
+    Reconnect { old: (u32, u32), new: (u32, u32) },
+    /// A node dragged from one position to another
+    Move { node_id: u32, from: Point, to: Point },
+    /// Several selected nodes dragged together, as (node_id, from, to)
+    MoveGroup { moves: Vec<(u32, Point, Point)> },
+    /// A node's custom name changed; `None` is the PipeWire name
+    Rename { node_id: u32, key: NodeKey, old_name: Option<String>, new_name: Option<String> },
+    /// An auto-layout pass, as every node's position before and after
+    Layout { from: HashMap<u32, (NodeKey, Point)>, to: HashMap<u32, (NodeKey, Point)> },
+}
+
+impl UndoAction {
+    /// Reverse the effect of this action on the PipeWire graph
+    fn revert(&self, nodes: &HashMap<u32, Node>, links: &[Link]) -> Task<Message> {
+        match self {
+            // Undo a connect = disconnect
+            UndoAction::Connect { output_port, input_port } => disconnect_ports(*output_port, *input_port, false),
+            // Undo a disconnect = reconnect
+            UndoAction::Disconnect { output_port, input_port } => connect_ports(nodes, links, *output_port, *input_port, false),
+            UndoAction::BatchConnect { pairs } => Task::batch(
+                pairs.iter().map(|&(output_port, input_port)| disconnect_ports(output_port, input_port, false)),
+            ),
+            UndoAction::BatchDisconnect { pairs } => Task::batch(
+                pairs.iter().map(|&(output_port, input_port)| connect_ports(nodes, links, output_port, input_port, false)),
+            ),
+            UndoAction::ApplyPreset { connected, disconnected, .. } => Task::batch(
+                connected.iter().map(|&(output_port, input_port)| disconnect_ports(output_port, input_port, false))
+                    .chain(disconnected.iter().map(|&(output_port, input_port)| connect_ports(nodes, links, output_port, input_port, false))),
+            ),
+            UndoAction::Reconnect { old, new } => Task::batch([
+                disconnect_ports(new.0, new.1, false),
+                connect_ports(nodes, links, old.0, old.1, false),
+            ]),
+            // Positions and names live in the graph, see Graph::revert_action
+            UndoAction::Move { .. }
+            | UndoAction::MoveGroup { .. }
+            | UndoAction::Rename { .. }
+            | UndoAction::Layout { .. } => Task::none(),
+        }
+    }
+
+    /// Whether this single-link action is about the `output_port` → `input_port` link
+    fn touches(&self, output_port: u32, input_port: u32) -> bool {
+        match self {
+            UndoAction::Connect { output_port: o, input_port: i }
+            | UndoAction::Disconnect { output_port: o, input_port: i } => (*o, *i) == (output_port, input_port),
+            UndoAction::Reconnect { old, new } => [*old, *new].contains(&(output_port, input_port)),
+            _ => false,
+        }
+    }
+
+    /// The action that reverting this one amounts to
+    fn inverse(self) -> Self {
+        match self {
+            UndoAction::Connect { output_port, input_port } =>
+                UndoAction::Disconnect { output_port, input_port },
+            UndoAction::Disconnect { output_port, input_port } =>
+                UndoAction::Connect { output_port, input_port },
+            UndoAction::BatchConnect { pairs } => UndoAction::BatchDisconnect { pairs },
+            UndoAction::BatchDisconnect { pairs } => UndoAction::BatchConnect { pairs },
+            UndoAction::ApplyPreset { name, connected, disconnected } =>
+                UndoAction::ApplyPreset { name, connected: disconnected, disconnected: connected },
+            UndoAction::Reconnect { old, new } => UndoAction::Reconnect { old: new, new: old },
+            UndoAction::Move { node_id, from, to } => UndoAction::Move { node_id, from: to, to: from },
+            UndoAction::MoveGroup { moves } => UndoAction::MoveGroup {
+                moves: moves.into_iter().map(|(node_id, from, to)| (node_id, to, from)).collect(),
+            },
+            UndoAction::Rename { node_id, key, old_name, new_name } =>
+                UndoAction::Rename { node_id, key, old_name: new_name, new_name: old_name },
+            UndoAction::Layout { from, to } => UndoAction::Layout { from: to, to: from },
+        }
+    }
+
+    /// Human-readable summary, e.g. "Connect Firefox:output_FL → Headphones:playback_FL"
+    fn description(&self, nodes: &HashMap<u32, Node>) -> String {
+        let node_name = |id: &u32| nodes.get(id)
+            .map(|n| n.custom_name.clone().unwrap_or_else(|| n.name.clone()))
+            .unwrap_or_else(|| format!("node {}", id));
+        let link = |output_port: u32, input_port: u32| {
+            format!("{} → {}", port_label(nodes, output_port), port_label(nodes, input_port))
+        };
+        match self {
+            UndoAction::Connect { output_port, input_port } =>
+                format!("Connect {}", link(*output_port, *input_port)),
+            UndoAction::Disconnect { output_port, input_port } =>
+                format!("Disconnect {}", link(*output_port, *input_port)),
+            UndoAction::BatchConnect { pairs } => match pairs.as_slice() {
+                [(output_port, input_port)] => format!("Connect {}", link(*output_port, *input_port)),
+                _ => format!("Connect {} links", pairs.len()),
+            },
+            UndoAction::BatchDisconnect { pairs } => match pairs.as_slice() {
+                [(output_port, input_port)] => format!("Disconnect {}", link(*output_port, *input_port)),
+                _ => format!("Disconnect {} links", pairs.len()),
+            },
+            UndoAction::ApplyPreset { name, .. } => format!("Apply preset {}", name),
+            UndoAction::Reconnect { new, .. } => format!("Reconnect {}", link(new.0, new.1)),
+            UndoAction::Move { node_id, .. } => format!("Move {}", node_name(node_id)),
+            UndoAction::MoveGroup { moves } => format!("Move {} nodes", moves.len()),
+            UndoAction::Rename { node_id, old_name, new_name, .. } => {
+                let base = nodes.get(node_id).map(|n| n.name.clone()).unwrap_or_else(|| node_name(node_id));
+                let old = old_name.clone().unwrap_or_else(|| base.clone());
+                format!("Rename {} to {}", old, new_name.clone().unwrap_or(base))
+            }
+            UndoAction::Layout { .. } => "Auto-layout".to_string(),
+        }
+    }
+}
+
+/// An undo stack entry; the description is taken when the action happens,
+/// so it still reads right after the nodes involved are gone
+#[derive(Debug, Clone)]
+pub struct UndoEntry {
+    pub action: UndoAction,
+    pub description: String,
+}
+
+/// Whether a key press triggers `binding`. Shift is only compared for
+/// letters and named keys, since it's part of typing symbols like "?"
+fn binding_matches(binding: &KeyBinding, key: &iced::keyboard::Key<&str>, modifiers: iced::keyboard::Modifiers) -> bool {
+    use iced::keyboard::Key;
+    let (matches_key, is_symbol) = match key {
+        Key::Character(c) => (
+            c.to_lowercase() == binding.key.to_lowercase(),
+            c.to_lowercase() == c.to_uppercase(),
+        ),
+        Key::Named(named) => (format!("{:?}", named).eq_ignore_ascii_case(&binding.key), false),
+        _ => (false, false),
+    };
+    matches_key
+        && binding.ctrl == modifiers.control()
+        && binding.alt == modifiers.alt()
+        && (is_symbol || binding.shift == modifiers.shift())
+}
+
+/// Link two ports; a failure comes back as a `ConnectionResult`. The
+/// ports' current names go along in case pw-link refuses the ids.
+fn connect_ports(nodes: &HashMap<u32, Node>, links: &[Link], output_port: u32, input_port: u32, undo_pushed: bool) -> Task<Message> {
+    if links.iter().any(|l| l.output_port == output_port && l.input_port == input_port) {
+        return Task::done(Message::Graph(GraphMessage::DuplicateLinkSkipped { output_port, input_port, undo_pushed }));
+    }
+    let names = port_link_name(nodes, output_port).zip(port_link_name(nodes, input_port));
+    Task::perform(crate::pipewire_connect(output_port, input_port, names), move |result| {
+        connection_result(result, output_port, input_port, undo_pushed)
+    })
+}
+
+/// Unlink two ports; a failure comes back as a `ConnectionResult`
+fn disconnect_ports(output_port: u32, input_port: u32, undo_pushed: bool) -> Task<Message> {
+    Task::perform(crate::pipewire_disconnect(output_port, input_port), move |result| {
+        connection_result(result, output_port, input_port, undo_pushed)
+    })
+}
+
+fn connection_result(result: Result<(), String>, output_port: u32, input_port: u32, undo_pushed: bool) -> Message {
+    Message::Graph(GraphMessage::ConnectionResult {
+        success: result.is_ok(),
+        output_port,
+        input_port,
+        error: result.err(),
+        undo_pushed,
+    })
+}
+
+/// A port in pw-link's "node.name:port.name" notation, unless another node
+/// shares the name and pw-link could pick the wrong one
+fn port_link_name(nodes: &HashMap<u32, Node>, port_id: u32) -> Option<String> {
+    let link_name = |node: &Node| node.node_name.clone().unwrap_or_else(|| node.name.clone());
+    let (node, port) = nodes.values().find_map(|node| {
+        node.input_ports.iter()
+            .chain(node.output_ports.iter())
+            .find(|p| p.id == port_id)
+            .map(|port| (node, port))
+    })?;
+    let name = link_name(node);
+    let ambiguous = nodes.values().any(|other| other.id != node.id && link_name(other) == name);
+    (!ambiguous).then(|| format!("{}:{}", name, port.name))
+}
+
+/// Channel suffixes stripped from port names to find their group
+const CHANNEL_SUFFIXES: &[&str] = &[
+    "_FL", "_FR", "_FC", "_LFE", "_RL", "_RR", "_SL", "_SR", "_MONO", "_L", "_R", " Left", " Right",
+];
+
+/// A port name's group: the name minus a channel suffix such as "_FL",
+/// " Left" or "_2"
+fn port_group(name: &str) -> Option<String> {
+    let stem = CHANNEL_SUFFIXES.iter()
+        .find_map(|suffix| name.strip_suffix(suffix))
+        .or_else(|| {
+            // Numbered channels: "capture_1", "capture_2", ...
+            let trimmed = name.trim_end_matches(|c: char| c.is_ascii_digit());
+            (trimmed.len() < name.len()).then(|| trimmed.strip_suffix('_')).flatten()
+        })?;
+    (!stem.is_empty()).then(|| stem.to_string())
+}
+
+/// "Node:port" for a port id, falling back to the bare id
+fn port_label(nodes: &HashMap<u32, Node>, port_id: u32) -> String {
+    nodes.values()
+        .find_map(|node| {
+            node.input_ports.iter()
+                .chain(node.output_ports.iter())
+                .find(|p| p.id == port_id)
+                .map(|port| format!("{}:{}", node.custom_name.as_ref().unwrap_or(&node.name), port.name))
+        })
+        .unwrap_or_else(|| format!("port {}", port_id))
 }
 
 pub struct DeviceInfo {
-    pub id: u32,
     pub name: String,
     pub description: String,
     pub api: String,
     pub active_node_ids: Vec<u32>,
 }
 
+/// A connected PipeWire client, for telling which process owns a node
+pub struct ClientInfo {
+    pub name: String,
+    pub pid: u32,
+    pub exe: String,
+}
+
 pub struct GhostNode {
     pub device_id: u32,
     pub device_name: String,
@@ -196,37 +698,241 @@ pub struct ProfilePickerState {
     pub last_used_index: Option<u32>,
 }
 
+/// A preset shown as would-be changes before it's applied
+pub struct PresetPreview {
+    pub path: std::path::PathBuf,
+    pub preset: crate::preset::Preset,
+    /// Port pairs it would connect, and live links it doesn't contain
+    adds: Vec<(u32, u32)>,
+    removes: Vec<(u32, u32)>,
+}
+
+/// Difference between the live links and the loaded preset
+pub struct PresetDelta {
+    /// Live links that the preset doesn't contain
+    pub added: std::collections::HashSet<u32>,
+    /// Preset connections (output_port_id, input_port_id) that aren't linked
+    pub missing: Vec<(u32, u32)>,
+    /// Preset connections whose nodes or ports aren't present
+    pub unresolved: usize,
+}
+
+/// Offer to also connect a mono output to the right channel of a stereo input
+pub struct StereoPairPrompt {
+    pub second_output: u32,
+    pub second_input: u32,
+    pub deadline: std::time::Instant,
+}
+
+/// What a context menu was opened on
+#[derive(Debug, Clone, Copy)]
+pub enum ContextMenuTarget {
+    Canvas,
+    Node(u32),
+    Link { output_port: u32, input_port: u32 },
+}
+
+/// Right-click menu (screen space)
+pub struct ContextMenu {
+    pub position: Point,
+    pub items: Vec<(String, GraphMessage)>,
+}
+
+const MINIMAP_SIZE: Size = Size::new(160.0, 100.0);
+const MINIMAP_MARGIN: f32 = 12.0;
+
+/// Flow dot travel per millisecond, as a fraction of the cable (~200ms end to end)
+const LINK_DOT_SPEED: f32 = 0.005;
+
+/// Hover time before a node's tooltip appears
+const NODE_TOOLTIP_DELAY: std::time::Duration = std::time::Duration::from_millis(600);
+
+/// Hover time before a port's tooltip appears
+const PORT_TOOLTIP_DELAY: std::time::Duration = std::time::Duration::from_millis(300);
+
+/// How long the stereo pair prompt waits before accepting on its own
+const STEREO_PROMPT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Length of one pulse on a negotiating link
+const NEGOTIATION_PULSE_PERIOD_MS: f32 = 1200.0;
+
+/// The presets listed in `auto_preset_paths`, skipping any that don't load
+fn load_auto_presets(config: &Config) -> Vec<crate::preset::Preset> {
+    config.auto_preset_paths.iter()
+        .filter_map(|path| crate::preset::Preset::load(std::path::Path::new(path)))
+        .collect()
+}
+
+/// A node auto-layout must leave in place, in layout (main, cross) axes
+struct LayoutObstacle {
+    main: f32,
+    main_len: f32,
+    cross: f32,
+    cross_len: f32,
+}
+
+/// Fraction of the remaining distance a node covers per 60 Hz animation frame
+const LAYOUT_TWEEN_FACTOR: f32 = 0.15;
+
+/// Distance (px) at which a tweening node snaps onto its target
+const LAYOUT_TWEEN_SNAP: f32 = 0.5;
+
 pub struct Graph {
     pub nodes: HashMap<u32, Node>,
     pub links: Vec<Link>,
+    /// Links reported before one of their nodes; moved into `links` once
+    /// both nodes exist
+    pub orphaned_links: Vec<Link>,
+    /// LinkAdded events ignored because their port pair was already linked
+    pub duplicate_link_count: usize,
+    /// Flow dot position (0.0–1.0 along the cable) for each active link
+    pub link_dot_positions: HashMap<u32, f32>,
+    last_tick: Option<std::time::Instant>,
+    /// Pulse phase (0.0–1.0) for links still negotiating
+    negotiation_pulse: f32,
     pub pan_offset: Vector,
     pub zoom: f32,
-    cache: Cache,
-    undo_stack: Vec<UndoAction>,
-    redo_stack: Vec<UndoAction>,
+    // Background and cables; nodes are drawn on top from their own layer
+    link_cache: Cache,
+    node_cache: Cache,
+    // Node and port hit boxes, rebuilt lazily after the graph changes
+    spatial_index: std::cell::RefCell<QuadTree<SpatialItem>>,
+    spatial_dirty: std::cell::Cell<bool>,
+    undo_stack: VecDeque<UndoEntry>,
+    redo_stack: Vec<UndoEntry>,
+    // Oldest undo entries are dropped past this many (from config)
+    undo_stack_limit: usize,
     pub show_help: bool,
+    // Preset picker overlay: presets found in the presets directory
+    pub show_preset_picker: bool,
+    pub available_presets: Vec<(crate::preset::Preset, std::path::PathBuf)>,
+    preset_picker_index: usize,
+    preset_delete_pending: bool,
+    pub show_history: bool,
+    // Rebindable key bindings by action name (from config)
+    shortcuts: HashMap<String, KeyBinding>,
+    pub show_minimap: bool,
 
     // Search/filter state
     pub search_query: String,
     pub search_active: bool,
-    pub filtered_nodes: std::collections::HashSet<u32>,
+    /// Matching nodes, sorted by position
+    pub filtered_nodes: Vec<u32>,
+    /// Match the view was last moved to with Enter / Shift+Enter
+    pub search_match_index: usize,
+    search_cycling: bool,
+    pub search_error: Option<String>,
+    /// Ports matched by a "port:" search (node_id, port_id)
+    pub filtered_ports: std::collections::HashSet<(u32, u32)>,
+    /// While dragging a new connection, the ports it could land on
+    connectable_ports: Option<std::collections::HashSet<u32>>,
+
+    // Only show ports (and links) of this type
+    pub port_type_filter: Option<PortType>,
+    // Only show nodes of this media class
+    pub media_class_filter: Option<MediaClass>,
+
+    // Default sink/source, resolved from the node.name in the metadata
+    pub default_sink_id: Option<u32>,
+    pub default_source_id: Option<u32>,
+    default_sink_name: Option<String>,
+    default_source_name: Option<String>,
 
     // Preset state
     pub current_preset: Option<crate::preset::Preset>,
     pub preset_path: Option<std::path::PathBuf>,
+    // Active config profile
+    pub profile: String,
+    // Hotkey slot the current preset was loaded from
+    pub active_preset_slot: Option<usize>,
+    // Presets whose connections are made as soon as both ends exist
+    pub auto_presets: Vec<crate::preset::Preset>,
+    /// Auto-preset links sent to pw-link that PipeWire hasn't reported yet
+    auto_preset_requests: std::collections::HashSet<(u32, u32)>,
     pub exclusive_mode: bool,
 
+    // Hit-test tolerances (from config)
+    pub link_hit_radius: f32,
+    pub port_hit_radius: f32,
+
+    // Zoom below which port labels are skipped (from config)
+    pub lod_zoom_threshold: f32,
+    // Label ports by alias where PipeWire has one (from config)
+    pub prefer_port_alias: bool,
+    // Leave monitor ports off the canvas (from config)
+    pub hide_monitor_ports: bool,
+
+    // Nodes with more ports than this per side scroll them (from config)
+    pub max_visible_ports: usize,
+    // First visible port of each scrolled node
+    pub port_scroll_offsets: HashMap<u32, usize>,
+
     // Node renaming state
     pub renaming_node: Option<u32>,
     pub rename_text: String,
 
     // Pinned connections (output_port_id, input_port_id)
     pub pinned_connections: std::collections::HashSet<(u32, u32)>,
+    // The same pins by node/port name, re-resolved to keep them connected
+    pub name_pinned: Vec<PinnedSpec>,
 
     // Device tracking
     pub devices: HashMap<u32, DeviceInfo>,
+    pub clients: HashMap<u32, ClientInfo>,
     pub ghost_nodes: Vec<GhostNode>,
+    /// Recently removed nodes by old id, with when they went away; drawn
+    /// faded until the grace period runs out or the node comes back
+    pub departed_nodes: HashMap<u32, (Node, std::time::Instant)>,
+    /// Nodes PipeWire removed since the last PendingRemovalTick; kept until
+    /// then since the id is sometimes reused right away
+    pub pending_removals: std::collections::HashSet<u32>,
     pub profile_picker: Option<ProfilePickerState>,
+
+    // Preset delta overlay (None when hidden)
+    pub preset_delta: Option<PresetDelta>,
+    // Preset being previewed, and its cables (world space, refreshed with the links)
+    pub preset_preview: Option<PresetPreview>,
+    pub preview_adds: Vec<(Point, Point, PortType)>,
+    pub preview_removes: Vec<(Point, Point)>,
+
+    // Unsnapped positions of nodes being dragged (so snapping doesn't eat small deltas)
+    drag_positions: HashMap<u32, Point>,
+    /// Unsnapped position of the ghost being dragged
+    ghost_drag_position: Option<Point>,
+
+    // Keyboard focus
+    pub focused_node: Option<u32>,
+
+    // Node under the cursor (or owning the hovered port), for connection highlighting
+    pub hovered_node: Option<u32>,
+
+    // Node tooltip: hover start, and the node whose tooltip is shown
+    pub hovered_node_timer: Option<(u32, std::time::Instant)>,
+    pub show_tooltip: Option<u32>,
+
+    // Port tooltip: hovered (node_id, port_id), hover start, and whether it's shown
+    pub hovered_port: Option<(u32, u32)>,
+    hovered_port_since: Option<std::time::Instant>,
+    pub show_port_tooltip: bool,
+
+    // Multi-node selection
+    pub selected_nodes: std::collections::HashSet<u32>,
+
+    // Pending "also connect right channel?" prompt
+    pub stereo_prompt: Option<StereoPairPrompt>,
+
+    // Screenshot in flight (overlays are hidden until it's taken)
+    pub exporting: bool,
+
+    pub context_menu: Option<ContextMenu>,
+    // Where the last context menu opened; profile submenus reuse it
+    context_menu_position: Point,
+    // Profiles fetched per device, and the device whose submenu is pending
+    pub device_profiles: HashMap<u32, Vec<DeviceProfile>>,
+    profile_menu_device: Option<u32>,
+
+    // Lost the PipeWire connection; a banner shows until it's back
+    pub pipewire_disconnected: bool,
 }
 
 impl Graph {
@@ -234,196 +940,1146 @@ impl Graph {
         Self {
             nodes: HashMap::new(),
             links: Vec::new(),
+            orphaned_links: Vec::new(),
+            duplicate_link_count: 0,
+            link_dot_positions: HashMap::new(),
+            last_tick: None,
+            negotiation_pulse: 0.0,
             pan_offset: Vector::ZERO,
             zoom: 1.0,
-            cache: Cache::new(),
-            undo_stack: Vec::new(),
+            link_cache: Cache::new(),
+            node_cache: Cache::new(),
+            spatial_index: std::cell::RefCell::new(QuadTree::default()),
+            spatial_dirty: std::cell::Cell::new(true),
+            undo_stack: VecDeque::new(),
             redo_stack: Vec::new(),
+            undo_stack_limit: config.undo_stack_limit,
             show_help: false,
+            show_preset_picker: false,
+            available_presets: Vec::new(),
+            preset_picker_index: 0,
+            preset_delete_pending: false,
+            show_history: false,
+            shortcuts: config.resolved_shortcuts(),
+            show_minimap: false,
             search_query: String::new(),
             search_active: false,
-            filtered_nodes: std::collections::HashSet::new(),
+            filtered_nodes: Vec::new(),
+            search_match_index: 0,
+            search_cycling: false,
+            search_error: None,
+            filtered_ports: std::collections::HashSet::new(),
+            connectable_ports: None,
+            port_type_filter: None,
+            media_class_filter: None,
+            default_sink_id: None,
+            default_source_id: None,
+            default_sink_name: None,
+            default_source_name: None,
             current_preset: None,
             preset_path: None,
+            profile: config.profile.clone(),
+            active_preset_slot: None,
+            auto_presets: load_auto_presets(config),
+            auto_preset_requests: std::collections::HashSet::new(),
             exclusive_mode: config.exclusive_mode,
+            link_hit_radius: config.link_hit_radius,
+            port_hit_radius: config.port_hit_radius,
+            lod_zoom_threshold: config.lod_zoom_threshold,
+            prefer_port_alias: config.prefer_port_alias,
+            hide_monitor_ports: config.hide_monitor_ports,
+            max_visible_ports: config.max_visible_ports.max(1),
+            port_scroll_offsets: HashMap::new(),
             renaming_node: None,
             rename_text: String::new(),
             pinned_connections: std::collections::HashSet::new(),
+            name_pinned: config.pinned_specs.clone(),
             devices: HashMap::new(),
+            clients: HashMap::new(),
             ghost_nodes: Vec::new(),
+            departed_nodes: HashMap::new(),
+            pending_removals: std::collections::HashSet::new(),
             profile_picker: None,
+            preset_delta: None,
+            preset_preview: None,
+            preview_adds: Vec::new(),
+            preview_removes: Vec::new(),
+            drag_positions: HashMap::new(),
+            ghost_drag_position: None,
+            focused_node: None,
+            hovered_node: None,
+            hovered_node_timer: None,
+            show_tooltip: None,
+            hovered_port: None,
+            hovered_port_since: None,
+            show_port_tooltip: false,
+            selected_nodes: std::collections::HashSet::new(),
+            stereo_prompt: None,
+            exporting: false,
+            context_menu: None,
+            context_menu_position: Point::ORIGIN,
+            device_profiles: HashMap::new(),
+            profile_menu_device: None,
+            pipewire_disconnected: false,
+        }
+    }
+
+    /// Redraw both cached layers and re-index hit boxes on next use
+    fn invalidate(&mut self) {
+        self.invalidate_links();
+        self.invalidate_nodes();
+    }
+
+    /// Redraw only the cable layer; hit boxes don't depend on links
+    fn invalidate_links(&mut self) {
+        self.link_cache.clear();
+        // Preview cables follow their ports like real ones
+        self.refresh_preview_cables();
+    }
+
+    /// Redraw both layers after a pan or zoom. The spatial index is in world
+    /// space, so it stays valid
+    fn invalidate_view(&mut self) {
+        self.link_cache.clear();
+        self.node_cache.clear();
+    }
+
+    /// Redraw only the node layer, for changes that can't move any cable
+    fn invalidate_nodes(&mut self) {
+        self.node_cache.clear();
+        self.spatial_dirty.set(true);
+    }
+
+    /// Re-read per-node settings after the config file was edited externally.
+    /// With the `previous` config, only positions the edit changed are
+    /// taken, so nodes moved in memory since stay where they are
+    pub fn reload_config(&mut self, config: &Config, previous: Option<&Config>) {
+        self.profile = config.profile.clone();
+        self.undo_stack_limit = config.undo_stack_limit;
+        self.max_visible_ports = config.max_visible_ports.max(1);
+        self.prefer_port_alias = config.prefer_port_alias;
+        self.hide_monitor_ports = config.hide_monitor_ports;
+        self.exclusive_mode = config.exclusive_mode;
+        self.link_hit_radius = config.link_hit_radius;
+        self.port_hit_radius = config.port_hit_radius;
+        self.lod_zoom_threshold = config.lod_zoom_threshold;
+        self.auto_presets = load_auto_presets(config);
+        self.shortcuts = config.resolved_shortcuts();
+        self.trim_undo_stack();
+        self.name_pinned = config.pinned_specs.clone();
+        for node in self.nodes.values_mut() {
+            let key = node.key();
+            node.custom_name = config.get_node_rename(&key).cloned();
+            node.collapsed = config.is_node_collapsed(&key);
+            node.layout_pinned = config.is_layout_pinned(&key);
+            node.hidden = config.is_node_hidden(&key);
+            let edited = previous.is_none_or(|previous| previous.get_position(&key) != config.get_position(&key));
+            if let Some(pos) = config.get_position(&key)
+                && (edited || !node.has_saved_position)
+            {
+                node.position = Point::new(pos.x, pos.y);
+                node.has_saved_position = true;
+            }
+        }
+        self.invalidate();
+    }
+
+    /// How often the graph needs ticks for timed prompts, if at all.
+    /// Animation runs on AnimationTick instead
+    pub fn tick_interval(&self) -> Option<std::time::Duration> {
+        if self.stereo_prompt.is_some() {
+            Some(std::time::Duration::from_millis(100))
+        } else {
+            None
         }
     }
 
     pub fn update(&mut self, message: GraphMessage, config: &mut Config) -> Task<Message> {
         match message {
             GraphMessage::NodeDragged { node_id, delta } => {
-                if let Some(node) = self.nodes.get_mut(&node_id) {
-                    node.position = node.position + delta / self.zoom;
-                    self.cache.clear();
+                // Dragging a selected node moves the whole selection
+                let moved: Vec<u32> = if self.selected_nodes.contains(&node_id) {
+                    self.selected_nodes.iter().copied().collect()
+                } else {
+                    vec![node_id]
+                };
+                for id in moved {
+                    if let Some(node) = self.nodes.get_mut(&id) {
+                        // Grabbing a node cancels its layout animation
+                        if let Some(target) = node.target_position.take() {
+                            node.position = target;
+                        }
+                        node.drag_start_position.get_or_insert(node.position);
+                        let raw = self.drag_positions.entry(id).or_insert(node.position);
+                        *raw += delta / self.zoom;
+                        node.position = drag_snap(*raw, config);
+                    }
+                }
+                self.invalidate();
+            }
+            GraphMessage::MultiNodeDragEnded { node_ids } => {
+                let mut positions = Vec::new();
+                let mut moves = Vec::new();
+                for id in node_ids {
+                    self.drag_positions.remove(&id);
+                    if let Some(node) = self.nodes.get_mut(&id) {
+                        node.has_saved_position = true;
+                        if let Some(from) = node.drag_start_position.take()
+                            && from != node.position
+                        {
+                            moves.push((id, from, node.position));
+                        }
+                        positions.push((node.key(), Position { x: node.position.x, y: node.position.y }));
+                    }
+                }
+                config.set_positions(positions);
+                // One entry for the whole drag, so a single undo puts every node back
+                match moves.as_slice() {
+                    [] => {}
+                    &[(node_id, from, to)] => self.push_undo(UndoAction::Move { node_id, from, to }),
+                    _ => {
+                        moves.sort_by_key(|&(id, ..)| id);
+                        self.push_undo(UndoAction::MoveGroup { moves });
+                    }
                 }
             }
             GraphMessage::NodeDragEnded { node_id } => {
+                self.drag_positions.remove(&node_id);
                 if let Some(node) = self.nodes.get_mut(&node_id) {
                     node.has_saved_position = true;
-                    let key = NodeKey {
-                        node_name: node.name.clone(),
-                        app_name: node.app_name.clone(),
-                        object_path: node.object_path.clone(),
-                        index: Some(node.index),
-                    };
                     config.set_position(
-                        key,
+                        node.key(),
                         Position {
                             x: node.position.x,
                             y: node.position.y,
                         },
                     );
+                    let to = node.position;
+                    if let Some(from) = node.drag_start_position.take()
+                        && from != to
+                    {
+                        self.push_undo(UndoAction::Move { node_id, from, to });
+                    }
                 }
             }
-            GraphMessage::ConnectionStarted { .. } => {
-                // Visual feedback handled in draw
+            GraphMessage::ConnectionStarted { node_id, port_id } => {
+                // Pending cable is drawn from the canvas state; the node layer
+                // shows which ports it can reach
+                let from = self.nodes.get(&node_id)
+                    .and_then(|n| n.input_ports.iter().chain(n.output_ports.iter()).find(|p| p.id == port_id))
+                    .map(|p| (p.port_type, p.direction));
+                self.connectable_ports = from.map(|(port_type, direction)| {
+                    self.nodes.values()
+                        .flat_map(|n| n.input_ports.iter().chain(n.output_ports.iter()))
+                        .filter(|p| p.port_type == port_type && p.direction != direction)
+                        .map(|p| p.id)
+                        .collect()
+                });
+                self.invalidate_nodes();
             }
-            GraphMessage::ConnectionEnded { from_node, from_port, to_node: _, to_port } => {
+            GraphMessage::ConnectionEnded { from_node, from_port, to_node, to_port } => {
+                if self.connectable_ports.take().is_some() {
+                    self.invalidate_nodes();
+                }
                 // Determine which is output and which is input
-                let (output_port, input_port) = {
-                    let from_is_output = self.nodes.get(&from_node)
-                        .map(|n| n.output_ports.iter().any(|p| p.id == from_port))
-                        .unwrap_or(false);
-
-                    if from_is_output {
-                        (from_port, to_port)
-                    } else {
-                        (to_port, from_port)
-                    }
+                let from_is_output = self.nodes.get(&from_node)
+                    .map(|n| n.output_ports.iter().any(|p| p.id == from_port))
+                    .unwrap_or(false);
+                let (output_port, input_port, output_node, input_node) = if from_is_output {
+                    (from_port, to_port, from_node, to_node)
+                } else {
+                    (to_port, from_port, to_node, from_node)
                 };
 
+                if self.would_create_cycle(output_node, input_node) {
+                    return self.update(GraphMessage::ShowError("Feedback loop detected".to_string()), config);
+                }
+
                 // Create connection and track for undo
-                crate::pipewire_connect(output_port, input_port);
-                self.undo_stack.push(UndoAction::Connect { output_port, input_port });
-                self.redo_stack.clear(); // Clear redo on new action
+                let task = connect_ports(&self.nodes, &self.links, output_port, input_port, true);
+                self.push_undo(UndoAction::Connect { output_port, input_port });
+
+                // Mono output into the left channel of a stereo input: offer the right channel too
+                self.stereo_prompt = None;
+                if self.find_stereo_pair(output_port).is_none()
+                    && let Some(second_input) = self.find_stereo_pair(input_port)
+                    && self.port_channel(input_port) == Some("FL")
+                {
+                    self.stereo_prompt = Some(StereoPairPrompt {
+                        second_output: output_port,
+                        second_input,
+                        deadline: std::time::Instant::now() + STEREO_PROMPT_TIMEOUT,
+                    });
+                }
+                return task;
             }
-            GraphMessage::ConnectionCancelled => {
-                self.cache.clear();
+            GraphMessage::ConfirmStereoPair { second_output, second_input } => {
+                self.stereo_prompt = None;
+                self.push_undo(UndoAction::Connect { output_port: second_output, input_port: second_input });
+                return connect_ports(&self.nodes, &self.links, second_output, second_input, true);
             }
-            GraphMessage::DisconnectLink { link_id: _, output_port, input_port } => {
-                // Disconnect and track for undo
-                crate::pipewire_disconnect(output_port, input_port);
-                self.undo_stack.push(UndoAction::Disconnect { output_port, input_port });
-                self.redo_stack.clear(); // Clear redo on new action
+            GraphMessage::DismissStereoPair => {
+                self.stereo_prompt = None;
             }
-            GraphMessage::Pan(delta) => {
-                self.pan_offset = self.pan_offset + delta;
-                self.cache.clear();
+            GraphMessage::ShowPresetDelta => {
+                self.preset_delta = if self.preset_delta.is_some() {
+                    None
+                } else {
+                    self.compute_preset_delta()
+                };
+                self.invalidate();
             }
-            GraphMessage::Zoom { delta, cursor } => {
-                let old_zoom = self.zoom;
-                self.zoom = (self.zoom * (1.0 + delta * 0.1)).clamp(0.25, 4.0);
-
-                // Zoom towards cursor
-                let cursor_world_x = (cursor.x - self.pan_offset.x) / old_zoom;
-                let cursor_world_y = (cursor.y - self.pan_offset.y) / old_zoom;
-                self.pan_offset.x = cursor.x - cursor_world_x * self.zoom;
-                self.pan_offset.y = cursor.y - cursor_world_y * self.zoom;
-                self.cache.clear();
+            GraphMessage::SavePresetVersion => {
+                if let (Some(preset), Some(path)) = (&self.current_preset, &self.preset_path) {
+                    let mut updated = preset.clone();
+                    updated.connections = self.links.iter()
+                        .filter_map(|l| self.link_to_preset_connection(l))
+                        .collect();
+                    updated.version += 1;
+                    if let Err(error) = updated.save(path) {
+                        return crate::toast::notify(Toast::error(format!("Failed to save {}: {}", path.display(), error)));
+                    }
+                    self.current_preset = Some(updated);
+                    self.preset_delta = self.compute_preset_delta();
+                    self.invalidate();
+                }
             }
-            GraphMessage::AutoLayout => {
-                self.perform_auto_layout();
-                self.cache.clear();
+            GraphMessage::OpenPresetPicker => {
+                self.available_presets = Config::presets_dir()
+                    .map(|dir| crate::preset::list_presets(&dir, &config.preset_last_applied))
+                    .unwrap_or_default();
+                // Start on the loaded preset if it's in the list
+                self.preset_picker_index = self.preset_path.as_ref()
+                    .and_then(|current| self.available_presets.iter().position(|(_, path)| path == current))
+                    .unwrap_or(0);
+                self.preset_delete_pending = false;
+                self.show_preset_picker = true;
             }
-            GraphMessage::Undo => {
-                if let Some(action) = self.undo_stack.pop() {
-                    match &action {
-                        UndoAction::Connect { output_port, input_port } => {
-                            // Undo a connect = disconnect
-                            crate::pipewire_disconnect(*output_port, *input_port);
-                        }
-                        UndoAction::Disconnect { output_port, input_port } => {
-                            // Undo a disconnect = reconnect
-                            crate::pipewire_connect(*output_port, *input_port);
-                        }
-                    }
-                    // Push inverse action to redo stack
-                    let inverse = match action {
-                        UndoAction::Connect { output_port, input_port } =>
-                            UndoAction::Disconnect { output_port, input_port },
-                        UndoAction::Disconnect { output_port, input_port } =>
-                            UndoAction::Connect { output_port, input_port },
-                    };
-                    self.redo_stack.push(inverse);
+            GraphMessage::ClosePresetPicker => {
+                // Esc first backs out of a pending delete
+                if self.preset_delete_pending {
+                    self.preset_delete_pending = false;
+                } else {
+                    self.show_preset_picker = false;
                 }
             }
-            GraphMessage::Redo => {
-                if let Some(action) = self.redo_stack.pop() {
-                    match &action {
-                        UndoAction::Connect { output_port, input_port } => {
-                            crate::pipewire_disconnect(*output_port, *input_port);
-                        }
-                        UndoAction::Disconnect { output_port, input_port } => {
-                            crate::pipewire_connect(*output_port, *input_port);
-                        }
+            GraphMessage::PresetPickerMove(delta) => {
+                let last = self.available_presets.len().saturating_sub(1) as i32;
+                self.preset_picker_index = (self.preset_picker_index as i32 + delta).clamp(0, last) as usize;
+                self.preset_delete_pending = false;
+            }
+            GraphMessage::PresetPickerSelect(index) => {
+                if index < self.available_presets.len() {
+                    self.preset_picker_index = index;
+                    return self.update(GraphMessage::PresetPickerApply, config);
+                }
+            }
+            GraphMessage::PresetPickerApply => {
+                if let Some((_, path)) = self.available_presets.get(self.preset_picker_index) {
+                    let path = path.clone();
+                    self.show_preset_picker = false;
+                    return self.update(GraphMessage::ApplyPreset { path }, config);
+                }
+            }
+            GraphMessage::PresetPickerDelete => {
+                if !self.preset_delete_pending {
+                    self.preset_delete_pending = !self.available_presets.is_empty();
+                } else if let Some((name, path)) = self.available_presets.get(self.preset_picker_index)
+                    .map(|(preset, path)| (preset.name.clone(), path.clone()))
+                {
+                    self.preset_delete_pending = false;
+                    if std::fs::remove_file(&path).is_err() {
+                        return crate::toast::notify(Toast::error(format!("Could not delete {}", name)));
                     }
-                    let inverse = match action {
-                        UndoAction::Connect { output_port, input_port } =>
-                            UndoAction::Disconnect { output_port, input_port },
-                        UndoAction::Disconnect { output_port, input_port } =>
-                            UndoAction::Connect { output_port, input_port },
-                    };
-                    self.undo_stack.push(inverse);
+                    if self.preset_path.as_ref() == Some(&path) {
+                        self.current_preset = None;
+                        self.preset_path = None;
+                        self.preset_delta = None;
+                    }
+                    self.available_presets.remove(self.preset_picker_index);
+                    self.preset_picker_index = self.preset_picker_index.min(self.available_presets.len().saturating_sub(1));
+                    self.invalidate();
+                    return crate::toast::notify(Toast::info(format!("Deleted {}", name)));
                 }
             }
-            GraphMessage::ToggleHelp => {
-                self.show_help = !self.show_help;
-                self.cache.clear();
+            GraphMessage::PresetPickerNew => {
+                let Some(dir) = Config::presets_dir() else {
+                    return Task::none();
+                };
+                // First free "preset-N.json" (or .yaml)
+                let extension = config.preset_format.extension();
+                let number = (1..).find(|n| !dir.join(format!("preset-{}.{}", n, extension)).exists()).unwrap_or(1);
+                let path = dir.join(format!("preset-{}.{}", number, extension));
+                let mut preset = crate::preset::Preset::new(format!("Preset {}", number));
+                preset.connections = self.links.iter()
+                    .filter_map(|l| self.link_to_preset_connection(l))
+                    .collect();
+                if let Err(error) = preset.save(&path) {
+                    return crate::toast::notify(Toast::error(format!("Failed to save {}: {}", path.display(), error)));
+                }
+                self.available_presets = crate::preset::list_presets(&dir, &config.preset_last_applied);
+                self.preset_picker_index = self.available_presets.iter()
+                    .position(|(_, p)| *p == path)
+                    .unwrap_or(0);
+                self.preset_delete_pending = false;
+                return crate::toast::notify(Toast::info(format!(
+                    "Saved {} ({} connections)", preset.name, preset.connections.len()
+                )));
             }
-            GraphMessage::SearchActivate => {
-                self.search_active = true;
-                self.search_query.clear();
-                self.filtered_nodes.clear();
-                self.cache.clear();
+            GraphMessage::PresetPickerPreview => {
+                if let Some((_, path)) = self.available_presets.get(self.preset_picker_index) {
+                    let path = path.clone();
+                    self.show_preset_picker = false;
+                    return self.update(GraphMessage::PreviewPreset { path }, config);
+                }
             }
-            GraphMessage::SearchInput { text } => {
-                self.search_active = true;
-                self.search_query.push_str(&text);
-                self.update_search_filter();
-                self.cache.clear();
+            GraphMessage::PresetPickerBrowse => {
+                self.show_preset_picker = false;
+                return Task::perform(crate::pick_preset_file(), |path| path)
+                    .and_then(|path| Task::done(Message::Graph(GraphMessage::ApplyPreset { path })));
             }
-            GraphMessage::SearchBackspace => {
-                self.search_query.pop();
-                self.update_search_filter();
-                self.cache.clear();
+            GraphMessage::PreviewPreset { path } => {
+                let Some(preset) = crate::preset::Preset::load(&path) else {
+                    return self.update(GraphMessage::ShowError(format!("Could not load {}", path.display())), config);
+                };
+                let expected: Vec<(u32, u32)> = preset.connections.iter()
+                    .filter_map(|conn| self.resolve_preset_connection(conn))
+                    .collect();
+                let adds = expected.iter().copied()
+                    .filter(|&(out, inp)| !self.links.iter().any(|l| l.output_port == out && l.input_port == inp))
+                    .collect();
+                let removes = self.links.iter()
+                    .map(|l| (l.output_port, l.input_port))
+                    .filter(|pair| !expected.contains(pair))
+                    .collect();
+                self.preset_preview = Some(PresetPreview { path, preset, adds, removes });
+                self.refresh_preview_cables();
             }
-            GraphMessage::SearchClear => {
-                self.search_active = false;
-                self.search_query.clear();
-                self.filtered_nodes.clear();
-                self.cache.clear();
+            GraphMessage::ApplyPresetPreview => {
+                // Make the graph match the preview: apply the preset, then drop the extras
+                let Some(preview) = self.preset_preview.take() else {
+                    return Task::none();
+                };
+                self.refresh_preview_cables();
+                let removes: Vec<(u32, u32)> = preview.removes.into_iter()
+                    .filter(|&(out, inp)| self.links.iter().any(|l| l.output_port == out && l.input_port == inp))
+                    .collect();
+                return self.apply_preset_file(preview.path, removes, config);
             }
-            GraphMessage::SearchCommit => {
-                // Focus on first matching node
-                if let Some(&node_id) = self.filtered_nodes.iter().next() {
-                    if let Some(node) = self.nodes.get(&node_id) {
-                        // Pan to center the node
-                        self.pan_offset = Vector::new(
-                            -node.position.x * self.zoom + 400.0,
-                            -node.position.y * self.zoom + 300.0,
-                        );
+            GraphMessage::CancelPresetPreview => {
+                self.preset_preview = None;
+                self.refresh_preview_cables();
+            }
+            GraphMessage::ApplyPreset { path } => {
+                return self.apply_preset_file(path, Vec::new(), config);
+            }
+            GraphMessage::LoadPresetSlot(slot) => {
+                let path = slot.checked_sub(1)
+                    .and_then(|i| config.preset_slots.get(i).cloned().flatten());
+                let Some(path) = path else {
+                    return self.update(GraphMessage::ShowError(format!("Preset slot {} is empty", slot)), config);
+                };
+                let task = self.update(GraphMessage::ApplyPreset { path: path.clone() }, config);
+                if self.preset_path.as_ref() == Some(&path) {
+                    self.active_preset_slot = Some(slot);
+                }
+                return task;
+            }
+            GraphMessage::AssignPresetSlot(slot) => {
+                if let Some(path) = self.preset_path.clone() {
+                    config.set_preset_slot(slot, path);
+                    self.active_preset_slot = Some(slot);
+                    return crate::toast::notify(Toast::info(format!("Assigned preset to slot {}", slot)));
+                }
+            }
+            GraphMessage::SelectInRect { from, to } => {
+                // Rubber-band selection adds to the existing selection
+                let a = self.screen_to_world(from);
+                let b = self.screen_to_world(to);
+                let rect = Rectangle::new(
+                    Point::new(a.x.min(b.x), a.y.min(b.y)),
+                    Size::new((a.x - b.x).abs(), (a.y - b.y).abs()),
+                );
+                for node in self.nodes.values() {
+                    let bounds = Rectangle::new(node.position, Size::new(NODE_WIDTH, self.visible_node_height(node)));
+                    if bounds.intersects(&rect) {
+                        self.selected_nodes.insert(node.id);
                     }
                 }
-                self.search_active = false;
-                self.search_query.clear();
-                self.filtered_nodes.clear();
-                self.cache.clear();
+                self.invalidate();
             }
-            GraphMessage::DeactivateDevice { device_id } => {
-                crate::set_device_profile(device_id, 0);
-                // PipeWire will send NodeRemoved events → ghost appears via handle_pipewire_event
+            GraphMessage::SelectAll => {
+                self.selected_nodes = self.nodes.keys().copied().collect();
+                self.invalidate();
             }
-            GraphMessage::ShowProfilePicker { ghost_index } => {
-                if let Some(ghost) = self.ghost_nodes.get(ghost_index) {
-                    let device_id = ghost.device_id;
-                    let device_name = ghost.device_name.clone();
-                    let last_used = config.get_device_profile(&device_name);
+            GraphMessage::ClearSelection => {
+                if !self.selected_nodes.is_empty() {
+                    self.selected_nodes.clear();
+                    self.invalidate();
+                }
+            }
+            GraphMessage::DisconnectSelected => {
+                let pairs: Vec<(u32, u32)> = self.links.iter()
+                    .filter(|l| self.selected_nodes.contains(&l.output_node) || self.selected_nodes.contains(&l.input_node))
+                    .map(|l| (l.output_port, l.input_port))
+                    .collect();
+                if !pairs.is_empty() {
+                    let task = Task::batch(
+                        pairs.iter().map(|&(output_port, input_port)| disconnect_ports(output_port, input_port, false)),
+                    );
+                    self.push_undo(UndoAction::BatchDisconnect { pairs });
+                    return task;
+                }
+            }
+            GraphMessage::BatchConnect { output_node, input_node } => {
+                // Pair outputs with inputs of the same type, in port order
+                let (Some(out_node), Some(in_node)) = (self.nodes.get(&output_node), self.nodes.get(&input_node)) else {
+                    return Task::none();
+                };
+                let mut pairs = Vec::new();
+                for port_type in [PortType::Audio, PortType::Midi, PortType::Video] {
+                    let outputs = out_node.output_ports.iter().filter(|p| p.port_type == port_type);
+                    let inputs = in_node.input_ports.iter().filter(|p| p.port_type == port_type);
+                    for (out_port, in_port) in outputs.zip(inputs) {
+                        let exists = self.links.iter()
+                            .any(|l| l.output_port == out_port.id && l.input_port == in_port.id);
+                        if !exists {
+                            pairs.push((out_port.id, in_port.id));
+                        }
+                    }
+                }
+                if !pairs.is_empty() {
+                    let task = Task::batch(
+                        pairs.iter().map(|&(output_port, input_port)| connect_ports(&self.nodes, &self.links, output_port, input_port, false)),
+                    );
+                    self.push_undo(UndoAction::BatchConnect { pairs });
+                    return task;
+                }
+            }
+            GraphMessage::ToggleNodeHidden { node_id } => {
+                if let Some(node) = self.nodes.get_mut(&node_id) {
+                    node.hidden = !node.hidden;
+                    config.set_node_hidden(&node.key(), node.hidden);
+                    if node.hidden {
+                        self.selected_nodes.remove(&node_id);
+                        if self.focused_node == Some(node_id) {
+                            self.focused_node = None;
+                        }
+                    }
+                    self.invalidate();
+                }
+            }
+            GraphMessage::SetDefaultSink { node_id } => {
+                // The metadata update moves the star once WirePlumber applies it
+                crate::set_default_node(node_id);
+            }
+            GraphMessage::SetNodeVolume { node_id, volume } => {
+                crate::set_node_volume(node_id, volume.clamp(0.0, 1.0));
+            }
+            GraphMessage::ToggleMute { node_id } => {
+                crate::toggle_node_mute(node_id);
+            }
+            GraphMessage::ScrollPorts { node_id, rows } => {
+                let Some(node) = self.nodes.get(&node_id) else {
+                    return Task::none();
+                };
+                let max_offset = node.input_ports.len().max(node.output_ports.len())
+                    .saturating_sub(self.max_visible_ports);
+                let offset = self.port_scroll_offsets.get(&node_id).copied().unwrap_or(0);
+                let offset = offset.saturating_add_signed(rows as isize).min(max_offset);
+                if offset == 0 {
+                    self.port_scroll_offsets.remove(&node_id);
+                } else {
+                    self.port_scroll_offsets.insert(node_id, offset);
+                }
+                self.invalidate();
+            }
+            GraphMessage::ShowAllNodes => {
+                let count = self.nodes.values().filter(|n| n.hidden).count();
+                for node in self.nodes.values_mut() {
+                    node.hidden = false;
+                }
+                config.clear_hidden_nodes();
+                self.invalidate();
+                if count > 0 {
+                    return crate::toast::notify(Toast::info(format!("Showing {} hidden nodes", count)));
+                }
+            }
+            GraphMessage::ToggleLayoutPin { node_id } => {
+                if let Some(node) = self.nodes.get_mut(&node_id) {
+                    node.layout_pinned = !node.layout_pinned;
+                    config.set_layout_pinned(&node.key(), node.layout_pinned);
+                    self.invalidate();
+                }
+            }
+            GraphMessage::DisconnectNode { node_id } => {
+                let pairs: Vec<(u32, u32)> = self.links.iter()
+                    .filter(|l| l.output_node == node_id || l.input_node == node_id)
+                    .map(|l| (l.output_port, l.input_port))
+                    .collect();
+                if !pairs.is_empty() {
+                    let task = Task::batch(
+                        pairs.iter().map(|&(output_port, input_port)| disconnect_ports(output_port, input_port, false)),
+                    );
+                    self.push_undo(UndoAction::BatchDisconnect { pairs });
+                    return task;
+                }
+            }
+            GraphMessage::DisconnectAll => {
+                let pairs: Vec<(u32, u32)> = self.links.iter().map(|l| (l.output_port, l.input_port)).collect();
+                if !pairs.is_empty() {
+                    let task = Task::batch(
+                        pairs.iter().map(|&(output_port, input_port)| disconnect_ports(output_port, input_port, false)),
+                    );
+                    self.push_undo(UndoAction::BatchDisconnect { pairs });
+                    return task;
+                }
+            }
+            GraphMessage::PinLink { output_port, input_port } => {
+                self.pinned_connections.insert((output_port, input_port));
+                if let Some(spec) = self.pinned_spec(output_port, input_port)
+                    && !self.name_pinned.contains(&spec)
+                {
+                    self.name_pinned.push(spec);
+                    config.set_pinned_specs(self.name_pinned.clone());
+                }
+            }
+            GraphMessage::UnpinLink { output_port, input_port } => {
+                self.pinned_connections.remove(&(output_port, input_port));
+                if let Some(spec) = self.pinned_spec(output_port, input_port) {
+                    self.name_pinned.retain(|s| *s != spec);
+                    config.set_pinned_specs(self.name_pinned.clone());
+                }
+            }
+            GraphMessage::ReconnectPinned => {
+                // Re-resolve names; a name shared by several ports connects them all
+                let mut tasks = Vec::new();
+                for spec in &self.name_pinned {
+                    let outputs = self.ports_by_name(&spec.output_node_name, &spec.output_port_name, PortDirection::Output);
+                    let inputs = self.ports_by_name(&spec.input_node_name, &spec.input_port_name, PortDirection::Input);
+                    for &output_port in &outputs {
+                        for &input_port in &inputs {
+                            let linked = self.links.iter()
+                                .any(|l| l.output_port == output_port && l.input_port == input_port);
+                            if !linked {
+                                tasks.push(connect_ports(&self.nodes, &self.links, output_port, input_port, false));
+                            }
+                            self.pinned_connections.insert((output_port, input_port));
+                        }
+                    }
+                }
+                return Task::batch(tasks);
+            }
+            GraphMessage::HoverChanged { node_id, port } => {
+                self.hovered_node = node_id.or(port.map(|(id, _)| id));
+                if node_id != self.hovered_node_timer.map(|(id, _)| id) {
+                    self.hovered_node_timer = node_id.map(|id| (id, std::time::Instant::now()));
+                    self.show_tooltip = None;
+                }
+                if port != self.hovered_port {
+                    self.hovered_port = port;
+                    self.hovered_port_since = port.map(|_| std::time::Instant::now());
+                    self.show_port_tooltip = false;
+                }
+            }
+            GraphMessage::ShowError(message) => {
+                return crate::toast::notify(Toast::error(message));
+            }
+            GraphMessage::ExportPng { viewport } => {
+                // Hide overlays and toasts before the window is captured
+                self.exporting = true;
+                return iced::window::latest()
+                    .and_then(iced::window::screenshot)
+                    .map(move |shot| Message::Graph(GraphMessage::ScreenshotTaken { shot, viewport }));
+            }
+            GraphMessage::ScreenshotTaken { shot, viewport } => {
+                self.exporting = false;
+                // The canvas sits at the window's top-left, above the status bar
+                let region = Rectangle {
+                    x: 0,
+                    y: 0,
+                    width: ((viewport.width * shot.scale_factor) as u32).min(shot.size.width),
+                    height: ((viewport.height * shot.scale_factor) as u32).min(shot.size.height),
+                };
+                let shot = shot.crop(region).unwrap_or(shot);
+                return crate::toast::notify(match crate::export::save_png(&shot.rgba, shot.size.width, shot.size.height) {
+                    Some(path) => Toast::info(format!("Saved {}", path.display())),
+                    None => Toast::error("Failed to save screenshot"),
+                });
+            }
+            GraphMessage::ExportSvg => {
+                let svg = crate::export::export_svg(&self.nodes, &self.links, self.zoom);
+                return crate::toast::notify(match crate::export::save_svg(&svg) {
+                    Some(path) => Toast::info(format!("Saved {}", path.display())),
+                    None => Toast::error("Failed to save SVG"),
+                });
+            }
+            GraphMessage::SwitchProfile(profile) => {
+                if profile != config.profile {
+                    let _ = config.save_atomic();
+                    *config = Config::load_or_default(&profile);
+                    let _ = Config::set_last_profile(&profile);
+                    self.reload_config(config, None);
+                    return crate::toast::notify(Toast::info(format!("Switched to profile {}", profile)));
+                }
+            }
+            GraphMessage::ExportScript => {
+                return Task::perform(
+                    crate::pick_save_file("Export connections", "sh", "solder-connections.sh".to_string()),
+                    |path| path,
+                )
+                .and_then(|path| Task::done(Message::Graph(GraphMessage::SaveScript { path })));
+            }
+            GraphMessage::SaveScript { path } => {
+                let script = crate::export::live_connections_to_script(&self.nodes, &self.links);
+                return crate::toast::notify(match crate::export::save_script(&path, &script) {
+                    Some(()) => Toast::info(format!("Saved {}", path.display())),
+                    None => Toast::error("Failed to save script"),
+                });
+            }
+            GraphMessage::PickQpwgraphExport => {
+                return Task::perform(
+                    crate::pick_save_file("Export qpwgraph patchbay", "qpwgraph", "solder.qpwgraph".to_string()),
+                    |path| path,
+                )
+                .and_then(|path| Task::done(Message::Graph(GraphMessage::ExportQpwgraph { path })));
+            }
+            GraphMessage::PickDotExport => {
+                return Task::perform(
+                    crate::pick_save_file("Export Graphviz graph", "dot", "solder.dot".to_string()),
+                    |path| path,
+                )
+                .and_then(|path| Task::done(Message::Graph(GraphMessage::ExportDot { path })));
+            }
+            GraphMessage::ExportDot { path } => {
+                let dot = crate::export::graph_to_dot(&self.nodes, &self.links);
+                return crate::toast::notify(match crate::export::save_dot(&path, &dot) {
+                    Some(()) => Toast::info(format!("Saved {}", path.display())),
+                    None => Toast::error("Failed to save graph"),
+                });
+            }
+            GraphMessage::ExportMermaid => {
+                return Task::perform(
+                    crate::pick_save_file("Export Mermaid diagram", "md", "solder-routing.md".to_string()),
+                    |path| path,
+                )
+                .and_then(|path| Task::done(Message::Graph(GraphMessage::SaveMermaid { path })));
+            }
+            GraphMessage::SaveMermaid { path } => {
+                return crate::toast::notify(match crate::export::save_mermaid(&path, &self.nodes, &self.links) {
+                    Ok(()) => Toast::info(format!("Saved {}", path.display())),
+                    Err(error) => Toast::error(format!("Failed to save {}: {}", path.display(), error)),
+                });
+            }
+            GraphMessage::ExportPresetYaml => {
+                let preset = self.preset_or_live_links();
+                let file_name = format!("{}.yaml", preset.name);
+                return Task::perform(crate::pick_save_file("Export preset as YAML", "yaml", file_name), |path| path)
+                    .and_then(|path| Task::done(Message::Graph(GraphMessage::SavePresetYaml { path })));
+            }
+            GraphMessage::SavePresetYaml { path } => {
+                // save() picks the format from the extension
+                let path = match crate::config::PresetFormat::from_path(&path) {
+                    crate::config::PresetFormat::Yaml => path,
+                    crate::config::PresetFormat::Json => path.with_extension("yaml"),
+                };
+                return crate::toast::notify(match self.preset_or_live_links().save(&path) {
+                    Ok(()) => Toast::info(format!("Saved {}", path.display())),
+                    Err(error) => Toast::error(format!("Failed to save {}: {}", path.display(), error)),
+                });
+            }
+            GraphMessage::PickWireplumberExport => {
+                return Task::perform(
+                    crate::pick_save_file("Export WirePlumber script", "lua", "solder-links.lua".to_string()),
+                    |path| path,
+                )
+                .and_then(|path| Task::done(Message::Graph(GraphMessage::ExportWireplumberLua { path })));
+            }
+            GraphMessage::ExportWireplumberLua { path } => {
+                let lua = crate::export::preset_to_wireplumber_lua(&self.preset_or_live_links());
+                return crate::toast::notify(match std::fs::write(&path, lua) {
+                    Ok(()) => Toast::info(format!("Saved {}", path.display())),
+                    Err(error) => Toast::error(format!("Failed to save {}: {}", path.display(), error)),
+                });
+            }
+            GraphMessage::ExportQpwgraph { path } => {
+                let preset = self.preset_or_live_links();
+                let xml = crate::export::preset_to_qpwgraph_xml(&preset, &self.nodes);
+                return crate::toast::notify(match std::fs::write(&path, xml) {
+                    Ok(()) => Toast::info(format!("Saved {}", path.display())),
+                    Err(_) => Toast::error("Failed to save patchbay"),
+                });
+            }
+            GraphMessage::PickQpwgraphImport => {
+                return Task::perform(crate::pick_open_file("Import qpwgraph patchbay", "qpwgraph"), |path| path)
+                    .and_then(|path| Task::done(Message::Graph(GraphMessage::ImportQpwgraph { path })));
+            }
+            GraphMessage::ImportQpwgraph { path } => {
+                let preset = std::fs::read_to_string(&path).ok()
+                    .and_then(|xml| crate::export::preset_from_qpwgraph_xml(&xml));
+                let Some(preset) = preset else {
+                    return self.update(GraphMessage::ShowError(format!("Could not import {}", path.display())), config);
+                };
+                let (connected, task) = self.apply_preset(&preset, Vec::new(), config);
+                let toast = Toast::info(format!("Imported {} ({} connections)", preset.name, connected));
+                // Not a solder preset file, so there is nowhere to save new versions
+                self.current_preset = Some(preset);
+                self.preset_path = None;
+                self.active_preset_slot = None;
+                self.preset_delta = None;
+                self.invalidate();
+                return Task::batch([task, crate::toast::notify(toast)]);
+            }
+            GraphMessage::ShowContextMenu { position, target, viewport } => {
+                let items = self.context_menu_items(target, viewport);
+                if !items.is_empty() {
+                    self.context_menu = Some(ContextMenu { position, items });
+                    self.context_menu_position = position;
+                }
+            }
+            GraphMessage::ContextMenuSelect { index } => {
+                if let Some(menu) = self.context_menu.take()
+                    && let Some((_, message)) = menu.items.into_iter().nth(index)
+                {
+                    return self.update(message, config);
+                }
+            }
+            GraphMessage::DismissContextMenu => {
+                self.context_menu = None;
+            }
+            GraphMessage::PendingRemovalTick => {
+                for id in std::mem::take(&mut self.pending_removals) {
+                    self.finalize_node_removal(id, config);
+                }
+            }
+            GraphMessage::Tick => {
+                // Auto-accept the stereo prompt once it times out
+                if let Some(prompt) = &self.stereo_prompt
+                    && std::time::Instant::now() >= prompt.deadline
+                {
+                    let (second_output, second_input) = (prompt.second_output, prompt.second_input);
+                    return self.update(GraphMessage::ConfirmStereoPair { second_output, second_input }, config);
+                }
+            }
+            GraphMessage::ConnectionCancelled => {
+                self.connectable_ports = None;
+                self.invalidate();
+            }
+            GraphMessage::ConnectionResult { success, output_port, input_port, error, undo_pushed } => {
+                if success {
+                    return Task::none();
+                }
+                self.auto_preset_requests.remove(&(output_port, input_port));
+                // The link never changed, so its optimistic undo entry has nothing to revert
+                if undo_pushed {
+                    self.drop_optimistic_undo(output_port, input_port);
+                }
+                let message = error.unwrap_or_else(|| "pw-link failed".to_string());
+                let link = format!("{} → {}", port_label(&self.nodes, output_port), port_label(&self.nodes, input_port));
+                return crate::toast::notify(Toast::error(format!("{}: {}", link, message)));
+            }
+            GraphMessage::DuplicateLinkSkipped { output_port, input_port, undo_pushed } => {
+                // Nothing was linked, so undoing the entry would remove the existing link
+                if undo_pushed {
+                    self.drop_optimistic_undo(output_port, input_port);
+                }
+                return crate::toast::notify(Toast::debug("Link already exists, skipping"));
+            }
+            GraphMessage::DisconnectLink { link_id: _, output_port, input_port } => {
+                // Disconnect and track for undo
+                self.push_undo(UndoAction::Disconnect { output_port, input_port });
+                return disconnect_ports(output_port, input_port, true);
+            }
+            GraphMessage::ReconnectLink { old_output, old_input, new_output, new_input } => {
+                self.invalidate();
+                if self.links.iter().any(|l| l.output_port == new_output && l.input_port == new_input) {
+                    return crate::toast::notify(Toast::info("Those ports are already linked"));
+                }
+                self.push_undo(UndoAction::Reconnect {
+                    old: (old_output, old_input),
+                    new: (new_output, new_input),
+                });
+                // Link the new pair first and only drop the old link once that
+                // worked, so a failure leaves the graph as it was
+                let names = port_link_name(&self.nodes, new_output).zip(port_link_name(&self.nodes, new_input));
+                return Task::perform(crate::pipewire_connect(new_output, new_input, names), |result| result)
+                    .then(move |result| match result {
+                        Ok(()) => disconnect_ports(old_output, old_input, false),
+                        Err(error) => Task::done(connection_result(Err(error), new_output, new_input, true)),
+                    });
+            }
+            GraphMessage::Pan(delta) => {
+                self.pan_offset += delta;
+                self.invalidate_view();
+            }
+            GraphMessage::Zoom { delta, cursor } => {
+                let old_zoom = self.zoom;
+                self.zoom = (self.zoom * (1.0 + delta * 0.1)).clamp(0.25, 4.0);
+
+                // Zoom towards cursor
+                let cursor_world_x = (cursor.x - self.pan_offset.x) / old_zoom;
+                let cursor_world_y = (cursor.y - self.pan_offset.y) / old_zoom;
+                self.pan_offset.x = cursor.x - cursor_world_x * self.zoom;
+                self.pan_offset.y = cursor.y - cursor_world_y * self.zoom;
+                self.invalidate_view();
+            }
+            GraphMessage::ZoomToFit { viewport } => {
+                const PADDING: f32 = 50.0;
+                match self.nodes_bounds() {
+                    Some(rect) => {
+                        let rect = rect.expand(PADDING);
+                        self.zoom = (viewport.width / rect.width)
+                            .min(viewport.height / rect.height)
+                            .clamp(0.25, 4.0);
+                        let center = rect.center();
+                        self.pan_offset = Vector::new(
+                            viewport.width / 2.0 - center.x * self.zoom,
+                            viewport.height / 2.0 - center.y * self.zoom,
+                        );
+                    }
+                    None => {
+                        self.zoom = 1.0;
+                        self.pan_offset = Vector::ZERO;
+                    }
+                }
+                self.invalidate_view();
+            }
+            GraphMessage::ToggleNodeCollapse { node_id } => {
+                if let Some(node) = self.nodes.get_mut(&node_id) {
+                    node.collapsed = !node.collapsed;
+                    config.set_node_collapsed(&node.key(), node.collapsed);
+                    self.invalidate();
+                }
+            }
+            GraphMessage::FocusNode { node_id, viewport } => {
+                if let Some(node) = self.nodes.get(&node_id) {
+                    self.focused_node = Some(node_id);
+
+                    // Pan just enough to bring the node on screen
+                    const MARGIN: f32 = 40.0;
+                    let top_left = Point::new(
+                        node.position.x * self.zoom + self.pan_offset.x,
+                        node.position.y * self.zoom + self.pan_offset.y,
+                    );
+                    let size = Size::new(NODE_WIDTH * self.zoom, self.visible_node_height(node) * self.zoom);
+                    if top_left.x < MARGIN {
+                        self.pan_offset.x += MARGIN - top_left.x;
+                    } else if top_left.x + size.width > viewport.width - MARGIN {
+                        self.pan_offset.x -= top_left.x + size.width - (viewport.width - MARGIN);
+                    }
+                    if top_left.y < MARGIN {
+                        self.pan_offset.y += MARGIN - top_left.y;
+                    } else if top_left.y + size.height > viewport.height - MARGIN {
+                        self.pan_offset.y -= top_left.y + size.height - (viewport.height - MARGIN);
+                    }
+                    self.invalidate();
+                }
+            }
+            GraphMessage::ClearFocus => {
+                self.focused_node = None;
+                self.invalidate();
+            }
+            GraphMessage::StartRename { node_id } => {
+                if let Some(node) = self.nodes.get(&node_id) {
+                    self.renaming_node = Some(node_id);
+                    self.rename_text = node.custom_name.clone().unwrap_or_else(|| node.name.clone());
+                    self.invalidate();
+                }
+            }
+            GraphMessage::RenameInput { text } => {
+                self.rename_text.push_str(&text);
+                self.invalidate();
+            }
+            GraphMessage::RenameBackspace => {
+                self.rename_text.pop();
+                self.invalidate();
+            }
+            GraphMessage::CommitRename => {
+                if let Some(node) = self.renaming_node.take().and_then(|id| self.nodes.get_mut(&id)) {
+                    let node_id = node.id;
+                    let key = node.key();
+                    let old_name = config.get_node_rename(&key).cloned();
+                    let new_name = self.rename_text.trim().to_string();
+                    // Empty name (or the original name) removes the custom name
+                    let new_name = (!new_name.is_empty() && new_name != node.name).then_some(new_name);
+                    node.custom_name = new_name.clone();
+                    match &new_name {
+                        Some(name) => config.set_node_rename(key.clone(), name.clone()),
+                        None => config.clear_node_rename(&key),
+                    }
+                    if new_name != old_name {
+                        self.push_undo(UndoAction::Rename { node_id, key, old_name, new_name });
+                    }
+                }
+                self.rename_text.clear();
+                self.invalidate();
+            }
+            GraphMessage::CancelRename => {
+                self.renaming_node = None;
+                self.rename_text.clear();
+                self.invalidate();
+            }
+            GraphMessage::SetPortTypeFilter(filter) => {
+                self.port_type_filter = filter;
+                self.invalidate();
+            }
+            GraphMessage::SetMediaClassFilter(filter) => {
+                self.media_class_filter = filter;
+                self.invalidate();
+            }
+            GraphMessage::ToggleMinimap => {
+                self.show_minimap = !self.show_minimap;
+            }
+            GraphMessage::CenterOn { world, viewport } => {
+                self.pan_offset = Vector::new(
+                    viewport.width / 2.0 - world.x * self.zoom,
+                    viewport.height / 2.0 - world.y * self.zoom,
+                );
+                self.invalidate_view();
+            }
+            GraphMessage::ToggleGridSnap => {
+                config.toggle_snap_to_grid();
+            }
+            GraphMessage::AutoLayout => {
+                self.run_layout(config, |graph, config| match config.layout_algorithm {
+                    LayoutAlgorithm::ColumnBfs => graph.perform_auto_layout(config),
+                    LayoutAlgorithm::ForceDirected => graph.perform_force_layout(config),
+                });
+            }
+            GraphMessage::ForceLayout => {
+                self.run_layout(config, |graph, config| graph.perform_force_layout(config));
+            }
+            GraphMessage::ToggleLayoutDirection => {
+                config.toggle_layout_direction();
+                let direction = match config.layout_direction {
+                    LayoutDirection::Horizontal => "left to right",
+                    LayoutDirection::Vertical => "top to bottom",
+                };
+                let layout = self.update(GraphMessage::AutoLayout, config);
+                return Task::batch([layout, crate::toast::notify(Toast::info(format!("Layout flows {}", direction)))]);
+            }
+            GraphMessage::IncrementalLayout => {
+                self.run_layout(config, |graph, _| graph.perform_incremental_layout());
+            }
+            GraphMessage::AnimationTick => {
+                let now = std::time::Instant::now();
+                let delta_ms = self.last_tick
+                    .map(|t| now.duration_since(t).as_secs_f32() * 1000.0)
+                    .unwrap_or(0.0)
+                    .min(100.0); // don't jump after a stall
+                self.last_tick = Some(now);
+
+                // Advance flow dots on active links
+                for pos in self.link_dot_positions.values_mut() {
+                    *pos = (*pos + LINK_DOT_SPEED * delta_ms).fract();
+                }
+                self.negotiation_pulse = (self.negotiation_pulse + delta_ms / NEGOTIATION_PULSE_PERIOD_MS).fract();
+
+                // Show the node tooltip once the hover delay has passed
+                if let Some((id, since)) = self.hovered_node_timer
+                    && self.show_tooltip.is_none()
+                    && since.elapsed() >= NODE_TOOLTIP_DELAY
+                {
+                    self.show_tooltip = Some(id);
+                }
+                if self.hovered_port_since.is_some_and(|since| since.elapsed() >= PORT_TOOLTIP_DELAY) {
+                    self.show_port_tooltip = true;
+                }
+
+                // Drop ghosts whose node didn't come back in time
+                let grace = std::time::Duration::from_millis(config.node_grace_period_ms);
+                let departed = self.departed_nodes.len();
+                self.departed_nodes.retain(|_, (_, removed)| removed.elapsed() < grace);
+                if self.departed_nodes.len() != departed {
+                    self.invalidate_nodes();
+                }
+
+                // Flow dots and tooltips are drawn uncached; only tweening
+                // nodes need the cached layers redrawn
+                if !self.is_tweening() {
+                    return Task::none();
+                }
+                // Scale the per-frame factor so the tween speed doesn't depend on the frame rate
+                let factor = 1.0 - (1.0 - LAYOUT_TWEEN_FACTOR).powf(delta_ms / 16.0);
+                for node in self.nodes.values_mut() {
+                    if let Some(target) = node.target_position {
+                        let delta = target - node.position;
+                        if delta.x.abs() < LAYOUT_TWEEN_SNAP && delta.y.abs() < LAYOUT_TWEEN_SNAP {
+                            node.position = target;
+                            node.target_position = None;
+                        } else {
+                            node.position += delta * factor;
+                        }
+                    }
+                }
+                self.invalidate();
+            }
+            GraphMessage::Undo => {
+                return self.undo(config);
+            }
+            GraphMessage::Redo => {
+                if let Some(UndoEntry { action, description }) = self.redo_stack.pop() {
+                    let task = self.revert_action(&action, config);
+                    self.undo_stack.push_back(UndoEntry { action: action.inverse(), description });
+                    self.trim_undo_stack();
+                    return task;
+                }
+            }
+            GraphMessage::UndoSteps(steps) => {
+                let tasks: Vec<_> = (0..steps).map(|_| self.undo(config)).collect();
+                return Task::batch(tasks);
+            }
+            GraphMessage::ToggleHistoryPanel => {
+                self.show_history = !self.show_history;
+            }
+            GraphMessage::ToggleHelp => {
+                self.show_help = !self.show_help;
+                self.invalidate();
+            }
+            GraphMessage::SearchActivate => {
+                self.search_active = true;
+                self.search_query.clear();
+                self.filtered_nodes.clear();
+                self.filtered_ports.clear();
+                self.search_error = None;
+                self.invalidate();
+            }
+            GraphMessage::SearchInput { text } => {
+                self.search_active = true;
+                self.search_query.push_str(&text);
+                self.update_search_filter();
+                self.invalidate();
+            }
+            GraphMessage::SearchBackspace => {
+                self.search_query.pop();
+                self.update_search_filter();
+                self.invalidate();
+            }
+            GraphMessage::SearchClear => {
+                self.search_active = false;
+                self.search_query.clear();
+                self.filtered_nodes.clear();
+                self.filtered_ports.clear();
+                self.search_error = None;
+                self.invalidate();
+            }
+            GraphMessage::SearchCommit => {
+                self.cycle_search_match(false);
+                self.invalidate();
+            }
+            GraphMessage::SearchPrev => {
+                self.cycle_search_match(true);
+                self.invalidate();
+            }
+            GraphMessage::DeactivateDevice { device_id } => {
+                crate::set_device_profile(device_id, 0);
+                // PipeWire will send NodeRemoved events → ghost appears via handle_pipewire_event
+            }
+            GraphMessage::ShowProfilePicker { ghost_index } => {
+                if let Some(ghost) = self.ghost_nodes.get(ghost_index) {
+                    let device_id = ghost.device_id;
+                    let device_name = ghost.device_name.clone();
+                    let last_used = config.get_device_profile(&device_name);
                     self.profile_picker = Some(ProfilePickerState {
                         device_id,
                         device_name,
@@ -431,7 +2087,7 @@ impl Graph {
                         position: ghost.position,
                         last_used_index: last_used,
                     });
-                    self.cache.clear();
+                    self.invalidate();
                     return Task::perform(
                         crate::load_device_profiles(device_id),
                         move |profiles| Message::Graph(GraphMessage::ProfilesLoaded { device_id, profiles }),
@@ -439,31 +2095,54 @@ impl Graph {
                 }
             }
             GraphMessage::ProfilesLoaded { device_id, profiles } => {
-                if let Some(ref mut picker) = self.profile_picker {
-                    if picker.device_id == device_id {
-                        picker.profiles = profiles;
-                        self.cache.clear();
-                    }
+                if let Some(ref mut picker) = self.profile_picker
+                    && picker.device_id == device_id
+                {
+                    picker.profiles = profiles.clone();
+                    self.invalidate();
+                }
+                let menu_pending = self.profile_menu_device == Some(device_id);
+                self.device_profiles.insert(device_id, profiles);
+                if menu_pending {
+                    self.profile_menu_device = None;
+                    return self.show_profile_menu(device_id);
+                }
+            }
+            GraphMessage::LoadDeviceProfiles { device_id } => {
+                self.profile_menu_device = Some(device_id);
+                return Task::perform(
+                    crate::load_device_profiles(device_id),
+                    move |profiles| Message::Graph(GraphMessage::ProfilesLoaded { device_id, profiles }),
+                );
+            }
+            GraphMessage::SetDeviceProfile { device_id, profile_index } => {
+                // Remember it for reactivating the device from its ghost
+                if let Some(device) = self.devices.get(&device_id) {
+                    config.set_device_profile(device.name.clone(), profile_index);
                 }
+                crate::set_device_profile(device_id, profile_index);
             }
             GraphMessage::ActivateDeviceProfile { device_id, device_name, profile_index } => {
                 config.set_device_profile(device_name, profile_index);
                 crate::set_device_profile(device_id, profile_index);
                 self.profile_picker = None;
-                self.cache.clear();
+                self.invalidate();
                 // PipeWire will send NodeAdded events → ghost removed via handle_pipewire_event
             }
             GraphMessage::DismissProfilePicker => {
                 self.profile_picker = None;
-                self.cache.clear();
+                self.invalidate();
             }
             GraphMessage::GhostDragged { ghost_index, delta } => {
                 if let Some(ghost) = self.ghost_nodes.get_mut(ghost_index) {
-                    ghost.position = ghost.position + delta / self.zoom;
-                    self.cache.clear();
+                    let raw = self.ghost_drag_position.get_or_insert(ghost.position);
+                    *raw += delta / self.zoom;
+                    ghost.position = drag_snap(*raw, config);
+                    self.invalidate();
                 }
             }
             GraphMessage::GhostDragEnded { ghost_index } => {
+                self.ghost_drag_position = None;
                 if let Some(ghost) = self.ghost_nodes.get_mut(ghost_index) {
                     ghost.has_saved_position = true;
                     config.set_device_position(
@@ -476,94 +2155,638 @@ impl Graph {
         Task::none()
     }
 
+    /// Middle of a node as drawn
+    fn node_center(&self, node: &Node) -> Point {
+        Point::new(node.position.x + NODE_WIDTH / 2.0, node.position.y + self.visible_node_height(node) / 2.0)
+    }
+
+    /// Closest node from the focused one in a direction (unit axis vector)
+    fn node_in_direction(&self, direction: Vector) -> Option<u32> {
+        let Some(current) = self.focused_node.and_then(|id| self.nodes.get(&id)) else {
+            return self.nodes.keys().min().copied();
+        };
+        let origin = self.node_center(current);
+        self.nodes.values()
+            .filter(|n| n.id != current.id)
+            .filter_map(|n| {
+                let center = self.node_center(n);
+                let offset = center - origin;
+                // Must lie on the requested side of the focused node
+                if offset.x * direction.x + offset.y * direction.y <= 0.0 {
+                    return None;
+                }
+                Some((n.id, origin.distance(center)))
+            })
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(id, _)| id)
+    }
+
+    /// Next or previous node by ID, wrapping around
+    fn node_in_sequence(&self, backwards: bool) -> Option<u32> {
+        let mut ids: Vec<u32> = self.nodes.keys().copied().collect();
+        ids.sort();
+        let current = self.focused_node.and_then(|id| ids.iter().position(|&i| i == id));
+        let next = match (current, backwards) {
+            (None, false) => 0,
+            (None, true) => ids.len().checked_sub(1)?,
+            (Some(i), false) => (i + 1) % ids.len(),
+            (Some(i), true) => (i + ids.len() - 1) % ids.len(),
+        };
+        ids.get(next).copied()
+    }
+
+    /// World-space bounding box of all nodes
+    fn nodes_bounds(&self) -> Option<Rectangle> {
+        self.nodes.values()
+            .map(|n| Rectangle::new(n.position, Size::new(NODE_WIDTH, self.visible_node_height(n))))
+            .reduce(|a, b| a.union(&b))
+    }
+
+    /// Minimap placement for a canvas size: (screen rect, world bounds shown, world→minimap scale)
+    fn minimap_transform(&self, size: Size) -> Option<(Rectangle, Rectangle, f32)> {
+        let world = self.nodes_bounds()?.expand(40.0);
+        let screen = Rectangle::new(
+            Point::new(
+                size.width - MINIMAP_SIZE.width - MINIMAP_MARGIN,
+                size.height - MINIMAP_SIZE.height - MINIMAP_MARGIN,
+            ),
+            MINIMAP_SIZE,
+        );
+        let scale = (MINIMAP_SIZE.width / world.width).min(MINIMAP_SIZE.height / world.height);
+        Some((screen, world, scale))
+    }
+
+    /// Convert a point inside the minimap to world coordinates
+    fn minimap_to_world(&self, size: Size, point: Point) -> Option<Point> {
+        let (screen, world, scale) = self.minimap_transform(size)?;
+        if !screen.contains(point) {
+            return None;
+        }
+        // Content is centered inside the minimap box
+        let offset_x = (MINIMAP_SIZE.width - world.width * scale) / 2.0;
+        let offset_y = (MINIMAP_SIZE.height - world.height * scale) / 2.0;
+        Some(Point::new(
+            world.x + (point.x - screen.x - offset_x) / scale,
+            world.y + (point.y - screen.y - offset_y) / scale,
+        ))
+    }
+
+    /// Look up a port (and its node) by port id alone
+    fn find_port(&self, port_id: u32) -> Option<(&Node, &Port)> {
+        self.nodes.values().find_map(|node| {
+            node.input_ports.iter()
+                .chain(node.output_ports.iter())
+                .find(|p| p.id == port_id)
+                .map(|port| (node, port))
+        })
+    }
+
+    /// World position of a port looked up by id alone
+    fn find_port_position(&self, port_id: u32) -> Option<Point> {
+        self.find_port(port_id).map(|(node, port)| self.visible_port_position(node, port))
+    }
+
+    /// Grab handles on a link's cable just outside each port's hit area
+    /// (output end, input end), used to drag an endpoint to another port
+    fn link_endpoint_handles(&self, link: &Link) -> Option<(Point, Point)> {
+        let start = self.find_port_position(link.output_port)?;
+        let end = self.find_port_position(link.input_port)?;
+        let (ctrl1, ctrl2) = link_control_points(start, end);
+        let reach = self.port_hit_radius + 4.0;
+        let handle_near = |anchor: Point, from_end: bool| {
+            (1..=25).map(|i| {
+                let t = i as f32 * 0.02;
+                Self::cubic_bezier(start, ctrl1, ctrl2, end, if from_end { 1.0 - t } else { t })
+            })
+            .find(|p| p.distance(anchor) >= reach)
+        };
+        Some((handle_near(start, false)?, handle_near(end, true)?))
+    }
+
+    /// Resolve a preset connection to live (output_port_id, input_port_id)
+    fn resolve_preset_connection(&self, conn: &crate::preset::PresetConnection) -> Option<(u32, u32)> {
+        let find_port = |matcher: &crate::preset::NodeMatcher, port_name: &str, direction: PortDirection| {
+            self.nodes.values()
+                .filter(|n| matcher.matches(&n.name, n.app_name.as_deref(), n.object_path.as_deref()))
+                .find_map(|n| {
+                    let ports = match direction {
+                        PortDirection::Output => &n.output_ports,
+                        PortDirection::Input => &n.input_ports,
+                    };
+                    ports.iter().find(|p| p.name == port_name).map(|p| p.id)
+                })
+        };
+        let output = find_port(&conn.output_node, &conn.output_port, PortDirection::Output)?;
+        let input = find_port(&conn.input_node, &conn.input_port, PortDirection::Input)?;
+        Some((output, input))
+    }
+
+    /// Connect every resolvable preset connection as one undoable batch and
+    /// apply the preset's node renames. Returns the number of new links.
+    /// Load the preset at `path` and apply it, also dropping the `removes`
+    /// links, and make it the current preset
+    fn apply_preset_file(&mut self, path: std::path::PathBuf, removes: Vec<(u32, u32)>, config: &mut Config) -> Task<Message> {
+        let Some(preset) = crate::preset::Preset::load(&path) else {
+            return self.update(GraphMessage::ShowError(format!("Could not load {}", path.display())), config);
+        };
+        let (connected, task) = self.apply_preset(&preset, removes, config);
+        // Kept in the config rather than the preset file, which may be shared or read-only
+        config.mark_preset_applied(&path);
+        let toast = Toast::info(format!("Applied {} ({} connections)", preset.name, connected));
+        config.last_preset = Some(path.to_string_lossy().into_owned());
+        config.mark_dirty();
+        self.current_preset = Some(preset);
+        self.preset_path = Some(path);
+        self.active_preset_slot = None;
+        self.preset_delta = None;
+        self.invalidate();
+        Task::batch([task, crate::toast::notify(toast)])
+    }
+
+    /// Make a preset's connections, plus whatever an exclusive preset or
+    /// `removes` disconnects, as one undo step
+    fn apply_preset(&mut self, preset: &crate::preset::Preset, removes: Vec<(u32, u32)>, config: &mut Config) -> (usize, Task<Message>) {
+        // Links that exist already (or are listed twice) are skipped; pw-link
+        // would only fail on them
+        let mut pairs: Vec<(u32, u32)> = Vec::new();
+        for (output_port, input_port) in preset.connections.iter().filter_map(|conn| self.resolve_preset_connection(conn)) {
+            let linked = self.links.iter().any(|l| l.output_port == output_port && l.input_port == input_port);
+            if linked || pairs.contains(&(output_port, input_port)) {
+                if cfg!(debug_assertions) {
+                    eprintln!(
+                        "preset {}: {} → {} already connected, skipped",
+                        preset.name,
+                        port_label(&self.nodes, output_port),
+                        port_label(&self.nodes, input_port),
+                    );
+                }
+                continue;
+            }
+            pairs.push((output_port, input_port));
+        }
+
+        let mut stale = self.exclusive_stale_links(preset);
+        for pair in removes {
+            if !stale.contains(&pair) {
+                stale.push(pair);
+            }
+        }
+        let mut tasks: Vec<Task<Message>> = stale.iter()
+            .map(|&(output_port, input_port)| disconnect_ports(output_port, input_port, false))
+            .collect();
+        tasks.extend(
+            pairs.iter().map(|&(output_port, input_port)| connect_ports(&self.nodes, &self.links, output_port, input_port, false)),
+        );
+        if !pairs.is_empty() || !stale.is_empty() {
+            self.push_undo(UndoAction::ApplyPreset {
+                name: preset.name.clone(),
+                connected: pairs.clone(),
+                disconnected: stale,
+            });
+        }
+        let task = Task::batch(tasks);
+
+        // Renames are keyed by node name
+        for node in self.nodes.values_mut() {
+            if let Some(name) = preset.node_renames.get(&node.name) {
+                node.custom_name = Some(name.clone());
+                config.set_node_rename(node.key(), name.clone());
+            }
+        }
+        (pairs.len(), task)
+    }
+
+    /// Links an exclusive preset removes: those from its matched sources that
+    /// it doesn't list. Empty unless the preset (or the config) is exclusive.
+    fn exclusive_stale_links(&self, preset: &crate::preset::Preset) -> Vec<(u32, u32)> {
+        if !preset.exclusive.unwrap_or(self.exclusive_mode) {
+            return Vec::new();
+        }
+        let wanted: Vec<(u32, u32)> = preset.connections.iter()
+            .filter_map(|conn| self.resolve_preset_connection(conn))
+            .collect();
+        self.links.iter()
+            .filter(|l| !wanted.contains(&(l.output_port, l.input_port)))
+            .filter(|l| self.nodes.get(&l.output_node).is_some_and(|node| {
+                preset.connections.iter().any(|conn| {
+                    conn.output_node.matches(&node.name, node.app_name.as_deref(), node.object_path.as_deref())
+                })
+            }))
+            .map(|l| (l.output_port, l.input_port))
+            .collect()
+    }
+
+    /// Make any auto-preset connections that involve `node_id` and can now
+    /// be resolved on both ends
+    fn apply_auto_presets(&mut self, node_id: u32) -> Task<Message> {
+        let Some(node) = self.nodes.get(&node_id) else {
+            return Task::none();
+        };
+        let involves = |matcher: &crate::preset::NodeMatcher| {
+            matcher.matches(&node.name, node.app_name.as_deref(), node.object_path.as_deref())
+        };
+        let mut tasks = Vec::new();
+        let mut requested = Vec::new();
+        for conn in self.auto_presets.iter().flat_map(|p| &p.connections) {
+            if !involves(&conn.output_node) && !involves(&conn.input_node) {
+                continue;
+            }
+            // Every PortAdded re-runs this, so skip pairs already on their way
+            if let Some((output_port, input_port)) = self.resolve_preset_connection(conn)
+                && !self.auto_preset_requests.contains(&(output_port, input_port))
+                && !self.links.iter().any(|l| l.output_port == output_port && l.input_port == input_port)
+            {
+                tasks.push(connect_ports(&self.nodes, &self.links, output_port, input_port, false));
+                requested.push((output_port, input_port));
+            }
+        }
+        self.auto_preset_requests.extend(requested);
+        Task::batch(tasks)
+    }
+
+    /// Describe a live link in preset terms (node matchers + port names)
+    fn link_to_preset_connection(&self, link: &Link) -> Option<crate::preset::PresetConnection> {
+        let matcher = |node: &Node| {
+            let mut m = crate::preset::NodeMatcher::new(node.name.clone());
+            if let Some(app) = &node.app_name {
+                m = m.with_app_name(app.clone());
+            }
+            if let Some(path) = &node.object_path {
+                m = m.with_object_path(path.clone());
+            }
+            m
+        };
+        let out_node = self.nodes.get(&link.output_node)?;
+        let in_node = self.nodes.get(&link.input_node)?;
+        let out_port = out_node.output_ports.iter().find(|p| p.id == link.output_port)?;
+        let in_port = in_node.input_ports.iter().find(|p| p.id == link.input_port)?;
+        Some(crate::preset::PresetConnection {
+            output_node: matcher(out_node),
+            output_port: out_port.name.clone(),
+            input_node: matcher(in_node),
+            input_port: in_port.name.clone(),
+            pinned: self.pinned_connections.contains(&(link.output_port, link.input_port)),
+        })
+    }
+
+    fn compute_preset_delta(&self) -> Option<PresetDelta> {
+        let preset = self.current_preset.as_ref()?;
+        let mut expected = std::collections::HashSet::new();
+        let mut unresolved = 0;
+        for conn in &preset.connections {
+            match self.resolve_preset_connection(conn) {
+                Some(ports) => {
+                    expected.insert(ports);
+                }
+                None => unresolved += 1,
+            }
+        }
+        let added = self.links.iter()
+            .filter(|l| !expected.contains(&(l.output_port, l.input_port)))
+            .map(|l| l.id)
+            .collect();
+        let missing = expected.into_iter()
+            .filter(|&(out, inp)| !self.links.iter().any(|l| l.output_port == out && l.input_port == inp))
+            .collect();
+        Some(PresetDelta { added, missing, unresolved })
+    }
+
+    /// Channel suffix of a port name (e.g. "playback_FL" → "FL")
+    fn port_channel(&self, port_id: u32) -> Option<&str> {
+        let (_, port) = self.find_port(port_id)?;
+        port.name.rsplit('_').next()
+    }
+
+    /// Find the other half of a FL/FR port pair on the same node and direction
+    fn find_stereo_pair(&self, port_id: u32) -> Option<u32> {
+        for node in self.nodes.values() {
+            for ports in [&node.input_ports, &node.output_ports] {
+                let Some(port) = ports.iter().find(|p| p.id == port_id) else {
+                    continue;
+                };
+                let (prefix, channel) = port.name.rsplit_once('_')?;
+                let partner = match channel {
+                    "FL" => "FR",
+                    "FR" => "FL",
+                    _ => return None,
+                };
+                let partner_name = format!("{}_{}", prefix, partner);
+                return ports.iter()
+                    .find(|p| p.name == partner_name && p.port_type == port.port_type)
+                    .map(|p| p.id);
+            }
+        }
+        None
+    }
+
     /// Update the filtered nodes based on search query
     fn update_search_filter(&mut self) {
         self.filtered_nodes.clear();
+        self.filtered_ports.clear();
+        self.search_error = None;
+        self.search_match_index = 0;
+        self.search_cycling = false;
         if self.search_query.is_empty() {
             return;
         }
 
-        let query_lower = self.search_query.to_lowercase();
-        for (&id, node) in &self.nodes {
-            let display_name = node.custom_name.as_ref().unwrap_or(&node.name);
-            if display_name.to_lowercase().contains(&query_lower) {
-                self.filtered_nodes.insert(id);
+        if let Some(port_query) = self.search_query.strip_prefix("port:") {
+            // "port:name" finds nodes by any of their port names
+            let port_query = port_query.to_lowercase();
+            for (&id, node) in &self.nodes {
+                for port in node.input_ports.iter().chain(node.output_ports.iter()) {
+                    if port.name.to_lowercase().contains(&port_query) {
+                        self.filtered_ports.insert((id, port.id));
+                        self.filtered_nodes.push(id);
+                    }
+                }
+            }
+        } else if let Some(pattern) = self.search_query.strip_prefix('/') {
+            // "/pattern" matches display names against a regex
+            match regex::Regex::new(pattern) {
+                Ok(re) => {
+                    for (&id, node) in &self.nodes {
+                        if re.is_match(node.custom_name.as_ref().unwrap_or(&node.name)) {
+                            self.filtered_nodes.push(id);
+                        }
+                    }
+                }
+                Err(e) => {
+                    // Syntax errors span several lines; the last one says what's wrong
+                    let message = e.to_string();
+                    self.search_error = message.lines().last().map(|l| l.trim().to_string());
+                }
+            }
+        } else {
+            let query_lower = self.search_query.to_lowercase();
+            for (&id, node) in &self.nodes {
+                let display_name = node.custom_name.as_ref().unwrap_or(&node.name);
+                if display_name.to_lowercase().contains(&query_lower) {
+                    self.filtered_nodes.push(id);
+                }
             }
         }
+
+        // Left-to-right, top-to-bottom so Enter walks the graph in reading order
+        self.filtered_nodes.dedup();
+        self.filtered_nodes.sort_by(|a, b| {
+            let (pa, pb) = (self.nodes[a].position, self.nodes[b].position);
+            pa.x.total_cmp(&pb.x).then(pa.y.total_cmp(&pb.y))
+        });
+    }
+
+    /// Step through search matches and center the view on the current one
+    fn cycle_search_match(&mut self, backwards: bool) {
+        let count = self.filtered_nodes.len();
+        if count == 0 {
+            return;
+        }
+        // The first Enter goes to the first match; later presses step from there
+        if self.search_cycling {
+            self.search_match_index = if backwards {
+                (self.search_match_index + count - 1) % count
+            } else {
+                (self.search_match_index + 1) % count
+            };
+        } else if backwards {
+            self.search_match_index = count - 1;
+        }
+        self.search_cycling = true;
+
+        if let Some(node) = self.nodes.get(&self.filtered_nodes[self.search_match_index]) {
+            // Pan to center the node
+            self.pan_offset = Vector::new(
+                -node.position.x * self.zoom + 400.0,
+                -node.position.y * self.zoom + 300.0,
+            );
+        }
     }
 
     /// Auto-layout: align connected nodes horizontally, isolate unconnected nodes
-    fn perform_auto_layout(&mut self) {
-        use std::collections::{HashMap, HashSet, VecDeque};
+    fn perform_auto_layout(&mut self, config: &Config) {
+        use std::collections::HashSet;
 
-        const COL_WIDTH: f32 = 250.0;
         const START_X: f32 = 50.0;
         const START_Y: f32 = 50.0;
-        const ROW_GAP: f32 = 25.0;  // Vertical spacing between nodes
         const ISOLATED_X: f32 = 50.0;
         const ISOLATED_GAP: f32 = 150.0;  // Extra gap between isolated and connected nodes
+        const COMPONENT_GAP: f32 = 60.0;  // Vertical gap between unrelated signal chains
+
+        let col_width = config.layout_col_width;
+        let row_gap = config.layout_row_gap;  // Spacing between nodes within a column
+        // Isolated nodes and ghosts snap like the connected columns; rounding
+        // up keeps them from sliding back into the node above
+        let grid = config.layout_grid_snap;
+        let snap_up = |v: f32| if grid > 0.0 { (v / grid).ceil() * grid } else { v };
+
+        // The layout is computed along a "main" axis (signal flow, columns)
+        // and a "cross" axis (stacking within a column). Horizontal flow is
+        // main = x; vertical flow swaps the axes so sources sit at the top.
+        let vertical = config.layout_direction == LayoutDirection::Vertical;
+        let to_point = |main: f32, cross: f32| if vertical { Point::new(cross, main) } else { Point::new(main, cross) };
+        // Heights as drawn, without borrowing self while nodes are moved
+        let (scroll_offsets, max_visible) = (self.port_scroll_offsets.clone(), self.max_visible_ports);
+        let height = |node: &Node| {
+            let offset = scroll_offsets.get(&node.id).copied().unwrap_or(0);
+            Self::node_height(node, PortWindow { offset, max_visible })
+        };
+        let main_extent = |node: &Node| if vertical { height(node) } else { NODE_WIDTH };
+        let cross_extent = |node: &Node| if vertical { NODE_WIDTH } else { height(node) };
 
-        // Reset all saved positions - L does a full re-layout
+        // Reset all saved positions - L does a full re-layout (except pinned nodes)
         for node in self.nodes.values_mut() {
-            node.has_saved_position = false;
+            node.has_saved_position = node.layout_pinned;
         }
 
-        // Build connection maps
-        let mut outgoing: HashMap<u32, Vec<u32>> = HashMap::new();
-        let mut incoming: HashMap<u32, Vec<u32>> = HashMap::new();
-        for link in &self.links {
-            outgoing.entry(link.output_node).or_default().push(link.input_node);
-            incoming.entry(link.input_node).or_default().push(link.output_node);
-        }
+        // Pinned nodes stay put and act as obstacles
+        let pinned: Vec<LayoutObstacle> = self.nodes.values()
+            .filter(|n| n.layout_pinned)
+            .map(|n| {
+                let (main, cross) = if vertical { (n.position.y, n.position.x) } else { (n.position.x, n.position.y) };
+                LayoutObstacle { main, main_len: main_extent(n), cross, cross_len: cross_extent(n) }
+            })
+            .collect();
 
         // Identify connected nodes (involved in at least one link)
+        let is_pinned = |id: &u32| self.nodes.get(id).is_some_and(|n| n.layout_pinned);
         let mut connected_nodes: HashSet<u32> = HashSet::new();
         for link in &self.links {
             connected_nodes.insert(link.output_node);
             connected_nodes.insert(link.input_node);
         }
+        connected_nodes.retain(|id| !is_pinned(id));
 
         // Separate isolated nodes (no connections at all)
         let mut isolated_nodes: Vec<u32> = Vec::new();
         for &id in self.nodes.keys() {
-            if !connected_nodes.contains(&id) {
+            if !connected_nodes.contains(&id) && !is_pinned(&id) {
                 isolated_nodes.push(id);
             }
         }
         isolated_nodes.sort();
 
-        // Place isolated nodes in a column on the left, stacked vertically
+        // Place isolated nodes in a column on the left (a row along the top
+        // when vertical), stacked along the cross axis
+        let isolated_main_len = isolated_nodes.iter()
+            .filter_map(|id| self.nodes.get(id))
+            .map(main_extent)
+            .fold(if vertical { GHOST_NODE_HEIGHT } else { NODE_WIDTH }, f32::max);
         let mut isolated_y = START_Y;
         for &id in &isolated_nodes {
-            if let Some(node) = self.nodes.get_mut(&id) {
-                if !node.has_saved_position {
-                    node.position = Point::new(ISOLATED_X, isolated_y);
-                    isolated_y += Self::node_height(node) + ROW_GAP;
-                }
+            if let Some(node) = self.nodes.get_mut(&id)
+                && !node.has_saved_position
+            {
+                let extent = cross_extent(node);
+                let slots = Self::layout_obstacles(&pinned, ISOLATED_X, isolated_main_len, &[]);
+                let y = snap_up(Self::find_free_y(isolated_y, extent, &slots, row_gap, START_Y));
+                node.position = to_point(snap_up(ISOLATED_X), y);
+                isolated_y = y + extent + row_gap;
             }
         }
 
-        // Place ghost nodes below isolated nodes in the same column
+        // Place ghost nodes after isolated nodes in the same column
         for ghost in &mut self.ghost_nodes {
-            ghost.position = Point::new(ISOLATED_X, isolated_y);
+            isolated_y = snap_up(isolated_y);
+            ghost.position = to_point(snap_up(ISOLATED_X), isolated_y);
             ghost.has_saved_position = false;
-            isolated_y += GHOST_NODE_HEIGHT + ROW_GAP;
+            isolated_y += if vertical { NODE_WIDTH } else { GHOST_NODE_HEIGHT } + row_gap;
+        }
+
+        // Calculate the main-axis offset for connected nodes (shift past the
+        // isolated column if there are isolated nodes or ghosts)
+        let has_left_column = !isolated_nodes.is_empty() || !self.ghost_nodes.is_empty();
+        let first_column_len = if vertical { isolated_main_len + row_gap } else { col_width };
+        let connected_start_x = if !has_left_column {
+            START_X
+        } else {
+            START_X + first_column_len + ISOLATED_GAP  // Shift connected graph further along
+        };
+
+        // Lay out each connected component (signal chain) on its own so
+        // unrelated chains don't interleave; largest first, stacked along
+        // the cross axis
+        let mut components = self.connected_components(&connected_nodes);
+        components.sort_by(|a, b| {
+            b.len().cmp(&a.len()).then_with(|| a.iter().min().cmp(&b.iter().min()))
+        });
+        let mut top = START_Y;
+        for component in &components {
+            let positions = self.layout_component(component, connected_start_x, top, &pinned, config);
+            let mut bottom = top;
+            for (id, pos) in positions {
+                if let Some(node) = self.nodes.get_mut(&id)
+                    && !node.has_saved_position
+                {
+                    node.position = pos;
+                    let cross = if vertical { pos.x } else { pos.y };
+                    bottom = bottom.max(cross + cross_extent(node));
+                }
+            }
+            top = bottom + COMPONENT_GAP;
+        }
+    }
+
+    /// Split nodes into groups connected by links (union-find)
+    fn connected_components(&self, nodes: &std::collections::HashSet<u32>) -> Vec<Vec<u32>> {
+        fn find(parent: &mut HashMap<u32, u32>, id: u32) -> u32 {
+            let mut root = id;
+            while let Some(&p) = parent.get(&root) && p != root {
+                root = p;
+            }
+            // Path compression
+            let mut current = id;
+            while current != root {
+                let next = parent[&current];
+                parent.insert(current, root);
+                current = next;
+            }
+            root
+        }
+
+        let mut parent: HashMap<u32, u32> = nodes.iter().map(|&id| (id, id)).collect();
+        for link in &self.links {
+            if nodes.contains(&link.output_node) && nodes.contains(&link.input_node) {
+                let a = find(&mut parent, link.output_node);
+                let b = find(&mut parent, link.input_node);
+                if a != b {
+                    parent.insert(a.max(b), a.min(b));
+                }
+            }
+        }
+
+        let mut groups: HashMap<u32, Vec<u32>> = HashMap::new();
+        let mut ids: Vec<u32> = nodes.iter().copied().collect();
+        ids.sort();
+        for id in ids {
+            let root = find(&mut parent, id);
+            groups.entry(root).or_default().push(id);
         }
+        groups.into_values().collect()
+    }
 
-        // Calculate the X offset for connected nodes (shift right if there are isolated nodes or ghosts)
-        let has_left_column = !isolated_nodes.is_empty() || !self.ghost_nodes.is_empty();
-        let connected_start_x = if !has_left_column {
-            START_X
+    /// Pinned nodes overlapping the column at `main` (of length `main_len`),
+    /// added to that column's (cross, extent) slots
+    fn layout_obstacles(pinned: &[LayoutObstacle], main: f32, main_len: f32, slots: &[(f32, f32)]) -> Vec<(f32, f32)> {
+        let mut all = slots.to_vec();
+        all.extend(pinned.iter()
+            .filter(|o| o.main < main + main_len && o.main + o.main_len > main)
+            .map(|o| (o.cross, o.cross_len)));
+        all
+    }
+
+    /// BFS column layout of one connected component, starting at
+    /// (`start_x`, `top`) along the main and cross axes. Returns the new
+    /// position of every node in it.
+    fn layout_component(
+        &self,
+        component: &[u32],
+        start_x: f32,
+        top: f32,
+        pinned: &[LayoutObstacle],
+        config: &Config,
+    ) -> HashMap<u32, Point> {
+        use std::collections::{HashSet, VecDeque};
+
+        let vertical = config.layout_direction == LayoutDirection::Vertical;
+        let row_gap = config.layout_row_gap;  // Spacing between nodes within a column
+        let members: HashSet<u32> = component.iter().copied().collect();
+
+        // Extent of a node along the cross axis (what stacks within a column)
+        let extent = |id: u32| {
+            self.nodes.get(&id)
+                .map(|n| if vertical { NODE_WIDTH } else { self.visible_node_height(n) })
+                .unwrap_or(80.0)
+        };
+        // Vertical rows must clear the component's tallest node
+        let column_len = if vertical {
+            component.iter().filter_map(|id| self.nodes.get(id)).map(|n| self.visible_node_height(n)).fold(0.0, f32::max)
         } else {
-            START_X + COL_WIDTH + ISOLATED_GAP  // Shift connected graph further right
+            NODE_WIDTH
         };
+        let col_width = if vertical {
+            config.layout_col_width.max(column_len + row_gap)
+        } else {
+            config.layout_col_width
+        };
+        let column_x = |col: usize| start_x + col as f32 * col_width;
+        let obstacles = |x: f32, slots: &[(f32, f32)]| Self::layout_obstacles(pinned, x, column_len, slots);
+
+        // Build connection maps (within the component)
+        let mut outgoing: HashMap<u32, Vec<u32>> = HashMap::new();
+        let mut incoming: HashMap<u32, Vec<u32>> = HashMap::new();
+        for link in &self.links {
+            if members.contains(&link.output_node) && members.contains(&link.input_node) {
+                outgoing.entry(link.output_node).or_default().push(link.input_node);
+                incoming.entry(link.input_node).or_default().push(link.output_node);
+            }
+        }
 
         // Classify connected nodes by ACTUAL connections (not just ports)
         let mut sources: Vec<u32> = Vec::new();
         let mut sinks: Vec<u32> = Vec::new();
         let mut processors: Vec<u32> = Vec::new();
 
-        for &id in &connected_nodes {
+        for &id in component {
             let has_incoming = incoming.contains_key(&id);
             let has_outgoing = outgoing.contains_key(&id);
 
@@ -571,7 +2794,7 @@ impl Graph {
                 (false, true) => sources.push(id),   // Only outputs = source
                 (true, false) => sinks.push(id),     // Only inputs = sink
                 (true, true) => processors.push(id), // Both = processor
-                (false, false) => {} // No connections (shouldn't happen for connected_nodes)
+                (false, false) => sources.push(id), // Only linked to pinned nodes
             }
         }
         sources.sort();
@@ -627,11 +2850,11 @@ impl Graph {
         let mut node_y: HashMap<u32, f32> = HashMap::new();
 
         // First pass: temporarily place sources to compute downstream positions
-        let mut y = START_Y;
+        let mut y = top;
         for &src in &sources {
-            let height = self.nodes.get(&src).map(|n| Self::node_height(n)).unwrap_or(80.0);
+            let height = extent(src);
             node_y.insert(src, y);
-            y += height + ROW_GAP;
+            y += height + row_gap;
         }
 
         // Compute initial Y positions for all non-source nodes
@@ -653,9 +2876,9 @@ impl Graph {
                     if !prev_col_ys.is_empty() {
                         prev_col_ys.iter().sum::<f32>() / prev_col_ys.len() as f32
                     } else {
-                        ins.iter().filter_map(|&i| node_y.get(&i).copied()).next().unwrap_or(START_Y)
+                        ins.iter().filter_map(|&i| node_y.get(&i).copied()).next().unwrap_or(top)
                     }
-                }).unwrap_or(START_Y);
+                }).unwrap_or(top);
                 (id, desired)
             }).collect();
 
@@ -664,8 +2887,8 @@ impl Graph {
 
             let slots = col_slots.entry(col).or_default();
             for (id, desired_y) in node_desired {
-                let height = self.nodes.get(&id).map(|n| Self::node_height(n)).unwrap_or(80.0);
-                let final_y = Self::find_free_y(desired_y, height, slots, ROW_GAP, START_Y);
+                let height = extent(id);
+                let final_y = Self::find_free_y(desired_y, height, &obstacles(column_x(col), slots), row_gap, top);
                 node_y.insert(id, final_y);
                 slots.push((final_y, height));
             }
@@ -680,7 +2903,7 @@ impl Graph {
 
         let mut source_desired: Vec<(u32, f32, f32)> = Vec::new();
         for &src in &sources {
-            let height = self.nodes.get(&src).map(|n| Self::node_height(n)).unwrap_or(80.0);
+            let height = extent(src);
             let outputs = outgoing.get(&src).cloned().unwrap_or_default();
             let median_y = if !outputs.is_empty() {
                 let mut ys: Vec<f32> = outputs.iter()
@@ -689,16 +2912,16 @@ impl Graph {
                 if !ys.is_empty() {
                     ys.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
                     let mid = ys.len() / 2;
-                    if ys.len() % 2 == 0 && mid > 0 {
+                    if ys.len().is_multiple_of(2) && mid > 0 {
                         (ys[mid - 1] + ys[mid]) / 2.0
                     } else {
                         ys[mid]
                     }
                 } else {
-                    START_Y
+                    top
                 }
             } else {
-                START_Y
+                top
             };
             source_desired.push((src, median_y, height));
         }
@@ -707,7 +2930,7 @@ impl Graph {
 
         let source_slots = col_slots.entry(0).or_default();
         for (src, desired_y, height) in &source_desired {
-            let final_y = Self::find_free_y(*desired_y, *height, source_slots, ROW_GAP, START_Y);
+            let final_y = Self::find_free_y(*desired_y, *height, &obstacles(column_x(0), source_slots), row_gap, top);
             node_y.insert(*src, final_y);
             source_slots.push((final_y, *height));
             source_slots.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
@@ -734,9 +2957,9 @@ impl Graph {
                     if !prev_col_ys.is_empty() {
                         prev_col_ys.iter().sum::<f32>() / prev_col_ys.len() as f32
                     } else {
-                        ins.iter().filter_map(|&i| node_y.get(&i).copied()).next().unwrap_or(START_Y)
+                        ins.iter().filter_map(|&i| node_y.get(&i).copied()).next().unwrap_or(top)
                     }
-                }).unwrap_or(START_Y);
+                }).unwrap_or(top);
 
                 // Get Y position of first output destination as group key
                 let output_group_y = outgoing.get(&id)
@@ -761,8 +2984,8 @@ impl Graph {
 
             let slots = col_slots.entry(col).or_default();
             for (id, desired_y, _) in node_desired {
-                let height = self.nodes.get(&id).map(|n| Self::node_height(n)).unwrap_or(80.0);
-                let final_y = Self::find_free_y(desired_y, height, slots, ROW_GAP, START_Y);
+                let height = extent(id);
+                let final_y = Self::find_free_y(desired_y, height, &obstacles(column_x(col), slots), row_gap, top);
                 node_y.insert(id, final_y);
                 slots.push((final_y, height));
                 slots.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
@@ -772,7 +2995,7 @@ impl Graph {
         // Third pass: reposition sources one more time based on FINAL output positions
         // This minimizes line length after downstream nodes have been positioned
         let mut final_source_desired: Vec<(u32, f32, f32)> = sources.iter().map(|&src| {
-            let height = self.nodes.get(&src).map(|n| Self::node_height(n)).unwrap_or(80.0);
+            let height = extent(src);
             let outputs = outgoing.get(&src).cloned().unwrap_or_default();
             let target_y = if !outputs.is_empty() {
                 // Use average Y of outputs (which are now in final positions)
@@ -782,9 +3005,9 @@ impl Graph {
                 let count = outputs.iter()
                     .filter(|&out| node_y.contains_key(out))
                     .count();
-                if count > 0 { sum / count as f32 } else { START_Y }
+                if count > 0 { sum / count as f32 } else { top }
             } else {
-                START_Y
+                top
             };
             (src, target_y, height)
         }).collect();
@@ -796,7 +3019,7 @@ impl Graph {
         source_slots_final.clear();
 
         for (src, desired_y, height) in final_source_desired {
-            let final_y = Self::find_free_y(desired_y, height, source_slots_final, ROW_GAP, START_Y);
+            let final_y = Self::find_free_y(desired_y, height, &obstacles(column_x(0), source_slots_final), row_gap, top);
             node_y.insert(src, final_y);
             source_slots_final.push((final_y, height));
             source_slots_final.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
@@ -804,7 +3027,7 @@ impl Graph {
 
         // Fourth pass: reposition sinks based on FINAL input positions
         let mut final_sink_desired: Vec<(u32, f32, f32)> = sinks.iter().map(|&sink| {
-            let height = self.nodes.get(&sink).map(|n| Self::node_height(n)).unwrap_or(80.0);
+            let height = extent(sink);
             let inputs = incoming.get(&sink).cloned().unwrap_or_default();
             let target_y = if !inputs.is_empty() {
                 let sum: f32 = inputs.iter()
@@ -813,9 +3036,9 @@ impl Graph {
                 let count = inputs.iter()
                     .filter(|&inp| node_y.contains_key(inp))
                     .count();
-                if count > 0 { sum / count as f32 } else { START_Y }
+                if count > 0 { sum / count as f32 } else { top }
             } else {
-                START_Y
+                top
             };
             (sink, target_y, height)
         }).collect();
@@ -827,7 +3050,7 @@ impl Graph {
         sink_slots_final.clear();
 
         for (sink, desired_y, height) in final_sink_desired {
-            let final_y = Self::find_free_y(desired_y, height, sink_slots_final, ROW_GAP, START_Y);
+            let final_y = Self::find_free_y(desired_y, height, &obstacles(column_x(sink_col), sink_slots_final), row_gap, top);
             node_y.insert(sink, final_y);
             sink_slots_final.push((final_y, height));
             sink_slots_final.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
@@ -865,7 +3088,7 @@ impl Graph {
 
                 if weight_total > 0.0 {
                     let avg = weighted_sum / weight_total;
-                    let current = node_y.get(&id).copied().unwrap_or(START_Y);
+                    let current = node_y.get(&id).copied().unwrap_or(top);
                     desired_ys.insert(id, current + (avg - current) * 0.7);
                 }
             }
@@ -879,8 +3102,8 @@ impl Graph {
                 let mut col_nodes: Vec<(u32, f32, f32)> = node_col.iter()
                     .filter(|&(_, &c)| c == col)
                     .map(|(&id, _)| {
-                        let y = node_y.get(&id).copied().unwrap_or(START_Y);
-                        let height = self.nodes.get(&id).map(|n| Self::node_height(n)).unwrap_or(80.0);
+                        let y = node_y.get(&id).copied().unwrap_or(top);
+                        let height = extent(id);
                         (id, y, height)
                     })
                     .collect();
@@ -891,7 +3114,7 @@ impl Graph {
                 for _ in 0..50 {
                     let mut any_overlap = false;
                     for i in 0..col_nodes.len().saturating_sub(1) {
-                        let needed = col_nodes[i].1 + col_nodes[i].2 + ROW_GAP;
+                        let needed = col_nodes[i].1 + col_nodes[i].2 + row_gap;
                         if col_nodes[i + 1].1 < needed {
                             let overlap = needed - col_nodes[i + 1].1;
                             col_nodes[i].1 -= overlap / 2.0;
@@ -900,14 +3123,14 @@ impl Graph {
                         }
                     }
                     // Enforce min_y
-                    if !col_nodes.is_empty() && col_nodes[0].1 < START_Y {
-                        col_nodes[0].1 = START_Y;
+                    if !col_nodes.is_empty() && col_nodes[0].1 < top {
+                        col_nodes[0].1 = top;
                     }
                     if !any_overlap { break; }
                 }
                 // Safety: ensure no overlaps remain
                 for i in 1..col_nodes.len() {
-                    let prev_bottom = col_nodes[i - 1].1 + col_nodes[i - 1].2 + ROW_GAP;
+                    let prev_bottom = col_nodes[i - 1].1 + col_nodes[i - 1].2 + row_gap;
                     if col_nodes[i].1 < prev_bottom {
                         col_nodes[i].1 = prev_bottom;
                     }
@@ -919,14 +3142,312 @@ impl Graph {
             }
         }
 
-        // Apply positions to connected nodes
-        for (&id, &col) in &node_col {
-            if let Some(node) = self.nodes.get_mut(&id) {
-                if !node.has_saved_position {
-                    let y = node_y.get(&id).copied().unwrap_or(START_Y);
-                    node.position = Point::new(connected_start_x + col as f32 * COL_WIDTH, y);
+        // Snap to a coarse grid for tidier alignment, pushing a node down a grid
+        // step whenever snapping would make it overlap the one above it
+        let grid = config.layout_grid_snap;
+        if grid > 0.0 {
+            for col in 0..=max_col {
+                let mut col_nodes: Vec<(u32, f32)> = node_col.iter()
+                    .filter(|&(_, &c)| c == col)
+                    .map(|(&id, _)| (id, node_y.get(&id).copied().unwrap_or(top)))
+                    .collect();
+                col_nodes.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+
+                let mut prev_bottom = f32::MIN;
+                for (id, y) in col_nodes {
+                    let mut snapped = (y / grid).round() * grid;
+                    if snapped < prev_bottom {
+                        snapped = (prev_bottom / grid).ceil() * grid;
+                    }
+                    let height = extent(id);
+                    prev_bottom = snapped + height + row_gap;
+                    node_y.insert(id, snapped);
+                }
+            }
+        }
+
+        // Final positions of the component's nodes
+        node_col.iter()
+            .map(|(&id, &col)| {
+                let y = node_y.get(&id).copied().unwrap_or(top);
+                let mut x = column_x(col);
+                if grid > 0.0 {
+                    x = (x / grid).round() * grid;
+                }
+                (id, if vertical { Point::new(y, x) } else { Point::new(x, y) })
+            })
+            .collect()
+    }
+
+    /// Spring-embedder layout: every pair of nodes repels, linked nodes are
+    /// pulled towards a rest length, and moves shrink as the system cools
+    fn perform_force_layout(&mut self, config: &Config) {
+        const START: f32 = 50.0;
+        const K_REPEL: f32 = 200_000.0;
+        const INITIAL_TEMPERATURE: f32 = 60.0;
+        const COOLING: f32 = 0.97;
+
+        let rest_len = config.layout_col_width;
+        let k_spring = config.spring_strength;
+
+        let mut ids: Vec<u32> = self.nodes.keys().copied().collect();
+        ids.sort();
+        if ids.is_empty() {
+            return;
+        }
+        let index: HashMap<u32, usize> = ids.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+
+        // Simulate node centers, so tall nodes don't skew the springs
+        let sizes: Vec<Size> = ids.iter()
+            .map(|id| Size::new(NODE_WIDTH, self.visible_node_height(&self.nodes[id])))
+            .collect();
+        let pinned: Vec<bool> = ids.iter().map(|id| self.nodes[id].layout_pinned).collect();
+        let mut centers: Vec<Point> = ids.iter().zip(&sizes)
+            .map(|(id, size)| {
+                let pos = self.nodes[id].position;
+                Point::new(pos.x + size.width / 2.0, pos.y + size.height / 2.0)
+            })
+            .collect();
+
+        let mut edges: Vec<(usize, usize)> = self.links.iter()
+            .filter_map(|l| Some((*index.get(&l.output_node)?, *index.get(&l.input_node)?)))
+            .filter(|(a, b)| a != b)
+            .map(|(a, b)| (a.min(b), a.max(b)))
+            .collect();
+        edges.sort();
+        edges.dedup();
+
+        let mut temperature = INITIAL_TEMPERATURE;
+        for _ in 0..config.layout_iterations {
+            let mut forces = vec![Vector::ZERO; centers.len()];
+
+            for i in 0..centers.len() {
+                for j in (i + 1)..centers.len() {
+                    let mut delta = centers[i] - centers[j];
+                    if delta.x == 0.0 && delta.y == 0.0 {
+                        // Coincident nodes: push apart along an arbitrary axis
+                        delta = Vector::new(1.0, (i + j) as f32 % 2.0 - 0.5);
+                    }
+                    let dist = (delta.x * delta.x + delta.y * delta.y).sqrt().max(1.0);
+                    let push = delta * (K_REPEL / (dist * dist) / dist);
+                    forces[i] += push;
+                    forces[j] -= push;
+                }
+            }
+
+            for &(a, b) in &edges {
+                let delta = centers[b] - centers[a];
+                let dist = (delta.x * delta.x + delta.y * delta.y).sqrt().max(1.0);
+                let pull = delta * (k_spring * (dist - rest_len) / dist);
+                forces[a] += pull;
+                forces[b] -= pull;
+            }
+
+            // Each step moves at most `temperature` pixels; pinned nodes don't move
+            for ((center, force), fixed) in centers.iter_mut().zip(&forces).zip(&pinned) {
+                if *fixed {
+                    continue;
+                }
+                let len = (force.x * force.x + force.y * force.y).sqrt();
+                if len > 0.0 {
+                    *center += *force * (len.min(temperature) / len);
+                }
+            }
+            temperature *= COOLING;
+        }
+
+        // Shift the result into view (unless pinned nodes anchor it), then
+        // snap like the column layout does
+        let offset = if pinned.contains(&true) {
+            Vector::ZERO
+        } else {
+            let min_x = centers.iter().zip(&sizes).map(|(c, s)| c.x - s.width / 2.0).fold(f32::MAX, f32::min);
+            let min_y = centers.iter().zip(&sizes).map(|(c, s)| c.y - s.height / 2.0).fold(f32::MAX, f32::min);
+            Vector::new(START - min_x, START - min_y)
+        };
+        let grid = config.layout_grid_snap;
+        for (((id, center), size), fixed) in ids.iter().zip(&centers).zip(&sizes).zip(&pinned) {
+            if *fixed {
+                continue;
+            }
+            let mut pos = Point::new(center.x - size.width / 2.0, center.y - size.height / 2.0) + offset;
+            if grid > 0.0 {
+                pos = Point::new((pos.x / grid).round() * grid, (pos.y / grid).round() * grid);
+            }
+            if let Some(node) = self.nodes.get_mut(id) {
+                node.position = pos;
+            }
+        }
+    }
+
+    fn current_positions(&self) -> HashMap<u32, Point> {
+        self.nodes.values().map(|n| (n.id, n.position)).collect()
+    }
+
+    /// Turn a layout's jump into an animation: each node moved since `start`
+    /// goes back there and tweens to its new position on AnimationTick
+    fn animate_layout_from(&mut self, start: HashMap<u32, Point>) {
+        for node in self.nodes.values_mut() {
+            if let Some(&from) = start.get(&node.id)
+                && from != node.position
+            {
+                node.target_position = Some(node.position);
+                node.position = from;
+            }
+        }
+        self.invalidate();
+    }
+
+    /// The message a rebindable shortcut maps to, if `key` is bound
+    fn shortcut_message(&self, key: &iced::keyboard::Key<&str>, modifiers: iced::keyboard::Modifiers, viewport: Size) -> Option<GraphMessage> {
+        let (action, _) = self.shortcuts.iter().find(|(_, binding)| binding_matches(binding, key, modifiers))?;
+        Some(match action.as_str() {
+            "auto_layout" => GraphMessage::AutoLayout,
+            "zoom_fit" => GraphMessage::ZoomToFit { viewport },
+            "search" => GraphMessage::SearchActivate,
+            "undo" => GraphMessage::Undo,
+            "redo" => GraphMessage::Redo,
+            "toggle_help" => GraphMessage::ToggleHelp,
+            "toggle_minimap" => GraphMessage::ToggleMinimap,
+            _ => return None,
+        })
+    }
+
+    fn undo(&mut self, config: &mut Config) -> Task<Message> {
+        let Some(UndoEntry { action, description }) = self.undo_stack.pop_back() else {
+            return Task::none();
+        };
+        let task = self.revert_action(&action, config);
+        // Push inverse action to redo stack
+        self.redo_stack.push(UndoEntry { action: action.inverse(), description });
+        task
+    }
+
+    /// Pop the undo entry pushed for an `output_port` → `input_port` request
+    /// that didn't go through
+    fn drop_optimistic_undo(&mut self, output_port: u32, input_port: u32) {
+        if self.undo_stack.back().is_some_and(|entry| entry.action.touches(output_port, input_port)) {
+            self.undo_stack.pop_back();
+        }
+    }
+
+    /// Record a new undoable action, dropping the oldest past the limit
+    fn push_undo(&mut self, action: UndoAction) {
+        let description = action.description(&self.nodes);
+        self.undo_stack.push_back(UndoEntry { action, description });
+        self.redo_stack.clear();
+        self.trim_undo_stack();
+    }
+
+    fn trim_undo_stack(&mut self) {
+        while self.undo_stack.len() > self.undo_stack_limit {
+            self.undo_stack.pop_front();
+        }
+    }
+
+    /// Run a layout pass with animation and an undo entry, and save the result
+    fn run_layout(&mut self, config: &mut Config, layout: impl FnOnce(&mut Self, &Config)) {
+        let from = self.layout_snapshot();
+        let start = self.current_positions();
+        layout(self, config);
+        // Everything on the canvas is now placed; Shift+I only moves later arrivals
+        for node in self.nodes.values_mut() {
+            node.has_saved_position = true;
+        }
+        let to = self.layout_snapshot();
+        config.set_positions(to.values().map(|(key, pos)| (key.clone(), Position { x: pos.x, y: pos.y })));
+        self.animate_layout_from(start);
+        if from.iter().any(|(id, (_, pos))| to.get(id).is_some_and(|(_, p)| p != pos)) {
+            self.push_undo(UndoAction::Layout { from, to });
+        }
+    }
+
+    /// Every node's key and resting position, ignoring running animations
+    fn layout_snapshot(&self) -> HashMap<u32, (NodeKey, Point)> {
+        self.nodes.values()
+            .map(|n| (n.id, (n.key(), n.target_position.unwrap_or(n.position))))
+            .collect()
+    }
+
+    /// Reverse an undo entry: moves and renames are applied here, the rest goes to PipeWire
+    fn revert_action(&mut self, action: &UndoAction, config: &mut Config) -> Task<Message> {
+        match action {
+            UndoAction::Move { node_id, from, .. } => {
+                if let Some(node) = self.nodes.get_mut(node_id) {
+                    node.position = *from;
+                    node.target_position = None;
+                    config.set_position(node.key(), Position { x: from.x, y: from.y });
+                    self.invalidate();
+                }
+            }
+            UndoAction::MoveGroup { moves } => {
+                for &(node_id, from, _) in moves {
+                    if let Some(node) = self.nodes.get_mut(&node_id) {
+                        node.position = from;
+                        node.target_position = None;
+                        config.set_position(node.key(), Position { x: from.x, y: from.y });
+                    }
+                }
+                self.invalidate();
+            }
+            UndoAction::Layout { from, .. } => {
+                let start = self.current_positions();
+                for (id, (key, pos)) in from {
+                    config.set_position(key.clone(), Position { x: pos.x, y: pos.y });
+                    if let Some(node) = self.nodes.get_mut(id) {
+                        node.position = *pos;
+                    }
+                }
+                self.animate_layout_from(start);
+            }
+            UndoAction::Rename { node_id, key, old_name, .. } => {
+                match old_name {
+                    Some(name) => config.set_node_rename(key.clone(), name.clone()),
+                    None => config.clear_node_rename(key),
+                }
+                if let Some(node) = self.nodes.get_mut(node_id) {
+                    node.custom_name = old_name.clone();
+                    self.invalidate();
                 }
             }
+            _ => return action.revert(&self.nodes, &self.links),
+        }
+        Task::none()
+    }
+
+    /// Whether anything needs animation frames: layout tweens, flow dots,
+    /// negotiation pulses or a pending hover tooltip
+    pub fn is_animating(&self) -> bool {
+        let negotiating = self.links.iter().any(|l| l.state == LinkState::Negotiating);
+        self.is_tweening()
+            || !self.link_dot_positions.is_empty()
+            || negotiating
+            || (self.hovered_node_timer.is_some() && self.show_tooltip.is_none())
+            || (self.hovered_port.is_some() && !self.show_port_tooltip)
+            || !self.departed_nodes.is_empty()
+    }
+
+    /// Whether any node is still moving towards its layout target
+    fn is_tweening(&self) -> bool {
+        self.nodes.values().any(|n| n.target_position.is_some())
+    }
+
+    /// Place only nodes without a saved position, around the rest of the graph
+    fn perform_incremental_layout(&mut self) {
+        let mut newcomers: Vec<u32> = self.nodes.values()
+            .filter(|n| !n.has_saved_position && !n.layout_pinned)
+            .map(|n| n.id)
+            .collect();
+        newcomers.sort();
+
+        // Take each node out while placing it so it doesn't count as its own obstacle
+        for id in newcomers {
+            let Some(node) = self.nodes.remove(&id) else {
+                continue;
+            };
+            let pos = layout::position_by_type(&self.nodes, &node);
+            let pos = self.find_non_overlapping_position(pos);
+            self.nodes.insert(id, Node { position: pos, ..node });
         }
     }
 
@@ -979,7 +3500,7 @@ impl Graph {
             .fold(min_y, f32::max)
     }
 
-    pub fn handle_pipewire_event(&mut self, event: PipewireEvent, config: &mut Config) {
+    pub fn handle_pipewire_event(&mut self, event: PipewireEvent, config: &mut Config) -> Task<Message> {
         match event {
             PipewireEvent::DeviceAdded { id, name, description, api } => {
                 let position = config.get_device_position(&name)
@@ -987,7 +3508,6 @@ impl Graph {
                 let has_saved_position = position.is_some();
 
                 self.devices.insert(id, DeviceInfo {
-                    id,
                     name: name.clone(),
                     description: description.clone(),
                     api: api.clone(),
@@ -1004,21 +3524,27 @@ impl Graph {
                         position: ghost_pos,
                         has_saved_position,
                     });
-                    self.cache.clear();
+                    self.invalidate();
                 }
             }
             PipewireEvent::DeviceRemoved { id } => {
                 self.devices.remove(&id);
+                self.device_profiles.remove(&id);
                 self.ghost_nodes.retain(|g| g.device_id != id);
                 if self.profile_picker.as_ref().is_some_and(|p| p.device_id == id) {
                     self.profile_picker = None;
                 }
-                self.cache.clear();
+                self.invalidate();
             }
-            PipewireEvent::NodeAdded { id, name, app_name, serial, object_path, device_id } => {
+            PipewireEvent::NodeAdded { id, name, app_name, serial, object_path, device_id, media_class, node_name, client_id } => {
+                // A removed id handed straight back keeps its node's place
+                let reused_position = self.pending_removals.remove(&id)
+                    .then(|| self.nodes.get(&id).map(|n| n.position))
+                    .flatten();
+
                 // Count how many nodes with same name/app/path already exist (for indexing duplicates)
                 let index = self.nodes.values()
-                    .filter(|n| n.name == name && n.app_name == app_name && n.object_path == object_path)
+                    .filter(|n| n.id != id && n.name == name && n.app_name == app_name && n.object_path == object_path)
                     .count() as u32;
 
                 let key = NodeKey {
@@ -1035,17 +3561,37 @@ impl Graph {
 
                 // Get custom name from config if set
                 let custom_name = config.get_node_rename(&key).cloned();
-
-                // Offset if another node is already at this position
-                let position = self.find_non_overlapping_position(base_position);
+                let collapsed = config.is_node_collapsed(&key);
+                let layout_pinned = config.is_layout_pinned(&key);
+                let hidden = config.is_node_hidden(&key);
+
+                // An app that reconnects within the grace period takes its
+                // ghost's place; otherwise offset from any node already there
+                let grace = std::time::Duration::from_millis(config.node_grace_period_ms);
+                let returning = self.departed_nodes.iter()
+                    .find(|(_, (n, removed))| {
+                        n.name == name && n.app_name == app_name && n.object_path == object_path
+                            && removed.elapsed() < grace
+                    })
+                    .map(|(&old_id, (n, _))| (old_id, n.position));
+                let (position, has_saved_position) = match (reused_position, returning) {
+                    (Some(position), _) => (position, true),
+                    (None, Some((old_id, position))) => {
+                        self.departed_nodes.remove(&old_id);
+                        (position, true)
+                    }
+                    (None, None) => (self.find_non_overlapping_position(base_position), has_saved_position),
+                };
 
                 // Track node in parent device
-                if let Some(dev_id) = device_id {
-                    if let Some(device) = self.devices.get_mut(&dev_id) {
+                if let Some(dev_id) = device_id
+                    && let Some(device) = self.devices.get_mut(&dev_id)
+                {
+                    if !device.active_node_ids.contains(&id) {
                         device.active_node_ids.push(id);
-                        // Remove ghost node for this device (device is now active)
-                        self.ghost_nodes.retain(|g| g.device_id != dev_id);
                     }
+                    // Remove ghost node for this device (device is now active)
+                    self.ghost_nodes.retain(|g| g.device_id != dev_id);
                 }
 
                 self.nodes.insert(
@@ -1062,49 +3608,50 @@ impl Graph {
                         input_ports: Vec::new(),
                         output_ports: Vec::new(),
                         custom_name,
-                        source: NodeSource::PipeWire,
                         device_id,
+                        media_class,
+                        state: NodeState::Creating,
+                        node_name,
+                        client_id,
+                        is_default: false,
+                        volume: None,
+                        muted: false,
+                        collapsed,
+                        layout_pinned,
+                        hidden,
+                        target_position: None,
+                        drag_start_position: None,
                     },
                 );
-                self.cache.clear();
-            }
-            PipewireEvent::NodeRemoved { id } => {
-                // Check if this node belongs to a device - may need to create ghost
-                let removed_node = self.nodes.remove(&id);
-                self.links.retain(|l| l.output_node != id && l.input_node != id);
-
-                if let Some(node) = &removed_node {
-                    if let Some(dev_id) = node.device_id {
-                        if let Some(device) = self.devices.get_mut(&dev_id) {
-                            device.active_node_ids.retain(|&nid| nid != id);
-                            // If device has no more active nodes and is ALSA, create ghost
-                            if device.active_node_ids.is_empty() && device.api == "alsa" {
-                                let ghost_already_exists = self.ghost_nodes.iter().any(|g| g.device_id == dev_id);
-                                if !ghost_already_exists {
-                                    let position = node.position;
-                                    config.set_device_position(
-                                        device.name.clone(),
-                                        Position { x: position.x, y: position.y },
-                                    );
-                                    self.ghost_nodes.push(GhostNode {
-                                        device_id: dev_id,
-                                        device_name: device.name.clone(),
-                                        display_name: device.description.clone(),
-                                        position,
-                                        has_saved_position: true,
-                                    });
-                                }
-                            }
-                        }
+                // The metadata may name a node that only appears now
+                self.resolve_default_nodes();
+                // Links that arrived before this node can be placed now
+                let (resolved, orphaned): (Vec<Link>, Vec<Link>) = std::mem::take(&mut self.orphaned_links)
+                    .into_iter()
+                    .partition(|l| self.nodes.contains_key(&l.output_node) && self.nodes.contains_key(&l.input_node));
+                self.orphaned_links = orphaned;
+                if !resolved.is_empty() {
+                    self.links.extend(resolved);
+                    if self.preset_delta.is_some() {
+                        self.preset_delta = self.compute_preset_delta();
                     }
+                    self.invalidate_links();
                 }
-
-                self.cache.clear();
+                // A fresh node has no ports yet, so no cable can touch it
+                self.invalidate_nodes();
+                return self.apply_auto_presets(id);
+            }
+            PipewireEvent::NodeRemoved { id } => {
+                // Finalized on the next PendingRemovalTick, unless PipeWire
+                // hands the id straight back to a new node
+                self.pending_removals.insert(id);
             }
             PipewireEvent::PortAdded {
                 node_id,
                 port_id,
                 name,
+                alias,
+                is_monitor,
                 direction,
                 port_type,
             } => {
@@ -1117,9 +3664,15 @@ impl Graph {
                 if let Some(node) = self.nodes.get_mut(&node_id) {
                     let port = Port {
                         id: port_id,
+                        group: port_group(&name),
                         name,
+                        alias,
+                        is_monitor,
                         direction,
                         port_type,
+                        format: None,
+                        channels: None,
+                        sample_rate: None,
                     };
                     match direction {
                         PortDirection::Input => node.input_ports.push(port),
@@ -1128,90 +3681,590 @@ impl Graph {
                 }
 
                 // Reposition based on node type (source/sink/processor)
-                if should_reposition {
-                    if let Some(node) = self.nodes.get(&node_id).cloned() {
-                        let new_pos = layout::position_by_type(&self.nodes, &node);
-                        let final_pos = self.find_non_overlapping_position(new_pos);
-                        if let Some(node) = self.nodes.get_mut(&node_id) {
-                            node.position = final_pos;
-                        }
+                if should_reposition
+                    && let Some(node) = self.nodes.get(&node_id).cloned()
+                {
+                    let new_pos = layout::position_by_type(&self.nodes, &node);
+                    let final_pos = self.find_non_overlapping_position(new_pos);
+                    if let Some(node) = self.nodes.get_mut(&node_id) {
+                        node.position = final_pos;
                     }
                 }
 
-                self.cache.clear();
-            }
-            PipewireEvent::PortRemoved { node_id, port_id } => {
-                if let Some(node) = self.nodes.get_mut(&node_id) {
-                    node.input_ports.retain(|p| p.id != port_id);
-                    node.output_ports.retain(|p| p.id != port_id);
-                    self.cache.clear();
-                }
-            }
-            PipewireEvent::LinkAdded {
-                id,
-                output_node,
-                output_port,
-                input_node,
-                input_port,
-            } => {
-                self.links.push(Link {
-                    id,
-                    output_node,
-                    output_port,
-                    input_node,
-                    input_port,
-                });
-                self.cache.clear();
+                // Ports arrive after their node, so auto-presets usually resolve here
+                self.invalidate();
+                return self.apply_auto_presets(node_id);
+            }
+            PipewireEvent::PortRemoved { node_id, port_id } => {
+                if let Some(node) = self.nodes.get_mut(&node_id) {
+                    node.input_ports.retain(|p| p.id != port_id);
+                    node.output_ports.retain(|p| p.id != port_id);
+                    self.invalidate();
+                }
+            }
+            PipewireEvent::LinkAdded {
+                id,
+                output_node,
+                output_port,
+                input_node,
+                input_port,
+            } => {
+                let link = Link {
+                    id,
+                    output_node,
+                    output_port,
+                    input_node,
+                    input_port,
+                    state: LinkState::Negotiating,
+                };
+                self.auto_preset_requests.remove(&(output_port, input_port));
+                // Keep one entry per port pair so `links` means "what is connected"
+                if self.links.iter().any(|l| l.output_port == output_port && l.input_port == input_port)
+                {
+                    eprintln!("Ignoring link {}: ports {} -> {} are already linked", id, output_port, input_port);
+                    self.duplicate_link_count += 1;
+                    return Task::none();
+                }
+                // Registry order isn't guaranteed; hold on to the link until
+                // its nodes show up
+                if !self.nodes.contains_key(&output_node) || !self.nodes.contains_key(&input_node) {
+                    self.orphaned_links.push(link);
+                    self.invalidate_links();
+                    return Task::none();
+                }
+                self.links.push(link);
+                if self.preset_delta.is_some() {
+                    self.preset_delta = self.compute_preset_delta();
+                }
+                self.invalidate_links();
+            }
+            PipewireEvent::LinkRemoved { id } => {
+                self.links.retain(|l| l.id != id);
+                self.orphaned_links.retain(|l| l.id != id);
+                self.link_dot_positions.remove(&id);
+                if self.preset_delta.is_some() {
+                    self.preset_delta = self.compute_preset_delta();
+                }
+                self.invalidate_links();
+            }
+            PipewireEvent::Disconnected => {
+                self.pipewire_disconnected = true;
+                self.clear_all_nodes();
+            }
+            PipewireEvent::Reconnected => {
+                self.pipewire_disconnected = false;
+                self.invalidate();
+                return crate::toast::notify(Toast::info("PipeWire reconnected"));
+            }
+            PipewireEvent::PortFormatChanged { port_id, format, channels, sample_rate } => {
+                let port = self.nodes.values_mut()
+                    .flat_map(|n| n.input_ports.iter_mut().chain(n.output_ports.iter_mut()))
+                    .find(|p| p.id == port_id);
+                if let Some(port) = port {
+                    port.format = Some(format);
+                    port.channels = (channels > 0).then_some(channels);
+                    port.sample_rate = (sample_rate > 0).then_some(sample_rate);
+                    self.invalidate();
+                }
+            }
+            PipewireEvent::LinkStateChanged { id, state } => {
+                // Self-loops are drawn without flow dots, so they needn't animate
+                let self_loop = self.links.iter().any(|l| l.id == id && l.is_self_loop());
+                if state == LinkState::Active && !self_loop {
+                    self.link_dot_positions.entry(id).or_insert(0.0);
+                } else {
+                    self.link_dot_positions.remove(&id);
+                }
+                if let Some(link) = self.links.iter_mut()
+                    .chain(self.orphaned_links.iter_mut())
+                    .find(|l| l.id == id)
+                    && link.state != state
+                {
+                    link.state = state;
+                    self.invalidate_links();
+                }
+            }
+            PipewireEvent::NodeStateChanged { id, state } => {
+                if let Some(node) = self.nodes.get_mut(&id)
+                    && node.state != state
+                {
+                    node.state = state;
+                    self.invalidate_nodes();
+                }
+            }
+            PipewireEvent::ClientAdded { id, name, pid, exe } => {
+                self.clients.insert(id, ClientInfo { name, pid, exe });
+            }
+            PipewireEvent::ClientRemoved { id } => {
+                self.clients.remove(&id);
+            }
+            PipewireEvent::NodeVolumeChanged { id, volume, muted } => {
+                if let Some(node) = self.nodes.get_mut(&id)
+                    && (node.volume != Some(volume) || node.muted != muted)
+                {
+                    node.volume = Some(volume);
+                    node.muted = muted;
+                    self.invalidate_nodes();
+                }
+            }
+            PipewireEvent::MetadataChanged { key, value } => {
+                // Values look like {"name":"alsa_output.pci-0000_00_1f.3.analog-stereo"}
+                let name = serde_json::from_str::<serde_json::Value>(&value)
+                    .ok()
+                    .and_then(|v| v.get("name")?.as_str().map(String::from));
+                match key.as_str() {
+                    "default.audio.sink" => self.default_sink_name = name,
+                    "default.audio.source" => self.default_source_name = name,
+                    _ => return Task::none(),
+                }
+                self.resolve_default_nodes();
+                self.invalidate_nodes();
+            }
+        }
+        Task::none()
+    }
+
+    /// Drop everything PipeWire reported; it will be re-announced on reconnect
+    pub fn clear_all_nodes(&mut self) {
+        self.nodes.clear();
+        self.port_scroll_offsets.clear();
+        self.links.clear();
+        self.orphaned_links.clear();
+        self.auto_preset_requests.clear();
+        self.devices.clear();
+        self.clients.clear();
+        self.ghost_nodes.clear();
+        self.departed_nodes.clear();
+        self.pending_removals.clear();
+        self.link_dot_positions.clear();
+        self.selected_nodes.clear();
+        self.focused_node = None;
+        self.hovered_node = None;
+        self.profile_picker = None;
+        self.invalidate();
+    }
+
+    /// Whether a node's header shows its app name under the title. Only
+    /// the app name decides it, so headers don't resize as ports come and go
+    pub(crate) fn has_subtitle(node: &Node) -> bool {
+        node.app_name.is_some()
+    }
+
+    /// The app name line under a node's title, cut to 20 characters
+    pub(crate) fn subtitle(node: &Node) -> Option<String> {
+        let app_name = node.app_name.as_ref()?;
+        let mut subtitle: String = app_name.chars().take(20).collect();
+        if app_name.chars().count() > 20 {
+            subtitle.pop();
+            subtitle.push('…');
+        }
+        Some(subtitle)
+    }
+
+    /// Height of a node's header, including the app name line if it has one
+    pub(crate) fn node_header_height(node: &Node) -> f32 {
+        if Self::has_subtitle(node) {
+            NODE_HEADER_HEIGHT + NODE_SUBTITLE_HEIGHT
+        } else {
+            NODE_HEADER_HEIGHT
+        }
+    }
+
+    pub(crate) fn node_height(node: &Node, window: PortWindow) -> f32 {
+        if node.collapsed {
+            return Self::node_header_height(node);
+        }
+        let rows = window.rows(node.input_ports.len()).max(window.rows(node.output_ports.len()));
+        Self::node_header_height(node) + (rows as f32 * (PORT_HEIGHT + PORT_SPACING)) + PORT_SPACING
+    }
+
+    /// Where a port sits on its node. Ports scrolled out of `window` are
+    /// placed on the "hidden" row they were scrolled past.
+    pub(crate) fn port_position(node: &Node, port: &Port, window: PortWindow) -> Point {
+        let ports = Self::side_ports(node, port.direction);
+        let index = ports.iter().position(|p| p.id == port.id).unwrap_or(0);
+        let x = match port.direction {
+            PortDirection::Input => node.position.x,
+            PortDirection::Output => node.position.x + NODE_WIDTH,
+        };
+        // Collapsed nodes route every link to the middle of the header edge
+        if node.collapsed {
+            return Point::new(x, node.position.y + Self::node_header_height(node) / 2.0);
+        }
+        let row = window.row(index, ports.len());
+        let y = node.position.y + Self::node_header_height(node) + PORT_SPACING + (row as f32 * (PORT_HEIGHT + PORT_SPACING)) + PORT_HEIGHT / 2.0;
+        Point::new(x, y)
+    }
+
+    /// Whether a port is scrolled into view in `window`
+    pub(crate) fn port_shown(node: &Node, port: &Port, window: PortWindow) -> bool {
+        let ports = Self::side_ports(node, port.direction);
+        let index = ports.iter().position(|p| p.id == port.id).unwrap_or(0);
+        window.shows(index, ports.len())
+    }
+
+    fn side_ports(node: &Node, direction: PortDirection) -> &[Port] {
+        match direction {
+            PortDirection::Input => &node.input_ports,
+            PortDirection::Output => &node.output_ports,
+        }
+    }
+
+    /// The part of a node's port lists currently on the canvas
+    pub(crate) fn port_window(&self, node_id: u32) -> PortWindow {
+        PortWindow {
+            offset: self.port_scroll_offsets.get(&node_id).copied().unwrap_or(0),
+            max_visible: self.max_visible_ports,
+        }
+    }
+
+    /// Current search, drag, filter and zoom state for draw_node
+    fn node_draw_options(&self) -> NodeDrawOptions<'_> {
+        NodeDrawOptions {
+            matched_ports: &self.filtered_ports,
+            connectable: self.connectable_ports.as_ref(),
+            port_filter: self.port_type_filter,
+            zoom: self.zoom,
+            lod_threshold: self.lod_zoom_threshold,
+            prefer_alias: self.prefer_port_alias,
+            hide_monitors: self.hide_monitor_ports,
+        }
+    }
+
+    /// Whether the wheel at `world` should scroll a node's port lists
+    /// rather than zoom: the cursor is below the header of a node too tall
+    /// to show every port
+    fn scrolls_ports(&self, node_id: u32, world: Point) -> bool {
+        self.nodes.get(&node_id).is_some_and(|node| {
+            !node.collapsed
+                && world.y > node.position.y + Self::node_header_height(node)
+                && node.input_ports.len().max(node.output_ports.len()) > self.max_visible_ports
+        })
+    }
+
+    /// Height of a node as drawn on the canvas
+    fn visible_node_height(&self, node: &Node) -> f32 {
+        Self::node_height(node, self.port_window(node.id))
+    }
+
+    /// Position of a port as drawn on the canvas
+    fn visible_port_position(&self, node: &Node, port: &Port) -> Point {
+        Self::port_position(node, port, self.port_window(node.id))
+    }
+
+    /// Whether linking `output_node` → `input_node` closes a loop, i.e. the
+    /// output node is already downstream of the input node
+    fn would_create_cycle(&self, output_node: u32, input_node: u32) -> bool {
+        let mut visited = std::collections::HashSet::new();
+        let mut queue = std::collections::VecDeque::from([input_node]);
+        while let Some(current) = queue.pop_front() {
+            if current == output_node {
+                return true;
+            }
+            if !visited.insert(current) {
+                continue;
+            }
+            for link in self.links.iter().filter(|l| l.output_node == current) {
+                queue.push_back(link.input_node);
+            }
+        }
+        false
+    }
+
+    /// Entries for a context menu opened on `target`
+    fn context_menu_items(&self, target: ContextMenuTarget, viewport: Size) -> Vec<(String, GraphMessage)> {
+        match target {
+            ContextMenuTarget::Canvas => {
+                let mut items = vec![
+                    ("Export as SVG".to_string(), GraphMessage::ExportSvg),
+                    ("Export as PNG".to_string(), GraphMessage::ExportPng { viewport }),
+                    ("Auto-layout".to_string(), GraphMessage::AutoLayout),
+                    ("Export pw-link script".to_string(), GraphMessage::ExportScript),
+                    ("Export Graphviz graph".to_string(), GraphMessage::PickDotExport),
+                    ("Export Mermaid diagram".to_string(), GraphMessage::ExportMermaid),
+                    ("Export qpwgraph patchbay".to_string(), GraphMessage::PickQpwgraphExport),
+                    ("Export WirePlumber script".to_string(), GraphMessage::PickWireplumberExport),
+                    ("Export preset as YAML".to_string(), GraphMessage::ExportPresetYaml),
+                    ("Import qpwgraph patchbay".to_string(), GraphMessage::PickQpwgraphImport),
+                ];
+                items.extend(
+                    Config::profiles().into_iter()
+                        .filter(|profile| *profile != self.profile)
+                        .map(|profile| (format!("Switch to profile {}", profile), GraphMessage::SwitchProfile(profile))),
+                );
+                if self.preset_path.is_some() {
+                    items.extend((1..=9).map(|slot| (
+                        format!("Assign current preset to slot {}", slot),
+                        GraphMessage::AssignPresetSlot(slot),
+                    )));
+                }
+                items
+            }
+            ContextMenuTarget::Node(node_id) => {
+                // One "connect all" entry per other selected node
+                let mut items: Vec<(String, GraphMessage)> = self.selected_nodes.iter()
+                    .filter(|&&id| id != node_id)
+                    .filter_map(|&id| self.nodes.get(&id))
+                    .map(|target| {
+                        let name = target.custom_name.as_ref().unwrap_or(&target.name);
+                        (
+                            format!("Connect all to {}", name),
+                            GraphMessage::BatchConnect { output_node: node_id, input_node: target.id },
+                        )
+                    })
+                    .collect();
+                if self.links.iter().any(|l| l.output_node == node_id || l.input_node == node_id) {
+                    items.push(("Disconnect all".to_string(), GraphMessage::DisconnectNode { node_id }));
+                }
+                let pinned = self.nodes.get(&node_id).is_some_and(|n| n.layout_pinned);
+                let label = if pinned { "Unpin from auto-layout" } else { "Pin for auto-layout" };
+                items.push((label.to_string(), GraphMessage::ToggleLayoutPin { node_id }));
+                items.push(("Hide node".to_string(), GraphMessage::ToggleNodeHidden { node_id }));
+                if let Some(device_id) = self.nodes.get(&node_id).and_then(|n| n.device_id) {
+                    items.push(("Set profile...".to_string(), GraphMessage::LoadDeviceProfiles { device_id }));
+                }
+                let is_sink = self.nodes.get(&node_id).is_some_and(|n| n.class() == MediaClass::AudioSink);
+                if is_sink && self.default_sink_id != Some(node_id) {
+                    items.push(("Set as default output".to_string(), GraphMessage::SetDefaultSink { node_id }));
+                }
+                if let Some(node) = self.nodes.get(&node_id)
+                    && node.volume.is_some()
+                {
+                    let label = if node.muted { "Unmute" } else { "Mute" };
+                    items.push((label.to_string(), GraphMessage::ToggleMute { node_id }));
+                }
+                items
+            }
+            ContextMenuTarget::Link { output_port, input_port } => {
+                let pin = if self.pinned_connections.contains(&(output_port, input_port)) {
+                    ("Unpin connection".to_string(), GraphMessage::UnpinLink { output_port, input_port })
+                } else {
+                    ("Pin connection".to_string(), GraphMessage::PinLink { output_port, input_port })
+                };
+                let link_id = self.links.iter()
+                    .find(|l| l.output_port == output_port && l.input_port == input_port)
+                    .map(|l| l.id)
+                    .unwrap_or_default();
+                vec![
+                    pin,
+                    ("Disconnect".to_string(), GraphMessage::DisconnectLink { link_id, output_port, input_port }),
+                ]
+            }
+        }
+    }
+
+    /// Name-based spec for a link, so a pin survives node restarts
+    fn pinned_spec(&self, output_port: u32, input_port: u32) -> Option<PinnedSpec> {
+        let (out_node, out_port) = self.find_port(output_port)?;
+        let (in_node, in_port) = self.find_port(input_port)?;
+        Some(PinnedSpec {
+            output_node_name: out_node.name.clone(),
+            output_port_name: out_port.name.clone(),
+            input_node_name: in_node.name.clone(),
+            input_port_name: in_port.name.clone(),
+        })
+    }
+
+    /// Current port IDs for every port named `port_name` on nodes named `node_name`
+    fn ports_by_name(&self, node_name: &str, port_name: &str, direction: PortDirection) -> Vec<u32> {
+        self.nodes.values()
+            .filter(|n| n.name == node_name)
+            .flat_map(|n| match direction {
+                PortDirection::Input => n.input_ports.iter(),
+                PortDirection::Output => n.output_ports.iter(),
+            })
+            .filter(|p| p.name == port_name)
+            .map(|p| p.id)
+            .collect()
+    }
+
+    /// Drop a removed node for good, leaving a device ghost or a departed
+    /// ghost behind where that applies
+    fn finalize_node_removal(&mut self, id: u32, config: &mut Config) {
+        // Check if this node belongs to a device - may need to create ghost
+        let removed_node = self.nodes.remove(&id);
+        self.selected_nodes.remove(&id);
+        self.port_scroll_offsets.remove(&id);
+        if self.focused_node == Some(id) {
+            self.focused_node = None;
+        }
+        if self.hovered_node_timer.is_some_and(|(node_id, _)| node_id == id) {
+            self.hovered_node_timer = None;
+            self.show_tooltip = None;
+        }
+        if self.hovered_port.is_some_and(|(node_id, _)| node_id == id) {
+            self.hovered_port = None;
+            self.hovered_port_since = None;
+            self.show_port_tooltip = false;
+        }
+        self.links.retain(|l| l.output_node != id && l.input_node != id);
+        self.orphaned_links.retain(|l| l.output_node != id && l.input_node != id);
+        if self.default_sink_id == Some(id) || self.default_source_id == Some(id) {
+            self.resolve_default_nodes();
+        }
+
+        if let Some(node) = &removed_node
+            && let Some(dev_id) = node.device_id
+            && let Some(device) = self.devices.get_mut(&dev_id)
+        {
+            device.active_node_ids.retain(|&nid| nid != id);
+            // If device has no more active nodes and is ALSA, create ghost
+            if device.active_node_ids.is_empty() && device.api == "alsa" {
+                let ghost_already_exists = self.ghost_nodes.iter().any(|g| g.device_id == dev_id);
+                if !ghost_already_exists {
+                    let position = node.position;
+                    config.set_device_position(
+                        device.name.clone(),
+                        Position { x: position.x, y: position.y },
+                    );
+                    self.ghost_nodes.push(GhostNode {
+                        device_id: dev_id,
+                        device_name: device.name.clone(),
+                        display_name: device.description.clone(),
+                        position,
+                        has_saved_position: true,
+                    });
+                }
+            }
+        }
+
+        // Linger as a ghost unless the device ghost already stands in
+        if let Some(node) = removed_node
+            && config.node_grace_period_ms > 0
+            && !node.device_id.is_some_and(|dev_id| self.ghost_nodes.iter().any(|g| g.device_id == dev_id))
+        {
+            self.departed_nodes.insert(id, (node, std::time::Instant::now()));
+        }
+
+        self.invalidate();
+    }
+
+    /// Whether removed nodes are waiting for a PendingRemovalTick
+    pub fn has_pending_removals(&self) -> bool {
+        !self.pending_removals.is_empty()
+    }
+
+    /// Whether a name-pinned connection needs the periodic reconnect check
+    pub fn has_pinned_specs(&self) -> bool {
+        !self.name_pinned.is_empty()
+    }
+
+    /// Whether a port survives the port type filter and, if they are
+    /// hidden, isn't a monitor port
+    fn port_visible(&self, port: &Port) -> bool {
+        self.port_type_filter.is_none_or(|t| t == port.port_type)
+            && !(self.hide_monitor_ports && port.is_monitor)
+    }
+
+    /// Hidden nodes, nodes outside the media class filter and nodes with
+    /// no ports left after the port type filter are not drawn
+    fn node_visible(&self, node: &Node) -> bool {
+        !node.hidden
+            && self.media_class_filter.is_none_or(|filter| filter.includes(node.class()))
+            && (self.port_type_filter.is_none()
+                || node.input_ports.iter().chain(node.output_ports.iter()).any(|p| self.port_visible(p)))
+    }
+
+    /// How many nodes are hidden or filtered out
+    pub fn hidden_node_count(&self) -> usize {
+        self.nodes.values().filter(|n| !self.node_visible(n)).count()
+    }
+
+    /// Recompute the preview's cable endpoints from its port pairs
+    fn refresh_preview_cables(&mut self) {
+        self.preview_adds.clear();
+        self.preview_removes.clear();
+        let Some(preview) = &self.preset_preview else {
+            return;
+        };
+        for &(output_port, input_port) in &preview.adds {
+            if let (Some((out_node, out_port)), Some(end)) = (self.find_port(output_port), self.find_port_position(input_port)) {
+                let start = self.visible_port_position(out_node, out_port);
+                self.preview_adds.push((start, end, out_port.port_type));
             }
-            PipewireEvent::LinkRemoved { id } => {
-                self.links.retain(|l| l.id != id);
-                self.cache.clear();
+        }
+        for &(output_port, input_port) in &preview.removes {
+            if let (Some(start), Some(end)) = (self.find_port_position(output_port), self.find_port_position(input_port)) {
+                self.preview_removes.push((start, end));
             }
         }
     }
 
-    fn node_height(node: &Node) -> f32 {
-        let port_count = node.input_ports.len().max(node.output_ports.len());
-        NODE_HEADER_HEIGHT + (port_count as f32 * (PORT_HEIGHT + PORT_SPACING)) + PORT_SPACING
+    /// The loaded preset, or the live connections if there is none
+    fn preset_or_live_links(&self) -> crate::preset::Preset {
+        self.current_preset.clone().unwrap_or_else(|| {
+            let mut preset = crate::preset::Preset::new("solder");
+            preset.connections = self.links.iter()
+                .filter_map(|l| self.link_to_preset_connection(l))
+                .collect();
+            preset
+        })
     }
 
-    fn port_position(node: &Node, port: &Port) -> Point {
-        let ports = match port.direction {
-            PortDirection::Input => &node.input_ports,
-            PortDirection::Output => &node.output_ports,
-        };
-        let index = ports.iter().position(|p| p.id == port.id).unwrap_or(0);
-        let x = match port.direction {
-            PortDirection::Input => node.position.x,
-            PortDirection::Output => node.position.x + NODE_WIDTH,
+    /// Open a context menu listing a device's loaded profiles
+    fn show_profile_menu(&mut self, device_id: u32) -> Task<Message> {
+        let items: Vec<(String, GraphMessage)> = self.device_profiles.get(&device_id)
+            .into_iter()
+            .flatten()
+            .map(|profile| {
+                let label = if profile.description.is_empty() { &profile.name } else { &profile.description };
+                (
+                    label.clone(),
+                    GraphMessage::SetDeviceProfile { device_id, profile_index: profile.index },
+                )
+            })
+            .collect();
+        if items.is_empty() {
+            return crate::toast::notify(Toast::warning("No profiles found for this device"));
+        }
+        self.context_menu = Some(ContextMenu { position: self.context_menu_position, items });
+        Task::none()
+    }
+
+    /// Match the default sink/source names from the metadata against the
+    /// current nodes and flag them
+    fn resolve_default_nodes(&mut self) {
+        let find = |name: &Option<String>| {
+            let name = name.as_deref()?;
+            self.nodes.values().find(|n| n.node_name.as_deref() == Some(name)).map(|n| n.id)
         };
-        let y = node.position.y + NODE_HEADER_HEIGHT + PORT_SPACING + (index as f32 * (PORT_HEIGHT + PORT_SPACING)) + PORT_HEIGHT / 2.0;
-        Point::new(x, y)
+        self.default_sink_id = find(&self.default_sink_name);
+        self.default_source_id = find(&self.default_source_name);
+        for node in self.nodes.values_mut() {
+            node.is_default = Some(node.id) == self.default_sink_id || Some(node.id) == self.default_source_id;
+        }
     }
 
     pub fn hit_test(&self, point: Point) -> HitResult {
         let world_point = self.screen_to_world(point);
 
-        // Larger hit radius for ports (easier to click)
-        const PORT_HIT_RADIUS: f32 = 15.0;
-
-        // Check ports FIRST across all nodes (ports are on edges, may be outside node bounds)
-        for node in self.nodes.values() {
-            for port in node.input_ports.iter().chain(node.output_ports.iter()) {
-                let port_pos = Self::port_position(node, port);
+        if self.spatial_dirty.replace(false) {
+            self.spatial_index.borrow_mut().rebuild(self, self.port_hit_radius);
+        }
+        let candidates = self.spatial_index.borrow().query_point(world_point);
+
+        // Check ports FIRST (ports are on edges, may be outside node bounds)
+        for &item in &candidates {
+            if let SpatialItem::Port { node_id, port_id } = item
+                && let Some(node) = self.nodes.get(&node_id).filter(|n| self.node_visible(n))
+                && let Some(port) = node.input_ports.iter().chain(node.output_ports.iter()).find(|p| p.id == port_id)
+                && self.port_visible(port)
+                && Self::port_shown(node, port, self.port_window(node_id))
+            {
+                let port_pos = self.visible_port_position(node, port);
                 let dist = ((world_point.x - port_pos.x).powi(2) + (world_point.y - port_pos.y).powi(2)).sqrt();
-                if dist < PORT_HIT_RADIUS {
-                    return HitResult::Port { node_id: node.id, port_id: port.id };
+                if dist < self.port_hit_radius {
+                    return HitResult::Port { node_id, port_id };
                 }
             }
         }
 
         // Then check node bodies
-        for node in self.nodes.values() {
-            let height = Self::node_height(node);
-            let bounds = Rectangle::new(node.position, Size::new(NODE_WIDTH, height));
-            if bounds.contains(world_point) {
-                return HitResult::Node(node.id);
+        for &item in &candidates {
+            if let SpatialItem::Node(node_id) = item
+                && self.nodes.get(&node_id).is_some_and(|n| self.node_visible(n))
+            {
+                return HitResult::Node(node_id);
             }
         }
 
@@ -1225,15 +4278,33 @@ impl Graph {
         }
 
         // Check links (sample points along bezier curve)
-        for link in &self.links {
-            if let Some(dist) = self.distance_to_link(world_point, link) {
-                if dist < 8.0 {
-                    return HitResult::Link {
-                        link_id: link.id,
-                        output_port: link.output_port,
-                        input_port: link.input_port,
-                    };
-                }
+        for link in self.links.iter().filter(|l| !l.is_self_loop()) {
+            if self.find_port(link.output_port).is_some_and(|(_, p)| !self.port_visible(p)) {
+                continue;
+            }
+            if let Some(dist) = self.distance_to_link(world_point, link)
+                && dist < self.link_hit_radius
+            {
+                return HitResult::Link {
+                    link_id: link.id,
+                    output_port: link.output_port,
+                    input_port: link.input_port,
+                };
+            }
+        }
+        for link in self.links.iter().filter(|l| l.is_self_loop()) {
+            let Some([start, ctrl1, ctrl2, end]) = self.self_link_curve(link) else {
+                continue;
+            };
+            let near = (0..=20)
+                .map(|i| Self::cubic_bezier(start, ctrl1, ctrl2, end, i as f32 / 20.0))
+                .any(|p| p.distance(world_point) < self.link_hit_radius);
+            if near {
+                return HitResult::Link {
+                    link_id: link.id,
+                    output_port: link.output_port,
+                    input_port: link.input_port,
+                };
             }
         }
 
@@ -1247,14 +4318,22 @@ impl Graph {
         )
     }
 
+    /// The part of the world currently on screen
+    fn visible_world_rect(&self, size: Size) -> Rectangle {
+        Rectangle::new(
+            self.screen_to_world(Point::ORIGIN),
+            Size::new(size.width / self.zoom, size.height / self.zoom),
+        )
+    }
+
     fn distance_to_link(&self, point: Point, link: &Link) -> Option<f32> {
         let out_node = self.nodes.get(&link.output_node)?;
         let in_node = self.nodes.get(&link.input_node)?;
         let out_port = out_node.output_ports.iter().find(|p| p.id == link.output_port)?;
         let in_port = in_node.input_ports.iter().find(|p| p.id == link.input_port)?;
 
-        let start = Self::port_position(out_node, out_port);
-        let end = Self::port_position(in_node, in_port);
+        let start = self.visible_port_position(out_node, out_port);
+        let end = self.visible_port_position(in_node, in_port);
         let control_offset = ((end.x - start.x).abs() / 2.0).max(60.0);
         let ctrl1 = Point::new(start.x + control_offset, start.y);
         let ctrl2 = Point::new(end.x - control_offset, end.y);
@@ -1270,6 +4349,18 @@ impl Graph {
         Some(min_dist)
     }
 
+    /// Arc for a link from a node into itself: out of the output port, up
+    /// over the header and down into the input port
+    fn self_link_curve(&self, link: &Link) -> Option<[Point; 4]> {
+        let node = self.nodes.get(&link.output_node).filter(|n| !n.hidden && !n.collapsed)?;
+        let out_port = node.output_ports.iter().find(|p| p.id == link.output_port)?;
+        let in_port = node.input_ports.iter().find(|p| p.id == link.input_port)?;
+        let start = self.visible_port_position(node, out_port);
+        let end = self.visible_port_position(node, in_port);
+        let top = node.position.y - NODE_WIDTH * 0.4;
+        Some([start, Point::new(start.x + 40.0, top), Point::new(end.x - 40.0, top), end])
+    }
+
     fn cubic_bezier(p0: Point, p1: Point, p2: Point, p3: Point, t: f32) -> Point {
         let t2 = t * t;
         let t3 = t2 * t;
@@ -1290,7 +4381,7 @@ impl Graph {
             let overlaps = self.nodes.values().any(|node| {
                 let dx = (node.position.x - pos.x).abs();
                 let dy = (node.position.y - pos.y).abs();
-                dx < NODE_WIDTH * 0.5 && dy < NODE_HEADER_HEIGHT * 2.0
+                dx < NODE_WIDTH * 0.5 && dy < Self::node_header_height(node) * 2.0
             });
             if !overlaps {
                 break;
@@ -1323,7 +4414,10 @@ impl canvas::Program<Message> for Graph {
         bounds: Rectangle,
         cursor: mouse::Cursor,
     ) -> Vec<Geometry> {
-        let content = self.cache.draw(renderer, bounds.size(), |frame| {
+        // Slack for glows, selection outlines and cable width at the edges
+        let viewport = self.visible_world_rect(bounds.size()).expand(8.0);
+
+        let links_geo = self.link_cache.draw(renderer, bounds.size(), |frame| {
             // Background
             frame.fill_rectangle(
                 Point::ORIGIN,
@@ -1352,38 +4446,173 @@ impl canvas::Program<Message> for Graph {
             frame.translate(self.pan_offset);
             frame.scale(self.zoom);
 
-            // Draw links
-            for link in &self.links {
+            // Draw links; self-loops get an arc below
+            for link in self.links.iter().filter(|l| !l.is_self_loop()) {
                 let output_node = self.nodes.get(&link.output_node);
                 let input_node = self.nodes.get(&link.input_node);
 
-                if let (Some(out_node), Some(in_node)) = (output_node, input_node) {
+                if let (Some(out_node), Some(in_node)) = (output_node, input_node)
+                    && !out_node.hidden
+                    && !in_node.hidden
+                {
                     let out_port = out_node.output_ports.iter().find(|p| p.id == link.output_port);
                     let in_port = in_node.input_ports.iter().find(|p| p.id == link.input_port);
 
                     if let (Some(out_port), Some(_in_port)) = (out_port, in_port) {
-                        let start = Self::port_position(out_node, out_port);
-                        let end = Self::port_position(in_node, _in_port);
+                        if !self.port_visible(out_port) {
+                            continue;
+                        }
+                        let start = self.visible_port_position(out_node, out_port);
+                        let end = self.visible_port_position(in_node, _in_port);
+                        if link_offscreen(start, end, &viewport) {
+                            continue;
+                        }
                         // Use output port's type for link color
-                        draw_bezier_link(frame, start, end, out_port.port_type);
+                        // Manual additions on top of the preset get a cyan highlight
+                        if self.preset_delta.as_ref().is_some_and(|d| d.added.contains(&link.id)) {
+                            draw_link_highlight(frame, start, end, palette::ACCENT_INPUT);
+                        }
+                        draw_bezier_link(frame, start, end, out_port.port_type, &link.state);
                     }
                 }
             }
 
+            for link in self.links.iter().filter(|l| l.is_self_loop()) {
+                if let Some(curve) = self.self_link_curve(link)
+                    && let Some((node, port)) = self.find_port(link.output_port)
+                    && self.node_visible(node)
+                {
+                    draw_link_curve(frame, curve, port.port_type, &link.state);
+                }
+            }
+
+            // Preset connections that are currently disconnected
+            if let Some(delta) = &self.preset_delta {
+                for &(output_port, input_port) in &delta.missing {
+                    let start = self.find_port_position(output_port);
+                    let end = self.find_port_position(input_port);
+                    if let (Some(start), Some(end)) = (start, end) {
+                        let (ctrl1, ctrl2) = link_control_points(start, end);
+                        draw_dashed_bezier(frame, [start, ctrl1, ctrl2, end], 6.0, 6.0, palette::LINK_GHOST, 2.0);
+                    }
+                }
+            }
+
+            // Links whose other node hasn't arrived, hanging off a placeholder
+            for link in &self.orphaned_links {
+                if let Some(start) = self.find_port_position(link.output_port) {
+                    draw_orphaned_link(frame, start, link.input_node, true);
+                } else if let Some(end) = self.find_port_position(link.input_port) {
+                    draw_orphaned_link(frame, end, link.output_node, false);
+                }
+            }
+        });
+
+        let nodes_geo = self.node_cache.draw(renderer, bounds.size(), |frame| {
+            frame.translate(self.pan_offset);
+            frame.scale(self.zoom);
+
             // Draw nodes
-            for node in self.nodes.values() {
+            let options = self.node_draw_options();
+            for node in self.nodes.values().filter(|n| self.node_visible(n)) {
+                let node_rect = Rectangle::new(node.position, Size::new(NODE_WIDTH, self.visible_node_height(node)));
+                if !node_rect.intersects(&viewport) {
+                    continue;
+                }
                 // Dim nodes that don't match search filter
                 let dimmed = self.search_active && !self.search_query.is_empty()
                     && !self.filtered_nodes.contains(&node.id);
-                draw_node(frame, node, dimmed);
+                let rename = (self.renaming_node == Some(node.id)).then_some(self.rename_text.as_str());
+                draw_node(frame, node, self.port_window(node.id), dimmed, self.selected_nodes.contains(&node.id), rename, &options);
+                if self.focused_node == Some(node.id) {
+                    stroke_rounded_rect(
+                        frame,
+                        Point::new(node.position.x - 3.0, node.position.y - 3.0),
+                        Size::new(NODE_WIDTH + 6.0, self.visible_node_height(node) + 6.0),
+                        11.0,
+                        palette::PORT_AUDIO,
+                        2.0,
+                    );
+                }
             }
 
             // Draw ghost nodes
             for ghost in &self.ghost_nodes {
                 draw_ghost_node(frame, ghost);
             }
+            for (node, _) in self.departed_nodes.values() {
+                draw_departed_node(frame, node, self.visible_node_height(node));
+            }
         });
 
+        // Preset preview cables, uncached so they never go stale in link_cache
+        let preview_geo = {
+            let mut frame = Frame::new(renderer, bounds.size());
+            if self.preset_preview.is_some() {
+                frame.translate(self.pan_offset);
+                frame.scale(self.zoom);
+                for &(start, end, port_type) in &self.preview_adds {
+                    if self.port_type_filter.is_none_or(|filter| filter == port_type) {
+                        let (ctrl1, ctrl2) = link_control_points(start, end);
+                        draw_dashed_bezier(&mut frame, [start, ctrl1, ctrl2, end], 8.0, 5.0, palette::PORT_AUDIO, 2.5);
+                    }
+                }
+                for &(start, end) in &self.preview_removes {
+                    let (ctrl1, ctrl2) = link_control_points(start, end);
+                    draw_dashed_bezier(&mut frame, [start, ctrl1, ctrl2, end], 8.0, 5.0, palette::LINK_ERROR, 2.5);
+                }
+            }
+            frame.into_geometry()
+        };
+
+        // Hovering a node highlights it, its links and its direct neighbours.
+        // Drawn as a veil plus redrawn highlights so the cached layer stays valid.
+        let hover_geo = {
+            let mut frame = Frame::new(renderer, bounds.size());
+            if let Some(hovered) = self.hovered_node
+                && matches!(state.interaction, InteractionMode::None)
+                && self.nodes.contains_key(&hovered)
+            {
+                let hovered_links: Vec<&Link> = self.links.iter()
+                    .filter(|l| l.output_node == hovered || l.input_node == hovered)
+                    .filter(|l| [l.output_node, l.input_node].iter().all(|id| self.nodes.get(id).is_some_and(|n| !n.hidden)))
+                    .collect();
+                let mut highlight_set: std::collections::HashSet<u32> = hovered_links.iter()
+                    .flat_map(|l| [l.output_node, l.input_node])
+                    .collect();
+                highlight_set.insert(hovered);
+
+                frame.fill_rectangle(Point::ORIGIN, bounds.size(), Color::from_rgba(0.075, 0.075, 0.085, 0.75));
+                frame.translate(self.pan_offset);
+                frame.scale(self.zoom);
+
+                for link in hovered_links {
+                    if link.is_self_loop() {
+                        if let Some(curve) = self.self_link_curve(link)
+                            && let Some((_, port)) = self.find_port(link.output_port)
+                        {
+                            draw_link_curve(&mut frame, curve, port.port_type, &link.state);
+                        }
+                    } else if let (Some((_, out_port)), Some(start), Some(end)) = (
+                        self.find_port(link.output_port),
+                        self.find_port_position(link.output_port),
+                        self.find_port_position(link.input_port),
+                    ) && self.port_visible(out_port)
+                    {
+                        draw_bezier_link(&mut frame, start, end, out_port.port_type, &link.state);
+                    }
+                }
+                let options = self.node_draw_options();
+                for node in highlight_set.iter().filter_map(|id| self.nodes.get(id)) {
+                    let dimmed = self.search_active && !self.search_query.is_empty()
+                        && !self.filtered_nodes.contains(&node.id);
+                    let rename = (self.renaming_node == Some(node.id)).then_some(self.rename_text.as_str());
+                    draw_node(&mut frame, node, self.port_window(node.id), dimmed, self.selected_nodes.contains(&node.id), rename, &options);
+                }
+            }
+            frame.into_geometry()
+        };
+
         // Draw pending connection (not cached - follows cursor)
         let pending = Frame::new(renderer, bounds.size());
         let pending_geo = if let InteractionMode::CreatingConnection { from_node, from_port } = state.interaction {
@@ -1399,7 +4628,7 @@ impl canvas::Program<Message> for Graph {
                         .find(|p| p.id == from_port);
 
                     if let Some(port) = port {
-                        let start = Self::port_position(node, port);
+                        let start = self.visible_port_position(node, port);
                         let end = self.screen_to_world(cursor_pos);
 
                         // Draw with a pulsing/dashed style
@@ -1410,23 +4639,96 @@ impl canvas::Program<Message> for Graph {
             } else {
                 pending.into_geometry()
             }
+        } else if let InteractionMode::ReconnectingLink { fixed_port, moving_port_world, .. } = state.interaction {
+            let mut frame = Frame::new(renderer, bounds.size());
+            frame.translate(self.pan_offset);
+            frame.scale(self.zoom);
+            if let Some((node, port)) = self.find_port(fixed_port) {
+                let start = self.visible_port_position(node, port);
+                draw_pending_link(&mut frame, start, moving_port_world, port.direction, port.port_type);
+            }
+            frame.into_geometry()
         } else {
             pending.into_geometry()
         };
 
-        // Help overlay
-        let help_geo = if self.show_help {
+        // Flow dots on active links and pulses on negotiating ones (animated, so kept out of the cache)
+        let flow_geo = {
+            let mut frame = Frame::new(renderer, bounds.size());
+            frame.translate(self.pan_offset);
+            frame.scale(self.zoom);
+            // Dots follow a straight cable, which a self-loop's arc isn't
+            for link in self.links.iter().filter(|l| !l.is_self_loop()) {
+                let (Some(start), Some(end)) =
+                    (self.find_port_position(link.output_port), self.find_port_position(link.input_port))
+                else {
+                    continue;
+                };
+                let Some((_, out_port)) = self.find_port(link.output_port) else {
+                    continue;
+                };
+                if !self.port_visible(out_port) {
+                    continue;
+                }
+                let port_type = out_port.port_type;
+                if let Some(&t) = self.link_dot_positions.get(&link.id) {
+                    draw_link_flow_dot(&mut frame, start, end, port_type, t);
+                } else if link.state == LinkState::Negotiating {
+                    draw_link_pulse(&mut frame, start, end, port_type, self.negotiation_pulse);
+                }
+            }
+            frame.into_geometry()
+        };
+
+        // Rubber-band selection rectangle
+        let selection_geo = if let InteractionMode::Selecting { start, current } = state.interaction {
+            let mut frame = Frame::new(renderer, bounds.size());
+            let top_left = Point::new(start.x.min(current.x), start.y.min(current.y));
+            let size = Size::new((start.x - current.x).abs(), (start.y - current.y).abs());
+            frame.fill_rectangle(top_left, size, palette::NODE_SELECTED_GLOW);
+            frame.stroke(
+                &Path::rectangle(top_left, size),
+                Stroke::default().with_color(palette::NODE_SELECTED).with_width(1.0),
+            );
+            frame.into_geometry()
+        } else {
+            Frame::new(renderer, bounds.size()).into_geometry()
+        };
+
+        // Help overlay, or the preset picker
+        let help_geo = if self.show_help && !self.exporting {
+            let mut frame = Frame::new(renderer, bounds.size());
+            draw_help_overlay(&mut frame, bounds.size(), &self.shortcuts);
+            frame.into_geometry()
+        } else if self.show_preset_picker && !self.exporting {
+            let mut frame = Frame::new(renderer, bounds.size());
+            draw_preset_picker(
+                &mut frame,
+                bounds.size(),
+                &self.available_presets,
+                self.preset_picker_index,
+                self.preset_delete_pending,
+                self.preset_path.as_deref(),
+            );
+            frame.into_geometry()
+        } else {
+            Frame::new(renderer, bounds.size()).into_geometry()
+        };
+
+        // Undo/redo history (left edge)
+        let history_geo = if self.show_history && !self.exporting {
             let mut frame = Frame::new(renderer, bounds.size());
-            draw_help_overlay(&mut frame, bounds.size());
+            draw_history_panel(&mut frame, bounds.size(), &self.undo_stack, &self.redo_stack);
             frame.into_geometry()
         } else {
             Frame::new(renderer, bounds.size()).into_geometry()
         };
 
         // Search overlay
-        let search_geo = if self.search_active {
+        let search_geo = if self.search_active && !self.exporting {
             let mut frame = Frame::new(renderer, bounds.size());
-            draw_search_overlay(&mut frame, bounds.size(), &self.search_query, self.filtered_nodes.len());
+            let current = self.search_cycling.then_some(self.search_match_index);
+            draw_search_overlay(&mut frame, bounds.size(), &self.search_query, self.filtered_nodes.len(), current, self.search_error.as_deref());
             frame.into_geometry()
         } else {
             Frame::new(renderer, bounds.size()).into_geometry()
@@ -1439,6 +4741,15 @@ impl canvas::Program<Message> for Graph {
             frame.into_geometry()
         };
 
+        // Active preset slot (top left)
+        let slot_geo = if let (Some(slot), Some(preset)) = (self.active_preset_slot, &self.current_preset) {
+            let mut frame = Frame::new(renderer, bounds.size());
+            draw_preset_slot(&mut frame, slot, &preset.name);
+            frame.into_geometry()
+        } else {
+            Frame::new(renderer, bounds.size()).into_geometry()
+        };
+
         // Profile picker overlay (screen space)
         let picker_geo = if let Some(ref picker) = self.profile_picker {
             let mut frame = Frame::new(renderer, bounds.size());
@@ -1449,7 +4760,110 @@ impl canvas::Program<Message> for Graph {
             Frame::new(renderer, bounds.size()).into_geometry()
         };
 
-        vec![content, pending_geo, help_geo, search_geo, legend_geo, picker_geo]
+        // Minimap (bottom right)
+        let minimap_geo = {
+            let mut frame = Frame::new(renderer, bounds.size());
+            if self.show_minimap
+                && let Some((screen, world, scale)) = self.minimap_transform(bounds.size())
+            {
+                draw_minimap(&mut frame, self, bounds.size(), screen, world, scale);
+            }
+            frame.into_geometry()
+        };
+
+        // Preset delta summary panel (top right)
+        let delta_geo = if let Some(ref delta) = self.preset_delta {
+            let mut frame = Frame::new(renderer, bounds.size());
+            let can_save = self.preset_path.is_some();
+            draw_preset_delta_panel(&mut frame, bounds.size(), delta, can_save, cursor.position_in(bounds));
+            frame.into_geometry()
+        } else {
+            Frame::new(renderer, bounds.size()).into_geometry()
+        };
+
+        // Apply / Cancel bar for a preset preview (top center)
+        let preview_bar_geo = if let Some(ref preview) = self.preset_preview {
+            let mut frame = Frame::new(renderer, bounds.size());
+            draw_preset_preview_bar(&mut frame, bounds.size(), preview, cursor.position_in(bounds));
+            frame.into_geometry()
+        } else {
+            Frame::new(renderer, bounds.size()).into_geometry()
+        };
+
+        // Stereo pair prompt (bottom center)
+        let prompt_geo = if let Some(ref prompt) = self.stereo_prompt
+            && !self.exporting
+        {
+            let mut frame = Frame::new(renderer, bounds.size());
+            let remaining = prompt.deadline.saturating_duration_since(std::time::Instant::now());
+            draw_stereo_prompt(&mut frame, bounds.size(), remaining.as_secs_f32().ceil() as u32);
+            frame.into_geometry()
+        } else {
+            Frame::new(renderer, bounds.size()).into_geometry()
+        };
+
+        // Tooltips: hovered node details, or the error of a hovered failed link
+        let tooltip_geo = {
+            let mut frame = Frame::new(renderer, bounds.size());
+            if self.exporting {
+                // Left out of screenshots
+            } else if let Some(node) = self.show_tooltip.and_then(|id| self.nodes.get(&id))
+                && let Some(cursor_pos) = cursor.position_in(bounds)
+                && matches!(state.interaction, InteractionMode::None)
+            {
+                let client = node.client_id.and_then(|id| self.clients.get(&id));
+                draw_node_tooltip(&mut frame, bounds.size(), cursor_pos, node, client);
+            } else if self.show_port_tooltip
+                && matches!(state.interaction, InteractionMode::None)
+                && let Some((node_id, port_id)) = self.hovered_port
+                && let Some(node) = self.nodes.get(&node_id)
+                && let Some(port) = node.input_ports.iter().chain(node.output_ports.iter()).find(|p| p.id == port_id)
+            {
+                let world = self.visible_port_position(node, port);
+                let anchor = Point::new(
+                    world.x * self.zoom + self.pan_offset.x,
+                    world.y * self.zoom + self.pan_offset.y,
+                );
+                draw_port_tooltip(&mut frame, bounds.size(), anchor, port);
+            } else if let Some(cursor_pos) = cursor.position_in(bounds)
+                && let HitResult::Link { link_id, .. } = self.hit_test(cursor_pos)
+                && let Some(LinkState::Error(message)) = self.links.iter().find(|l| l.id == link_id).map(|l| &l.state)
+            {
+                draw_link_error_tooltip(&mut frame, bounds.size(), cursor_pos, message);
+            }
+            frame.into_geometry()
+        };
+
+        // What Ctrl+Z would undo (bottom center)
+        let undo_geo = if let Some(entry) = self.undo_stack.back()
+            && !self.exporting
+        {
+            let mut frame = Frame::new(renderer, bounds.size());
+            draw_undo_status(&mut frame, bounds.size(), &entry.description);
+            frame.into_geometry()
+        } else {
+            Frame::new(renderer, bounds.size()).into_geometry()
+        };
+
+        // Connection-lost banner (top center)
+        let banner_geo = if self.pipewire_disconnected {
+            let mut frame = Frame::new(renderer, bounds.size());
+            draw_disconnected_banner(&mut frame, bounds.size());
+            frame.into_geometry()
+        } else {
+            Frame::new(renderer, bounds.size()).into_geometry()
+        };
+
+        // Context menu (topmost)
+        let menu_geo = if let Some(ref menu) = self.context_menu {
+            let mut frame = Frame::new(renderer, bounds.size());
+            draw_context_menu(&mut frame, menu, cursor.position_in(bounds));
+            frame.into_geometry()
+        } else {
+            Frame::new(renderer, bounds.size()).into_geometry()
+        };
+
+        vec![links_geo, preview_geo, nodes_geo, hover_geo, flow_geo, pending_geo, selection_geo, history_geo, help_geo, search_geo, legend_geo, slot_geo, picker_geo, minimap_geo, delta_geo, preview_bar_geo, prompt_geo, undo_geo, banner_geo, tooltip_geo, menu_geo]
     }
 
     fn update(
@@ -1464,6 +4878,61 @@ impl canvas::Program<Message> for Graph {
         match event {
             iced::Event::Mouse(mouse_event) => match mouse_event {
                 mouse::Event::ButtonPressed(mouse::Button::Left) => {
+                    // The preset picker takes the click: apply a row or close
+                    if self.show_preset_picker {
+                        let row = preset_picker_row_at(bounds.size(), self.available_presets.len(), self.preset_picker_index, cursor_position);
+                        let message = match row {
+                            Some(index) => GraphMessage::PresetPickerSelect(index),
+                            None => GraphMessage::ClosePresetPicker,
+                        };
+                        return Some(canvas::Action::publish(Message::Graph(message)));
+                    }
+
+                    // An open context menu takes the click: pick an item or dismiss
+                    if let Some(ref menu) = self.context_menu {
+                        let message = match context_menu_item_at(menu, cursor_position) {
+                            Some(index) => GraphMessage::ContextMenuSelect { index },
+                            None => GraphMessage::DismissContextMenu,
+                        };
+                        return Some(canvas::Action::publish(Message::Graph(message)));
+                    }
+
+                    // History panel: clicking an undo entry rewinds to before it
+                    if self.show_history && history_panel_rect(bounds.size(), self.undo_stack.len(), self.redo_stack.len()).contains(cursor_position) {
+                        return Some(match history_undo_row_at(bounds.size(), self.undo_stack.len(), cursor_position) {
+                            Some(row) => canvas::Action::publish(Message::Graph(GraphMessage::UndoSteps(row + 1))),
+                            None => canvas::Action::capture(),
+                        });
+                    }
+
+                    // Apply / Cancel on the preset preview bar
+                    if self.preset_preview.is_some() {
+                        let (apply, cancel) = preset_preview_buttons(bounds.size());
+                        if apply.contains(cursor_position) {
+                            return Some(canvas::Action::publish(Message::Graph(GraphMessage::ApplyPresetPreview)));
+                        }
+                        if cancel.contains(cursor_position) {
+                            return Some(canvas::Action::publish(Message::Graph(GraphMessage::CancelPresetPreview)));
+                        }
+                    }
+
+                    // "Save as new version" button on the preset delta panel
+                    if self.preset_delta.is_some()
+                        && self.preset_path.is_some()
+                        && preset_delta_button_rect(bounds.size()).contains(cursor_position)
+                    {
+                        return Some(canvas::Action::publish(Message::Graph(GraphMessage::SavePresetVersion)));
+                    }
+
+                    // Clicking the minimap recenters the view there
+                    if self.show_minimap
+                        && let Some(world) = self.minimap_to_world(bounds.size(), cursor_position)
+                    {
+                        return Some(canvas::Action::publish(Message::Graph(
+                            GraphMessage::CenterOn { world, viewport: bounds.size() }
+                        )));
+                    }
+
                     // If profile picker is open, handle clicks on it first
                     if let Some(ref picker) = self.profile_picker {
                         if let Some(profile_idx) = hit_test_profile_picker(cursor_position, picker, self.pan_offset, self.zoom) {
@@ -1483,6 +4952,30 @@ impl canvas::Program<Message> for Graph {
                         }
                     }
 
+                    // Grabbing a link right next to a port picks up that end of the link
+                    const ENDPOINT_GRAB_RADIUS: f32 = 8.0;
+                    let world = self.screen_to_world(cursor_position);
+                    for link in &self.links {
+                        if let Some((out_handle, in_handle)) = self.link_endpoint_handles(link) {
+                            let grabbed = if world.distance(in_handle) < ENDPOINT_GRAB_RADIUS {
+                                Some((link.output_port, false))
+                            } else if world.distance(out_handle) < ENDPOINT_GRAB_RADIUS {
+                                Some((link.input_port, true))
+                            } else {
+                                None
+                            };
+                            if let Some((fixed_port, is_output_end)) = grabbed {
+                                state.interaction = InteractionMode::ReconnectingLink {
+                                    link_id: link.id,
+                                    fixed_port,
+                                    moving_port_world: world,
+                                    is_output_end,
+                                };
+                                return Some(canvas::Action::request_redraw());
+                            }
+                        }
+                    }
+
                     let hit = self.hit_test(cursor_position);
                     match hit {
                         HitResult::Port { node_id, port_id } => {
@@ -1492,6 +4985,42 @@ impl canvas::Program<Message> for Graph {
                             )))
                         }
                         HitResult::Node(node_id) => {
+                            // A click on the volume bar sets the volume there
+                            let world = self.screen_to_world(cursor_position);
+                            if let Some(node) = self.nodes.get(&node_id)
+                                && node.volume.is_some()
+                            {
+                                let bar = volume_bar_bounds(node);
+                                let hit = Rectangle::new(
+                                    Point::new(bar.x, bar.y - 3.0),
+                                    Size::new(bar.width, bar.height + 6.0),
+                                );
+                                if hit.contains(world) {
+                                    let volume = (world.x - bar.x) / bar.width;
+                                    return Some(canvas::Action::publish(Message::Graph(
+                                        GraphMessage::SetNodeVolume { node_id, volume },
+                                    )));
+                                }
+                            }
+
+                            let now = std::time::Instant::now();
+                            let is_double_click = state.last_click
+                                .is_some_and(|(id, at)| id == node_id && now.duration_since(at) < DOUBLE_CLICK_TIME);
+                            state.last_click = Some((node_id, now));
+
+                            if is_double_click {
+                                state.last_click = None;
+                                let world = self.screen_to_world(cursor_position);
+                                let on_header = self.nodes.get(&node_id)
+                                    .is_some_and(|n| world.y < n.position.y + Graph::node_header_height(n));
+                                let message = if on_header {
+                                    GraphMessage::ToggleNodeCollapse { node_id }
+                                } else {
+                                    GraphMessage::StartRename { node_id }
+                                };
+                                return Some(canvas::Action::publish(Message::Graph(message)));
+                            }
+
                             state.interaction = InteractionMode::Dragging { node_id, last_pos: cursor_position };
                             Some(canvas::Action::request_redraw())
                         }
@@ -1499,8 +5028,12 @@ impl canvas::Program<Message> for Graph {
                             state.interaction = InteractionMode::DraggingGhost { ghost_index: idx, last_pos: cursor_position };
                             Some(canvas::Action::request_redraw())
                         }
+                        HitResult::None if state.shift_held => {
+                            state.interaction = InteractionMode::Selecting { start: cursor_position, current: cursor_position };
+                            Some(canvas::Action::request_redraw())
+                        }
                         HitResult::Link { .. } | HitResult::None => {
-                            state.interaction = InteractionMode::Panning { last_pos: cursor_position };
+                            state.interaction = InteractionMode::Panning { start: cursor_position, last_pos: cursor_position };
                             Some(canvas::Action::request_redraw())
                         }
                     }
@@ -1510,17 +5043,17 @@ impl canvas::Program<Message> for Graph {
                     if state.ctrl_held {
                         match hit {
                             HitResult::Node(node_id) => {
-                                if let Some(node) = self.nodes.get(&node_id) {
-                                    if let Some(device_id) = node.device_id {
-                                        // Only allow deactivation for ALSA devices (they support profiles)
-                                        let is_alsa = self.devices.get(&device_id)
-                                            .map(|d| d.api == "alsa")
-                                            .unwrap_or(false);
-                                        if is_alsa {
-                                            return Some(canvas::Action::publish(Message::Graph(
-                                                GraphMessage::DeactivateDevice { device_id }
-                                            )));
-                                        }
+                                if let Some(node) = self.nodes.get(&node_id)
+                                    && let Some(device_id) = node.device_id
+                                {
+                                    // Only allow deactivation for ALSA devices (they support profiles)
+                                    let is_alsa = self.devices.get(&device_id)
+                                        .map(|d| d.api == "alsa")
+                                        .unwrap_or(false);
+                                    if is_alsa {
+                                        return Some(canvas::Action::publish(Message::Graph(
+                                            GraphMessage::DeactivateDevice { device_id }
+                                        )));
                                     }
                                 }
                                 None
@@ -1532,16 +5065,52 @@ impl canvas::Program<Message> for Graph {
                             }
                             _ => None,
                         }
+                    } else if let HitResult::Link { output_port, input_port, .. } = hit
+                        && state.shift_held
+                    {
+                        Some(canvas::Action::publish(Message::Graph(GraphMessage::ShowContextMenu {
+                            position: cursor_position,
+                            target: ContextMenuTarget::Link { output_port, input_port },
+                            viewport: bounds.size(),
+                        })))
                     } else if let HitResult::Link { link_id, output_port, input_port } = hit {
                         Some(canvas::Action::publish(Message::Graph(
                             GraphMessage::DisconnectLink { link_id, output_port, input_port }
                         )))
+                    } else if let HitResult::None = hit {
+                        Some(canvas::Action::publish(Message::Graph(
+                            GraphMessage::ShowContextMenu { position: cursor_position, target: ContextMenuTarget::Canvas, viewport: bounds.size() }
+                        )))
+                    } else if let HitResult::Node(node_id) = hit {
+                        Some(canvas::Action::publish(Message::Graph(
+                            GraphMessage::ShowContextMenu {
+                                position: cursor_position,
+                                target: ContextMenuTarget::Node(node_id),
+                                viewport: bounds.size(),
+                            }
+                        )))
                     } else {
                         None
                     }
                 }
+                mouse::Event::ButtonPressed(mouse::Button::Middle) => {
+                    // Pan regardless of what's under the cursor
+                    state.interaction = InteractionMode::MiddlePanning { last_pos: cursor_position };
+                    Some(canvas::Action::request_redraw())
+                }
+                mouse::Event::ButtonReleased(mouse::Button::Middle) => {
+                    if let InteractionMode::MiddlePanning { .. } = state.interaction {
+                        state.interaction = InteractionMode::None;
+                    }
+                    Some(canvas::Action::request_redraw())
+                }
                 mouse::Event::ButtonReleased(mouse::Button::Left) => {
                     let action = match state.interaction {
+                        InteractionMode::Dragging { node_id, .. } if self.selected_nodes.contains(&node_id) => {
+                            Some(canvas::Action::publish(Message::Graph(
+                                GraphMessage::MultiNodeDragEnded { node_ids: self.selected_nodes.iter().copied().collect() }
+                            )))
+                        }
                         InteractionMode::Dragging { node_id, .. } => {
                             Some(canvas::Action::publish(Message::Graph(
                                 GraphMessage::NodeDragEnded { node_id }
@@ -1569,6 +5138,48 @@ impl canvas::Program<Message> for Graph {
                                 GraphMessage::GhostDragEnded { ghost_index }
                             )))
                         }
+                        InteractionMode::Selecting { start, .. } => {
+                            Some(canvas::Action::publish(Message::Graph(
+                                GraphMessage::SelectInRect { from: start, to: cursor_position }
+                            )))
+                        }
+                        InteractionMode::ReconnectingLink { link_id, fixed_port, is_output_end, .. } => {
+                            let link = self.links.iter().find(|l| l.id == link_id);
+                            let target = match self.hit_test(cursor_position) {
+                                HitResult::Port { port_id, .. } => self.find_port(port_id),
+                                _ => None,
+                            };
+                            let fixed_type = self.find_port(fixed_port).map(|(_, p)| p.port_type);
+                            let wanted = if is_output_end { PortDirection::Output } else { PortDirection::Input };
+                            match (link, target) {
+                                (Some(link), Some((_, port)))
+                                    if port.direction == wanted && Some(port.port_type) == fixed_type =>
+                                {
+                                    let (new_output, new_input) = if is_output_end {
+                                        (port.id, fixed_port)
+                                    } else {
+                                        (fixed_port, port.id)
+                                    };
+                                    if (new_output, new_input) == (link.output_port, link.input_port) {
+                                        Some(canvas::Action::request_redraw())
+                                    } else {
+                                        Some(canvas::Action::publish(Message::Graph(GraphMessage::ReconnectLink {
+                                            old_output: link.output_port,
+                                            old_input: link.input_port,
+                                            new_output,
+                                            new_input,
+                                        })))
+                                    }
+                                }
+                                _ => Some(canvas::Action::request_redraw()),
+                            }
+                        }
+                        // A click on empty space (no pan movement) clears the selection
+                        InteractionMode::Panning { start, .. }
+                            if start.distance(cursor_position) < 3.0 && !self.selected_nodes.is_empty() =>
+                        {
+                            Some(canvas::Action::publish(Message::Graph(GraphMessage::ClearSelection)))
+                        }
                         _ => Some(canvas::Action::request_redraw()),
                     };
                     state.interaction = InteractionMode::None;
@@ -1586,16 +5197,39 @@ impl canvas::Program<Message> for Graph {
                                 GraphMessage::NodeDragged { node_id, delta }
                             )))
                         }
-                        InteractionMode::Panning { last_pos } => {
+                        InteractionMode::Panning { start, last_pos } => {
+                            let delta = Vector::new(
+                                cursor_position.x - last_pos.x,
+                                cursor_position.y - last_pos.y,
+                            );
+                            state.interaction = InteractionMode::Panning { start, last_pos: cursor_position };
+                            Some(canvas::Action::publish(Message::Graph(
+                                GraphMessage::Pan(delta)
+                            )))
+                        }
+                        InteractionMode::ReconnectingLink { link_id, fixed_port, is_output_end, .. } => {
+                            state.interaction = InteractionMode::ReconnectingLink {
+                                link_id,
+                                fixed_port,
+                                moving_port_world: self.screen_to_world(cursor_position),
+                                is_output_end,
+                            };
+                            Some(canvas::Action::request_redraw())
+                        }
+                        InteractionMode::MiddlePanning { last_pos } => {
                             let delta = Vector::new(
                                 cursor_position.x - last_pos.x,
                                 cursor_position.y - last_pos.y,
                             );
-                            state.interaction = InteractionMode::Panning { last_pos: cursor_position };
+                            state.interaction = InteractionMode::MiddlePanning { last_pos: cursor_position };
                             Some(canvas::Action::publish(Message::Graph(
                                 GraphMessage::Pan(delta)
                             )))
                         }
+                        InteractionMode::Selecting { start, .. } => {
+                            state.interaction = InteractionMode::Selecting { start, current: cursor_position };
+                            Some(canvas::Action::request_redraw())
+                        }
                         InteractionMode::CreatingConnection { .. } => {
                             // Request redraw to update the pending connection line
                             Some(canvas::Action::request_redraw())
@@ -1611,8 +5245,26 @@ impl canvas::Program<Message> for Graph {
                             )))
                         }
                         _ => {
-                            // Redraw for hover effects when picker is open
-                            if self.profile_picker.is_some() {
+                            // Restart tooltip timers when the hovered node or port changes
+                            let (node_id, port) = match self.hit_test(cursor_position) {
+                                HitResult::Node(id) => (Some(id), None),
+                                HitResult::Port { node_id, port_id } => (None, Some((node_id, port_id))),
+                                _ => (None, None),
+                            };
+                            if node_id != self.hovered_node_timer.map(|(id, _)| id) || port != self.hovered_port {
+                                return Some(canvas::Action::publish(Message::Graph(
+                                    GraphMessage::HoverChanged { node_id, port }
+                                )));
+                            }
+
+                            // Redraw for hover effects (picker, delta panel, tooltips)
+                            let has_failed_link = self.links.iter()
+                                .any(|l| matches!(l.state, LinkState::Error(_)));
+                            if self.profile_picker.is_some()
+                                || self.preset_delta.is_some()
+                                || self.show_tooltip.is_some()
+                                || has_failed_link
+                            {
                                 Some(canvas::Action::request_redraw())
                             } else {
                                 None
@@ -1625,6 +5277,15 @@ impl canvas::Program<Message> for Graph {
                         mouse::ScrollDelta::Lines { y, .. } => *y,
                         mouse::ScrollDelta::Pixels { y, .. } => *y / 100.0,
                     };
+                    if let HitResult::Node(node_id) | HitResult::Port { node_id, .. } = self.hit_test(cursor_position)
+                        && self.scrolls_ports(node_id, self.screen_to_world(cursor_position))
+                    {
+                        // Wheel up shows earlier ports
+                        let rows = scroll.abs().ceil() as i32 * if scroll > 0.0 { -1 } else { 1 };
+                        return Some(canvas::Action::publish(Message::Graph(
+                            GraphMessage::ScrollPorts { node_id, rows }
+                        )).and_capture());
+                    }
                     Some(canvas::Action::publish(Message::Graph(
                         GraphMessage::Zoom { delta: scroll, cursor: cursor_position }
                     )))
@@ -1636,6 +5297,28 @@ impl canvas::Program<Message> for Graph {
 
                 // Track ctrl state
                 state.ctrl_held = modifiers.control();
+                state.shift_held = modifiers.shift();
+
+                // While renaming a node, keys edit the name
+                if self.renaming_node.is_some() {
+                    return match key.as_ref() {
+                        Key::Named(iced::keyboard::key::Named::Escape) => {
+                            Some(canvas::Action::publish(Message::Graph(GraphMessage::CancelRename)))
+                        }
+                        Key::Named(iced::keyboard::key::Named::Backspace) => {
+                            Some(canvas::Action::publish(Message::Graph(GraphMessage::RenameBackspace)))
+                        }
+                        Key::Named(iced::keyboard::key::Named::Enter) => {
+                            Some(canvas::Action::publish(Message::Graph(GraphMessage::CommitRename)))
+                        }
+                        _ => text.as_ref()
+                            .filter(|t| !t.is_empty() && !modifiers.control() && !modifiers.alt())
+                            .filter(|t| t.chars().all(|c| !c.is_control()))
+                            .map(|t| canvas::Action::publish(Message::Graph(
+                                GraphMessage::RenameInput { text: t.to_string() }
+                            ))),
+                    };
+                }
 
                 // When search is active, handle typing
                 if self.search_active {
@@ -1646,20 +5329,23 @@ impl canvas::Program<Message> for Graph {
                         Key::Named(iced::keyboard::key::Named::Backspace) => {
                             return Some(canvas::Action::publish(Message::Graph(GraphMessage::SearchBackspace)));
                         }
+                        Key::Named(iced::keyboard::key::Named::Enter) if modifiers.shift() => {
+                            return Some(canvas::Action::publish(Message::Graph(GraphMessage::SearchPrev)));
+                        }
                         Key::Named(iced::keyboard::key::Named::Enter) => {
                             return Some(canvas::Action::publish(Message::Graph(GraphMessage::SearchCommit)));
                         }
                         _ => {
                             // Handle text input
-                            if let Some(txt) = text {
-                                if !txt.is_empty() && !modifiers.control() && !modifiers.alt() {
-                                    let input = txt.to_string();
-                                    // Filter out control characters
-                                    if input.chars().all(|c| !c.is_control()) {
-                                        return Some(canvas::Action::publish(Message::Graph(
-                                            GraphMessage::SearchInput { text: input }
-                                        )));
-                                    }
+                            if let Some(txt) = text
+                                && !txt.is_empty() && !modifiers.control() && !modifiers.alt()
+                            {
+                                let input = txt.to_string();
+                                // Filter out control characters
+                                if input.chars().all(|c| !c.is_control()) {
+                                    return Some(canvas::Action::publish(Message::Graph(
+                                        GraphMessage::SearchInput { text: input }
+                                    )));
                                 }
                             }
                             return None;
@@ -1667,37 +5353,194 @@ impl canvas::Program<Message> for Graph {
                     }
                 }
 
+                // The preset picker takes the keyboard while open
+                if self.show_preset_picker {
+                    use iced::keyboard::key::Named;
+                    let message = match key.as_ref() {
+                        Key::Named(Named::ArrowUp) => Some(GraphMessage::PresetPickerMove(-1)),
+                        Key::Named(Named::ArrowDown) => Some(GraphMessage::PresetPickerMove(1)),
+                        Key::Named(Named::PageUp) => Some(GraphMessage::PresetPickerMove(-(PRESET_PICKER_ROWS as i32))),
+                        Key::Named(Named::PageDown) => Some(GraphMessage::PresetPickerMove(PRESET_PICKER_ROWS as i32)),
+                        Key::Named(Named::Enter) => Some(GraphMessage::PresetPickerApply),
+                        Key::Named(Named::Delete) => Some(GraphMessage::PresetPickerDelete),
+                        Key::Named(Named::Escape) => Some(GraphMessage::ClosePresetPicker),
+                        Key::Character("y") | Key::Character("Y") if self.preset_delete_pending => {
+                            Some(GraphMessage::PresetPickerDelete)
+                        }
+                        Key::Character("n") | Key::Character("N") if !modifiers.control() => {
+                            Some(GraphMessage::PresetPickerNew)
+                        }
+                        Key::Character("o") | Key::Character("O") if !modifiers.control() => {
+                            Some(GraphMessage::PresetPickerBrowse)
+                        }
+                        Key::Character("p") | Key::Character("P") if !modifiers.control() => {
+                            Some(GraphMessage::PresetPickerPreview)
+                        }
+                        _ => None,
+                    };
+                    return Some(match message {
+                        Some(message) => canvas::Action::publish(Message::Graph(message)),
+                        None => canvas::Action::capture(),
+                    });
+                }
+
+                // A preset preview takes Enter (apply) and Esc (cancel)
+                if self.preset_preview.is_some() {
+                    match key.as_ref() {
+                        Key::Named(iced::keyboard::key::Named::Enter) => {
+                            return Some(canvas::Action::publish(Message::Graph(GraphMessage::ApplyPresetPreview)));
+                        }
+                        Key::Named(iced::keyboard::key::Named::Escape) => {
+                            return Some(canvas::Action::publish(Message::Graph(GraphMessage::CancelPresetPreview)));
+                        }
+                        _ => {}
+                    }
+                }
+
+                // Stereo pair prompt takes Y/N
+                if let Some(prompt) = &self.stereo_prompt {
+                    match key.as_ref() {
+                        Key::Character("y") | Key::Character("Y") if !modifiers.control() => {
+                            return Some(canvas::Action::publish(Message::Graph(GraphMessage::ConfirmStereoPair {
+                                second_output: prompt.second_output,
+                                second_input: prompt.second_input,
+                            })));
+                        }
+                        Key::Character("n") | Key::Character("N") if !modifiers.control() => {
+                            return Some(canvas::Action::publish(Message::Graph(GraphMessage::DismissStereoPair)));
+                        }
+                        _ => {}
+                    }
+                }
+
+                // Rebindable shortcuts from the config
+                if let Some(message) = self.shortcut_message(&key.as_ref(), *modifiers, bounds.size()) {
+                    return Some(canvas::Action::publish(Message::Graph(message)));
+                }
+
                 // Normal keyboard handling
                 match key.as_ref() {
                     Key::Named(iced::keyboard::key::Named::Escape) => {
-                        if self.profile_picker.is_some() {
+                        if self.context_menu.is_some() {
+                            Some(canvas::Action::publish(Message::Graph(GraphMessage::DismissContextMenu)))
+                        } else if self.profile_picker.is_some() {
                             Some(canvas::Action::publish(Message::Graph(GraphMessage::DismissProfilePicker)))
                         } else if self.show_help {
                             Some(canvas::Action::publish(Message::Graph(GraphMessage::ToggleHelp)))
+                        } else if self.show_history {
+                            Some(canvas::Action::publish(Message::Graph(GraphMessage::ToggleHistoryPanel)))
+                        } else if self.focused_node.is_some() {
+                            Some(canvas::Action::publish(Message::Graph(GraphMessage::ClearFocus)))
+                        } else if !self.selected_nodes.is_empty() {
+                            Some(canvas::Action::publish(Message::Graph(GraphMessage::ClearSelection)))
                         } else {
                             None
                         }
                     }
-                    // Ctrl+F or / to activate search
-                    Key::Character("f") | Key::Character("F") if modifiers.control() => {
-                        Some(canvas::Action::publish(Message::Graph(GraphMessage::SearchActivate)))
-                    }
+                    // / also activates search
                     Key::Character("/") if !modifiers.control() => {
                         Some(canvas::Action::publish(Message::Graph(GraphMessage::SearchActivate)))
                     }
-                    Key::Character("l") | Key::Character("L") if !modifiers.control() => {
-                        Some(canvas::Action::publish(Message::Graph(GraphMessage::AutoLayout)))
+                    Key::Character("g") | Key::Character("G") if !modifiers.control() => {
+                        Some(canvas::Action::publish(Message::Graph(GraphMessage::ToggleGridSnap)))
                     }
-                    Key::Character("z") | Key::Character("Z") if modifiers.control() && !modifiers.shift() => {
-                        Some(canvas::Action::publish(Message::Graph(GraphMessage::Undo)))
+                    Key::Character("a") | Key::Character("A") if modifiers.control() => {
+                        Some(canvas::Action::publish(Message::Graph(GraphMessage::SelectAll)))
                     }
-                    Key::Character("z") | Key::Character("Z") if modifiers.control() && modifiers.shift() => {
-                        Some(canvas::Action::publish(Message::Graph(GraphMessage::Redo)))
+                    // Port type filter: A audio, Shift+M MIDI, Shift+V video, Shift+A clear
+                    Key::Character("a") | Key::Character("A") if !modifiers.control() => {
+                        let filter = if modifiers.shift() { None } else { Some(PortType::Audio) };
+                        Some(canvas::Action::publish(Message::Graph(GraphMessage::SetPortTypeFilter(filter))))
+                    }
+                    Key::Character("m") | Key::Character("M") if !modifiers.control() && modifiers.shift() => {
+                        Some(canvas::Action::publish(Message::Graph(GraphMessage::SetPortTypeFilter(Some(PortType::Midi)))))
+                    }
+                    Key::Character("v") | Key::Character("V") if !modifiers.control() && modifiers.shift() => {
+                        Some(canvas::Action::publish(Message::Graph(GraphMessage::SetPortTypeFilter(Some(PortType::Video)))))
+                    }
+                    Key::Character("i") | Key::Character("I") if !modifiers.control() && modifiers.shift() => {
+                        Some(canvas::Action::publish(Message::Graph(GraphMessage::IncrementalLayout)))
+                    }
+                    Key::Character("l") | Key::Character("L") if modifiers.control() => {
+                        Some(canvas::Action::publish(Message::Graph(GraphMessage::ToggleLayoutDirection)))
+                    }
+                    Key::Character("l") | Key::Character("L") if !modifiers.control() && modifiers.shift() => {
+                        Some(canvas::Action::publish(Message::Graph(GraphMessage::ForceLayout)))
+                    }
+                    Key::Character("u") | Key::Character("U") if modifiers.control() && modifiers.shift() => {
+                        Some(canvas::Action::publish(Message::Graph(GraphMessage::ShowAllNodes)))
+                    }
+                    Key::Character("h") | Key::Character("H") if modifiers.control() => {
+                        Some(canvas::Action::publish(Message::Graph(GraphMessage::ToggleHistoryPanel)))
                     }
                     Key::Character("y") | Key::Character("Y") if modifiers.control() => {
                         Some(canvas::Action::publish(Message::Graph(GraphMessage::Redo)))
                     }
-                    Key::Character("?") | Key::Named(iced::keyboard::key::Named::F1) => {
+                    Key::Named(iced::keyboard::key::Named::Enter) if self.focused_node.is_some() => {
+                        self.focused_node.map(|node_id| canvas::Action::publish(Message::Graph(
+                            GraphMessage::StartRename { node_id }
+                        )))
+                    }
+                    // Arrow keys / Tab move keyboard focus between nodes
+                    Key::Named(named @ (iced::keyboard::key::Named::ArrowRight
+                        | iced::keyboard::key::Named::ArrowLeft
+                        | iced::keyboard::key::Named::ArrowUp
+                        | iced::keyboard::key::Named::ArrowDown
+                        | iced::keyboard::key::Named::Tab)) => {
+                        use iced::keyboard::key::Named;
+                        let target = match named {
+                            Named::ArrowRight => self.node_in_direction(Vector::new(1.0, 0.0)),
+                            Named::ArrowLeft => self.node_in_direction(Vector::new(-1.0, 0.0)),
+                            Named::ArrowUp => self.node_in_direction(Vector::new(0.0, -1.0)),
+                            Named::ArrowDown => self.node_in_direction(Vector::new(0.0, 1.0)),
+                            _ => self.node_in_sequence(modifiers.shift()),
+                        };
+                        target.map(|node_id| canvas::Action::publish(Message::Graph(
+                            GraphMessage::FocusNode { node_id, viewport: bounds.size() }
+                        )))
+                    }
+                    // Media class filter: Ctrl+1 sources, Ctrl+2 sinks, Ctrl+3 filters, Ctrl+4 video, Ctrl+5 clear
+                    Key::Character(digit @ ("1" | "2" | "3" | "4" | "5")) if modifiers.control() => {
+                        let filter = match digit {
+                            "1" => Some(MediaClass::AudioSource),
+                            "2" => Some(MediaClass::AudioSink),
+                            "3" => Some(MediaClass::Filter),
+                            "4" => Some(MediaClass::VideoSource),
+                            _ => None,
+                        };
+                        Some(canvas::Action::publish(Message::Graph(GraphMessage::SetMediaClassFilter(filter))))
+                    }
+                    Key::Character(digit @ ("1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9"))
+                        if !modifiers.control() && !modifiers.alt() && !modifiers.shift() =>
+                    {
+                        digit.parse().ok().map(|slot| canvas::Action::publish(Message::Graph(
+                            GraphMessage::LoadPresetSlot(slot)
+                        )))
+                    }
+                    Key::Named(iced::keyboard::key::Named::Home) => {
+                        Some(canvas::Action::publish(Message::Graph(GraphMessage::ZoomToFit { viewport: bounds.size() })))
+                    }
+                    Key::Named(iced::keyboard::key::Named::Delete) if !self.selected_nodes.is_empty() => {
+                        Some(canvas::Action::publish(Message::Graph(GraphMessage::DisconnectSelected)))
+                    }
+                    Key::Character("d") | Key::Character("D") if modifiers.control() && self.focused_node.is_some() => {
+                        self.focused_node.map(|node_id| canvas::Action::publish(Message::Graph(
+                            GraphMessage::DisconnectNode { node_id }
+                        )))
+                    }
+                    Key::Character("d") | Key::Character("D") if !modifiers.control() && self.current_preset.is_some() => {
+                        Some(canvas::Action::publish(Message::Graph(GraphMessage::ShowPresetDelta)))
+                    }
+                    Key::Character("e") | Key::Character("E") if modifiers.control() => {
+                        Some(canvas::Action::publish(Message::Graph(GraphMessage::ExportScript)))
+                    }
+                    Key::Character("p") | Key::Character("P") if modifiers.control() => {
+                        Some(canvas::Action::publish(Message::Graph(GraphMessage::OpenPresetPicker)))
+                    }
+                    Key::Character("s") | Key::Character("S") if modifiers.control() && modifiers.shift() => {
+                        Some(canvas::Action::publish(Message::Graph(GraphMessage::ExportPng { viewport: bounds.size() })))
+                    }
+                    Key::Named(iced::keyboard::key::Named::F1) => {
                         Some(canvas::Action::publish(Message::Graph(GraphMessage::ToggleHelp)))
                     }
                     _ => None,
@@ -1705,10 +5548,12 @@ impl canvas::Program<Message> for Graph {
             },
             iced::Event::Keyboard(iced::keyboard::Event::KeyReleased { modifiers, .. }) => {
                 state.ctrl_held = modifiers.control();
+                state.shift_held = modifiers.shift();
                 None
             }
             iced::Event::Keyboard(iced::keyboard::Event::ModifiersChanged(modifiers)) => {
                 state.ctrl_held = modifiers.control();
+                state.shift_held = modifiers.shift();
                 None
             }
             _ => None,
@@ -1724,7 +5569,9 @@ impl canvas::Program<Message> for Graph {
         if cursor.is_over(bounds) {
             match state.interaction {
                 InteractionMode::Dragging { .. } | InteractionMode::DraggingGhost { .. } => mouse::Interaction::Grabbing,
-                InteractionMode::Panning { .. } => mouse::Interaction::Grabbing,
+                InteractionMode::Panning { .. } | InteractionMode::MiddlePanning { .. } => mouse::Interaction::Grabbing,
+                InteractionMode::ReconnectingLink { .. } => mouse::Interaction::Grabbing,
+                InteractionMode::Selecting { .. } => mouse::Interaction::Crosshair,
                 InteractionMode::CreatingConnection { .. } => mouse::Interaction::Crosshair,
                 InteractionMode::None => {
                     if let Some(pos) = cursor.position_in(bounds) {
@@ -1745,11 +5592,17 @@ impl canvas::Program<Message> for Graph {
     }
 }
 
+/// Maximum gap between two clicks on the same node to count as a double-click
+const DOUBLE_CLICK_TIME: std::time::Duration = std::time::Duration::from_millis(300);
+
 #[derive(Debug, Clone, Copy)]
 pub enum InteractionMode {
     None,
     Dragging { node_id: u32, last_pos: Point },
-    Panning { last_pos: Point },
+    Panning { start: Point, last_pos: Point },
+    Selecting { start: Point, current: Point },
+    MiddlePanning { last_pos: Point },
+    ReconnectingLink { link_id: u32, fixed_port: u32, moving_port_world: Point, is_output_end: bool },
     CreatingConnection { from_node: u32, from_port: u32 },
     DraggingGhost { ghost_index: usize, last_pos: Point },
 }
@@ -1758,6 +5611,9 @@ pub enum InteractionMode {
 pub struct CanvasState {
     pub interaction: InteractionMode,
     pub ctrl_held: bool,
+    pub shift_held: bool,
+    /// Last left click on a node, for double-click detection
+    pub last_click: Option<(u32, std::time::Instant)>,
 }
 
 impl Default for CanvasState {
@@ -1765,22 +5621,30 @@ impl Default for CanvasState {
         Self {
             interaction: InteractionMode::None,
             ctrl_held: false,
+            shift_held: false,
+            last_click: None,
         }
     }
 }
 
 // Color palette - Midnight Studio aesthetic
-mod palette {
+pub(crate) mod palette {
     use iced::Color;
 
     // Backgrounds
     pub const NODE_BG: Color = Color::from_rgb(0.11, 0.11, 0.13);
     pub const NODE_HEADER: Color = Color::from_rgb(0.15, 0.15, 0.18);
+    // Header tints by media class
+    pub const HEADER_SOURCE: Color = Color::from_rgb(0.13, 0.20, 0.15);
+    pub const HEADER_SINK: Color = Color::from_rgb(0.13, 0.16, 0.23);
+    pub const HEADER_FILTER: Color = Color::from_rgb(0.19, 0.14, 0.24);
+    pub const HEADER_DEVICE: Color = Color::from_rgb(0.23, 0.18, 0.11);
     pub const NODE_BORDER: Color = Color::from_rgb(0.22, 0.22, 0.26);
     pub const NODE_BORDER_HIGHLIGHT: Color = Color::from_rgb(0.30, 0.30, 0.36);
+    pub const NODE_SELECTED: Color = Color::from_rgb(0.45, 0.70, 0.95);
+    pub const NODE_SELECTED_GLOW: Color = Color::from_rgba(0.45, 0.70, 0.95, 0.25);
 
-    // Accent colors - warm amber for output, cool cyan for input
-    pub const ACCENT_OUTPUT: Color = Color::from_rgb(0.92, 0.65, 0.25);  // Warm amber/gold
+    // Accent color
     pub const ACCENT_INPUT: Color = Color::from_rgb(0.30, 0.75, 0.85);   // Cool cyan
 
     // Port type colors (matches qpwgraph conventions)
@@ -1796,8 +5660,8 @@ mod palette {
     pub const TEXT_SECONDARY: Color = Color::from_rgb(0.55, 0.55, 0.60);
 
     // Links
-    pub const LINK_COLOR: Color = Color::from_rgb(0.50, 0.70, 0.80);
-    pub const LINK_GLOW: Color = Color::from_rgba(0.50, 0.70, 0.80, 0.15);
+    pub const LINK_ERROR: Color = Color::from_rgb(0.95, 0.30, 0.30);
+    pub const LINK_GHOST: Color = Color::from_rgba(0.55, 0.55, 0.60, 0.6);
 
     // Node category accent colors
     pub const CAT_AUDIO_SOURCE: Color = Color::from_rgb(0.92, 0.65, 0.25);    // Warm amber
@@ -1807,6 +5671,47 @@ mod palette {
     pub const CAT_MIDI: Color = Color::from_rgb(0.85, 0.35, 0.35);            // Red
 }
 
+/// Where a dragged item lands: `raw` rounded to the grid when snapping is on
+fn drag_snap(raw: Point, config: &Config) -> Point {
+    if config.snap_to_grid && config.grid_size > 0.0 {
+        let grid = config.grid_size;
+        Point::new((raw.x / grid).round() * grid, (raw.y / grid).round() * grid)
+    } else {
+        raw
+    }
+}
+
+/// A red dashed cable from a known port to a box standing in for the
+/// missing node `missing_id`, to the right of outputs and left of inputs
+fn draw_orphaned_link(frame: &mut Frame, port: Point, missing_id: u32, from_output: bool) {
+    let size = Size::new(80.0, 20.0);
+    let box_pos = if from_output {
+        Point::new(port.x + 90.0, port.y - size.height / 2.0)
+    } else {
+        Point::new(port.x - 90.0 - size.width, port.y - size.height / 2.0)
+    };
+    let (start, end) = if from_output {
+        (port, Point::new(box_pos.x, port.y))
+    } else {
+        (Point::new(box_pos.x + size.width, port.y), port)
+    };
+    let (ctrl1, ctrl2) = link_control_points(start, end);
+    draw_dashed_bezier(frame, [start, ctrl1, ctrl2, end], 5.0, 4.0, palette::LINK_ERROR, 2.0);
+
+    draw_rounded_rect(frame, box_pos, size, 4.0, palette::NODE_BG);
+    frame.stroke(
+        &Path::rectangle(box_pos, size),
+        Stroke::default().with_color(palette::LINK_ERROR).with_width(1.0),
+    );
+    frame.fill_text(Text {
+        content: format!("node {}?", missing_id),
+        position: Point::new(box_pos.x + 6.0, box_pos.y + 4.0),
+        color: palette::LINK_ERROR,
+        size: iced::Pixels(10.0),
+        ..Text::default()
+    });
+}
+
 fn draw_rounded_rect(frame: &mut Frame, pos: Point, size: Size, radius: f32, color: Color) {
     let path = Path::new(|builder| {
         let r = radius.min(size.width / 2.0).min(size.height / 2.0);
@@ -1851,8 +5756,36 @@ fn stroke_rounded_rect(frame: &mut Frame, pos: Point, size: Size, radius: f32, c
     frame.stroke(&path, Stroke::default().with_color(color).with_width(width));
 }
 
-fn draw_node(frame: &mut Frame, node: &Node, dimmed: bool) {
-    let height = Graph::node_height(node);
+/// The header strip showing a node's volume
+fn volume_bar_bounds(node: &Node) -> Rectangle {
+    Rectangle::new(
+        Point::new(node.position.x + 12.0, node.position.y + Graph::node_header_height(node) - 6.0),
+        Size::new(NODE_WIDTH - 36.0, 2.0),
+    )
+}
+
+/// Below this zoom, ports aren't drawn at all
+const LOD_HIDE_PORTS_ZOOM: f32 = 0.35;
+
+/// Graph-wide settings that shape how every node is drawn
+#[derive(Clone, Copy)]
+struct NodeDrawOptions<'a> {
+    /// Ports matching the search, as (node_id, port_id)
+    matched_ports: &'a std::collections::HashSet<(u32, u32)>,
+    /// Ports the pending cable can reach, while dragging one
+    connectable: Option<&'a std::collections::HashSet<u32>>,
+    port_filter: Option<PortType>,
+    zoom: f32,
+    /// Zoom below which port labels are left out
+    lod_threshold: f32,
+    prefer_alias: bool,
+    hide_monitors: bool,
+}
+
+fn draw_node(frame: &mut Frame, node: &Node, window: PortWindow, dimmed: bool, selected: bool, rename: Option<&str>, options: &NodeDrawOptions) {
+    let NodeDrawOptions { matched_ports, connectable, port_filter, zoom, lod_threshold, prefer_alias, hide_monitors } = *options;
+    let height = Graph::node_height(node, window);
+    let header_height = Graph::node_header_height(node);
     let corner_radius = 8.0;
 
     // Opacity modifier for dimmed nodes
@@ -1887,7 +5820,7 @@ fn draw_node(frame: &mut Frame, node: &Node, dimmed: bool) {
         let x = node.position.x;
         let y = node.position.y;
         let w = NODE_WIDTH;
-        let h = NODE_HEADER_HEIGHT;
+        let h = header_height;
 
         builder.move_to(Point::new(x + r, y));
         builder.line_to(Point::new(x + w - r, y));
@@ -1898,12 +5831,12 @@ fn draw_node(frame: &mut Frame, node: &Node, dimmed: bool) {
         builder.arc_to(Point::new(x, y), Point::new(x + r, y), r);
         builder.close();
     });
-    frame.fill(&header_path, dim(palette::NODE_HEADER));
+    frame.fill(&header_path, dim(header_color(node)));
 
     // Accent line under header
     let accent_line = Path::line(
-        Point::new(node.position.x, node.position.y + NODE_HEADER_HEIGHT),
-        Point::new(node.position.x + NODE_WIDTH, node.position.y + NODE_HEADER_HEIGHT),
+        Point::new(node.position.x, node.position.y + header_height),
+        Point::new(node.position.x + NODE_WIDTH, node.position.y + header_height),
     );
     frame.stroke(
         &accent_line,
@@ -1922,6 +5855,26 @@ fn draw_node(frame: &mut Frame, node: &Node, dimmed: bool) {
         1.0,
     );
 
+    // Selection glow
+    if selected {
+        stroke_rounded_rect(
+            frame,
+            Point::new(node.position.x - 2.0, node.position.y - 2.0),
+            Size::new(NODE_WIDTH + 4.0, height + 4.0),
+            corner_radius + 2.0,
+            palette::NODE_SELECTED_GLOW,
+            4.0,
+        );
+        stroke_rounded_rect(
+            frame,
+            node.position,
+            Size::new(NODE_WIDTH, height),
+            corner_radius,
+            palette::NODE_SELECTED,
+            2.0,
+        );
+    }
+
     // Left accent stripe (category color)
     if let Some(category) = NodeCategory::from_node(node) {
         let stripe_width = 3.0;
@@ -1949,18 +5902,202 @@ fn draw_node(frame: &mut Frame, node: &Node, dimmed: bool) {
     } else {
         name_to_display.clone()
     };
-    let title = Text {
-        content: display_name,
-        position: Point::new(node.position.x + 12.0, node.position.y + 7.0),
-        color: dim(palette::TEXT_PRIMARY),
-        size: iced::Pixels(13.0),
+    if let Some(text) = rename {
+        // Inline rename field: darker box, underline and cursor
+        let field_pos = Point::new(node.position.x + 6.0, node.position.y + 4.0);
+        let field_size = Size::new(NODE_WIDTH - 28.0, NODE_HEADER_HEIGHT - 8.0);
+        draw_rounded_rect(frame, field_pos, field_size, 3.0, palette::NODE_BG);
+        frame.stroke(
+            &Path::line(
+                Point::new(field_pos.x + 4.0, field_pos.y + field_size.height - 2.0),
+                Point::new(field_pos.x + field_size.width - 4.0, field_pos.y + field_size.height - 2.0),
+            ),
+            Stroke::default().with_color(palette::PORT_AUDIO).with_width(1.0),
+        );
+        // Show the tail of long names so the cursor stays visible
+        let visible: String = {
+            let chars: Vec<char> = text.chars().collect();
+            let start = chars.len().saturating_sub(max_chars - 1);
+            chars[start..].iter().collect()
+        };
+        frame.fill_text(Text {
+            content: format!("{}|", visible),
+            position: Point::new(node.position.x + 12.0, node.position.y + 7.0),
+            color: palette::TEXT_PRIMARY,
+            size: iced::Pixels(13.0),
+            ..Text::default()
+        });
+    } else {
+        let title = Text {
+            content: display_name,
+            position: Point::new(node.position.x + 12.0, node.position.y + 7.0),
+            color: dim(palette::TEXT_PRIMARY),
+            size: iced::Pixels(13.0),
+            ..Text::default()
+        };
+        frame.fill_text(title);
+    }
+
+    // App name under the title, so same-named streams from different apps
+    // can be told apart
+    if let Some(subtitle) = Graph::subtitle(node) {
+        frame.fill_text(Text {
+            content: subtitle,
+            position: Point::new(node.position.x + 12.0, node.position.y + 24.0),
+            color: dim(palette::TEXT_SECONDARY),
+            size: iced::Pixels(10.0),
+            ..Text::default()
+        });
+    }
+
+    // Volume bar under the title, red with an "M" when muted
+    if let Some(volume) = node.volume {
+        let bar = volume_bar_bounds(node);
+        let color = if node.muted { palette::LINK_ERROR } else { palette::PORT_AUDIO };
+        frame.fill_rectangle(bar.position(), bar.size(), dim(palette::NODE_BG));
+        frame.fill_rectangle(
+            bar.position(),
+            Size::new(bar.width * volume.clamp(0.0, 1.0), bar.height),
+            dim(color),
+        );
+        if node.muted {
+            frame.fill_text(Text {
+                content: "M".to_string(),
+                position: Point::new(bar.x + bar.width + 3.0, bar.y - 3.0),
+                color: dim(palette::LINK_ERROR),
+                size: iced::Pixels(8.0),
+                ..Text::default()
+            });
+        }
+    }
+
+    // Collapse chevron
+    frame.fill_text(Text {
+        content: if node.collapsed { "▶" } else { "▼" }.to_string(),
+        position: Point::new(node.position.x + NODE_WIDTH - 18.0, node.position.y + 9.0),
+        color: dim(palette::TEXT_SECONDARY),
+        size: iced::Pixels(9.0),
         ..Text::default()
+    });
+
+    // State dot in the header's top-right corner
+    let state_color = match node.state {
+        NodeState::Running => Some(palette::PORT_AUDIO),
+        NodeState::Idle => Some(Color::from_rgb(0.90, 0.80, 0.30)),
+        NodeState::Suspended => Some(palette::TEXT_SECONDARY),
+        NodeState::Error => Some(palette::LINK_ERROR),
+        NodeState::Creating => None,
     };
-    frame.fill_text(title);
+    if let Some(color) = state_color {
+        let dot = Path::circle(Point::new(node.position.x + NODE_WIDTH - 6.0, node.position.y + 6.0), 2.5);
+        frame.fill(&dot, dim(color));
+    }
+
+    // Star left of the padlock for the default sink and source
+    if node.is_default {
+        frame.fill_text(Text {
+            content: "★".to_string(),
+            position: Point::new(node.position.x + NODE_WIDTH - 48.0, node.position.y + 8.0),
+            color: dim(Color::from_rgb(0.95, 0.80, 0.35)),
+            size: iced::Pixels(10.0),
+            ..Text::default()
+        });
+    }
+
+    // Padlock left of the chevron for nodes pinned against auto-layout
+    if node.layout_pinned {
+        let lock_x = node.position.x + NODE_WIDTH - 34.0;
+        let lock_y = node.position.y + 8.0;
+        let shackle = Path::new(|builder| {
+            builder.move_to(Point::new(lock_x + 1.5, lock_y + 5.0));
+            builder.line_to(Point::new(lock_x + 1.5, lock_y + 3.0));
+            builder.arc_to(Point::new(lock_x + 1.5, lock_y), Point::new(lock_x + 4.0, lock_y), 2.5);
+            builder.arc_to(Point::new(lock_x + 6.5, lock_y), Point::new(lock_x + 6.5, lock_y + 3.0), 2.5);
+            builder.line_to(Point::new(lock_x + 6.5, lock_y + 5.0));
+        });
+        frame.stroke(&shackle, Stroke::default().with_color(dim(palette::TEXT_SECONDARY)).with_width(1.2));
+        frame.fill_rectangle(
+            Point::new(lock_x, lock_y + 5.0),
+            Size::new(8.0, 6.0),
+            dim(palette::TEXT_SECONDARY),
+        );
+    }
+
+    // Collapsed nodes, and everything at very low zoom, stop at the header
+    if node.collapsed || zoom < LOD_HIDE_PORTS_ZOOM {
+        return;
+    }
+    let show_labels = zoom >= lod_threshold;
+
+    // "▲ N hidden" / "▼ M hidden" on the rows around a scrolled port list
+    for (ports, direction) in [(&node.input_ports, PortDirection::Input), (&node.output_ports, PortDirection::Output)] {
+        let (above, below) = window.hidden(ports.len());
+        let label_x = match direction {
+            PortDirection::Input => node.position.x + 12.0,
+            PortDirection::Output => node.position.x + NODE_WIDTH / 2.0 + 8.0,
+        };
+        let row_y = |row: usize| {
+            node.position.y + header_height + PORT_SPACING + row as f32 * (PORT_HEIGHT + PORT_SPACING) + PORT_HEIGHT / 2.0 - 5.0
+        };
+        for (count, arrow, row) in [(above, "▲", 0), (below, "▼", window.max_visible + 1)] {
+            if count > 0 {
+                frame.fill_text(Text {
+                    content: format!("{} {} hidden", arrow, count),
+                    position: Point::new(label_x, row_y(row)),
+                    color: dim(palette::TEXT_SECONDARY),
+                    size: iced::Pixels(10.0),
+                    ..Text::default()
+                });
+            }
+        }
+    }
+
+    // Where the group changes down a port column, a rule with the new
+    // group's name in the gap between the rows
+    if show_labels {
+        for (ports, direction) in [(&node.input_ports, PortDirection::Input), (&node.output_ports, PortDirection::Output)] {
+            let (x_start, x_end) = match direction {
+                PortDirection::Input => (node.position.x + 10.0, node.position.x + NODE_WIDTH / 2.0 - 4.0),
+                PortDirection::Output => (node.position.x + NODE_WIDTH / 2.0 + 4.0, node.position.x + NODE_WIDTH - 10.0),
+            };
+            let mut previous: Option<&Option<String>> = None;
+            for port in ports.iter().filter(|p| {
+                port_filter.is_none_or(|t| t == p.port_type)
+                    && !(hide_monitors && p.is_monitor)
+                    && Graph::port_shown(node, p, window)
+            }) {
+                if let Some(previous) = previous
+                    && *previous != port.group
+                {
+                    let y = Graph::port_position(node, port, window).y - (PORT_HEIGHT + PORT_SPACING) / 2.0;
+                    let label = port.group.as_deref().unwrap_or("");
+                    let label_width = label.chars().count() as f32 * 4.5;
+                    frame.fill_text(Text {
+                        content: label.to_string(),
+                        position: Point::new(x_start, y - 4.5),
+                        color: dim(palette::TEXT_SECONDARY),
+                        size: iced::Pixels(8.0),
+                        ..Text::default()
+                    });
+                    frame.stroke(
+                        &Path::line(Point::new(x_start + label_width + 3.0, y), Point::new(x_end, y)),
+                        Stroke::default().with_color(dim(palette::NODE_BORDER)).with_width(1.0),
+                    );
+                }
+                previous = Some(&port.group);
+            }
+        }
+    }
 
     // Draw ports
     for port in node.input_ports.iter().chain(node.output_ports.iter()) {
-        let pos = Graph::port_position(node, port);
+        if port_filter.is_some_and(|t| t != port.port_type)
+            || (hide_monitors && port.is_monitor)
+            || !Graph::port_shown(node, port, window)
+        {
+            continue;
+        }
+        let pos = Graph::port_position(node, port, window);
 
         let (port_color, glow_color) = match port.port_type {
             PortType::Audio => (palette::PORT_AUDIO, palette::PORT_AUDIO_GLOW),
@@ -1968,24 +6105,77 @@ fn draw_node(frame: &mut Frame, node: &Node, dimmed: bool) {
             PortType::Video => (palette::PORT_VIDEO, palette::PORT_VIDEO_GLOW),
         };
 
+        // Ports matched by a port search get a wider, brighter glow
+        if matched_ports.contains(&(node.id, port.id)) {
+            let highlight = Path::circle(pos, PORT_RADIUS + 5.0);
+            frame.fill(&highlight, Color { a: 0.55, ..port_color });
+        }
+
+        // While a connection is being dragged, ports it can land on glow
+        // white and the rest fade out
+        let reachable = connectable.map(|ports| ports.contains(&port.id));
+        if reachable == Some(true) {
+            let highlight = Path::circle(pos, PORT_RADIUS + 4.0);
+            frame.fill(&highlight, dim(Color::from_rgba(1.0, 1.0, 1.0, 0.35)));
+        }
+        let port_dim = |c: Color| -> Color {
+            let c = dim(c);
+            if reachable == Some(false) { Color { a: c.a * 0.3, ..c } } else { c }
+        };
+
         // Outer glow
         let glow = Path::circle(pos, PORT_RADIUS + 3.0);
-        frame.fill(&glow, dim(glow_color));
+        frame.fill(&glow, port_dim(glow_color));
 
-        // Port circle
+        // Port circle; monitor ports are hollow with a small "M"
         let circle = Path::circle(pos, PORT_RADIUS);
-        frame.fill(&circle, dim(port_color));
+        if port.is_monitor {
+            frame.fill(&circle, dim(palette::NODE_BG));
+            frame.stroke(&circle, Stroke::default().with_color(port_dim(port_color)).with_width(1.5));
+            frame.fill_text(Text {
+                content: "M".to_string(),
+                position: Point::new(pos.x + PORT_RADIUS - 1.0, pos.y - PORT_RADIUS - 6.0),
+                color: port_dim(palette::TEXT_SECONDARY),
+                size: iced::Pixels(7.0),
+                ..Text::default()
+            });
+        } else {
+            frame.fill(&circle, port_dim(port_color));
+
+            // Inner highlight
+            let inner = Path::circle(pos, PORT_RADIUS - 2.0);
+            frame.fill(&inner, port_dim(Color::from_rgba(1.0, 1.0, 1.0, 0.15)));
+        }
+
+        // Sample rate badge under the port ("48k"), to spot mismatches up close
+        if let Some(rate) = port.sample_rate
+            && zoom > 0.8
+        {
+            let khz = rate as f32 / 1000.0;
+            let label = if khz.fract() == 0.0 { format!("{}k", khz) } else { format!("{:.1}k", khz) };
+            frame.fill_text(Text {
+                content: label,
+                position: Point::new(pos.x - 6.0, pos.y + PORT_RADIUS + 1.0),
+                color: dim(palette::TEXT_SECONDARY),
+                size: iced::Pixels(7.0),
+                ..Text::default()
+            });
+        }
 
-        // Inner highlight
-        let inner = Path::circle(pos, PORT_RADIUS - 2.0);
-        frame.fill(&inner, dim(Color::from_rgba(1.0, 1.0, 1.0, 0.15)));
+        if !show_labels {
+            continue;
+        }
 
         // Port label (truncate if too long)
         let max_port_chars = 12;
-        let port_display = if port.name.len() > max_port_chars {
-            format!("{}…", &port.name[..max_port_chars - 1])
+        let port_name = match &port.alias {
+            Some(alias) if prefer_alias => alias,
+            _ => &port.name,
+        };
+        let port_display = if port_name.chars().count() > max_port_chars {
+            format!("{}…", port_name.chars().take(max_port_chars - 1).collect::<String>())
         } else {
-            port.name.clone()
+            port_name.clone()
         };
         let label_x = match port.direction {
             PortDirection::Input => pos.x + PORT_RADIUS + 6.0,
@@ -2002,7 +6192,21 @@ fn draw_node(frame: &mut Frame, node: &Node, dimmed: bool) {
     }
 }
 
-fn draw_bezier_link(frame: &mut Frame, start: Point, end: Point, port_type: PortType) {
+/// Header background: amber for hardware, then tinted by media class
+fn header_color(node: &Node) -> Color {
+    if node.device_id.is_some() {
+        return palette::HEADER_DEVICE;
+    }
+    match node.class() {
+        MediaClass::AudioSource | MediaClass::VideoSource | MediaClass::MidiSource => palette::HEADER_SOURCE,
+        MediaClass::AudioSink | MediaClass::VideoSink | MediaClass::MidiSink => palette::HEADER_SINK,
+        MediaClass::Filter => palette::HEADER_FILTER,
+        MediaClass::AudioDuplex | MediaClass::Other => palette::NODE_HEADER,
+    }
+}
+
+/// Control points for a link cable running from an output port to an input port
+pub(crate) fn link_control_points(start: Point, end: Point) -> (Point, Point) {
     let dx = end.x - start.x;
     let dy = (end.y - start.y).abs();
 
@@ -2019,13 +6223,54 @@ fn draw_bezier_link(frame: &mut Frame, start: Point, end: Point, port_type: Port
     let min_offset = 20.0 + 40.0 * alignment_factor;  // 20-60 based on alignment
     let control_offset = (horizontal_dist / 2.0).max(min_offset);
 
+    (
+        Point::new(start.x + control_offset, start.y),
+        Point::new(end.x - control_offset, end.y),
+    )
+}
+
+/// Whether a link cable certainly lies outside `viewport`. The curve stays
+/// inside the hull of its control points, so it is offscreen when all four sit
+/// beyond the same edge.
+fn link_offscreen(start: Point, end: Point, viewport: &Rectangle) -> bool {
+    let (ctrl1, ctrl2) = link_control_points(start, end);
+    let points = [start, ctrl1, ctrl2, end];
+    points.iter().all(|p| p.x < viewport.x)
+        || points.iter().all(|p| p.x > viewport.x + viewport.width)
+        || points.iter().all(|p| p.y < viewport.y)
+        || points.iter().all(|p| p.y > viewport.y + viewport.height)
+}
+
+fn draw_bezier_link(frame: &mut Frame, start: Point, end: Point, port_type: PortType, state: &LinkState) {
+    let (ctrl1, ctrl2) = link_control_points(start, end);
+    draw_link_curve(frame, [start, ctrl1, ctrl2, end], port_type, state);
+}
+
+/// A link cable along the given cubic curve, styled by type and state
+fn draw_link_curve(frame: &mut Frame, [start, ctrl1, ctrl2, end]: [Point; 4], port_type: PortType, state: &LinkState) {
+
+    // Failed links: red dashed cable with a warning marker at the midpoint
+    if let LinkState::Error(_) = state {
+        draw_dashed_bezier(frame, [start, ctrl1, ctrl2, end], 8.0, 6.0, palette::LINK_ERROR, 2.5);
+        let mid = Graph::cubic_bezier(start, ctrl1, ctrl2, end, 0.5);
+        frame.fill(&Path::circle(mid, 9.0), palette::NODE_BG);
+        frame.stroke(
+            &Path::circle(mid, 9.0),
+            Stroke::default().with_color(palette::LINK_ERROR).with_width(1.5),
+        );
+        frame.fill_text(Text {
+            content: "⚠".to_string(),
+            position: Point::new(mid.x - 5.0, mid.y - 7.0),
+            color: palette::LINK_ERROR,
+            size: iced::Pixels(11.0),
+            ..Text::default()
+        });
+        return;
+    }
+
     let path = Path::new(|builder| {
         builder.move_to(start);
-        builder.bezier_curve_to(
-            Point::new(start.x + control_offset, start.y),
-            Point::new(end.x - control_offset, end.y),
-            end,
-        );
+        builder.bezier_curve_to(ctrl1, ctrl2, end);
     });
 
     // Color based on port type
@@ -2035,6 +6280,26 @@ fn draw_bezier_link(frame: &mut Frame, start: Point, end: Point, port_type: Port
         PortType::Video => (palette::PORT_VIDEO, palette::PORT_VIDEO_GLOW),
     };
 
+    match state {
+        // Paused: short dashes in the port color
+        LinkState::Paused => {
+            draw_dashed_bezier(frame, [start, ctrl1, ctrl2, end], 4.0, 4.0, color, 2.0);
+            return;
+        }
+        // Negotiating: a faint cable; the flow layer pulses it until it settles
+        LinkState::Negotiating => {
+            frame.stroke(
+                &path,
+                Stroke::default()
+                    .with_color(Color { a: 0.3, ..color })
+                    .with_width(2.0)
+                    .with_line_cap(canvas::LineCap::Round),
+            );
+            return;
+        }
+        LinkState::Active | LinkState::Error(_) => {}
+    }
+
     // Outer glow layer
     frame.stroke(
         &path,
@@ -2119,7 +6384,148 @@ fn draw_pending_link(frame: &mut Frame, start: Point, end: Point, direction: Por
     frame.fill(&cursor_inner, color);
 }
 
-fn draw_help_overlay(frame: &mut Frame, size: Size) {
+/// Rows the preset picker shows before it scrolls
+const PRESET_PICKER_ROWS: usize = 10;
+const PRESET_PICKER_ROW_HEIGHT: f32 = 40.0;
+const PRESET_PICKER_WIDTH: f32 = 360.0;
+
+/// The picker box, and the first preset row shown so `selected` stays visible
+fn preset_picker_layout(size: Size, count: usize, selected: usize) -> (Rectangle, usize) {
+    let rows = count.clamp(1, PRESET_PICKER_ROWS);
+    let height = rows as f32 * PRESET_PICKER_ROW_HEIGHT + 90.0;
+    let rect = Rectangle::new(
+        Point::new((size.width - PRESET_PICKER_WIDTH) / 2.0, (size.height - height) / 2.0),
+        Size::new(PRESET_PICKER_WIDTH, height),
+    );
+    (rect, (selected + 1).saturating_sub(PRESET_PICKER_ROWS))
+}
+
+fn preset_picker_row_rect(rect: Rectangle, row: usize) -> Rectangle {
+    Rectangle::new(
+        Point::new(rect.x + 10.0, rect.y + 50.0 + row as f32 * PRESET_PICKER_ROW_HEIGHT),
+        Size::new(rect.width - 20.0, PRESET_PICKER_ROW_HEIGHT),
+    )
+}
+
+/// Index of the preset under `cursor`, if any
+fn preset_picker_row_at(size: Size, count: usize, selected: usize, cursor: Point) -> Option<usize> {
+    let (rect, first) = preset_picker_layout(size, count, selected);
+    (first..count.min(first + PRESET_PICKER_ROWS))
+        .find(|&i| preset_picker_row_rect(rect, i - first).contains(cursor))
+}
+
+/// A stable chip color per tag
+fn tag_color(tag: &str) -> Color {
+    const COLORS: [Color; 4] = [palette::PORT_AUDIO, palette::PORT_MIDI, palette::PORT_VIDEO, palette::ACCENT_INPUT];
+    let hash = tag.bytes().fold(0usize, |h, b| h.wrapping_mul(31).wrapping_add(b as usize));
+    COLORS[hash % COLORS.len()]
+}
+
+fn draw_preset_picker(
+    frame: &mut Frame,
+    size: Size,
+    presets: &[(crate::preset::Preset, std::path::PathBuf)],
+    selected: usize,
+    delete_pending: bool,
+    loaded: Option<&std::path::Path>,
+) {
+    frame.fill_rectangle(Point::ORIGIN, size, Color::from_rgba(0.0, 0.0, 0.0, 0.6));
+
+    let (rect, first) = preset_picker_layout(size, presets.len(), selected);
+    draw_rounded_rect(frame, rect.position(), rect.size(), 12.0, Color::from_rgb(0.12, 0.12, 0.14));
+    frame.fill_text(Text {
+        content: "Presets".to_string(),
+        position: Point::new(rect.x + 20.0, rect.y + 18.0),
+        color: palette::TEXT_PRIMARY,
+        size: iced::Pixels(16.0),
+        ..Text::default()
+    });
+
+    if presets.is_empty() {
+        frame.fill_text(Text {
+            content: "No presets yet. Press N to save the current connections.".to_string(),
+            position: Point::new(rect.x + 20.0, rect.y + 56.0),
+            color: palette::TEXT_SECONDARY,
+            size: iced::Pixels(12.0),
+            ..Text::default()
+        });
+    }
+    for (row, (preset, path)) in presets.iter().enumerate().skip(first).take(PRESET_PICKER_ROWS) {
+        let row_rect = preset_picker_row_rect(rect, row - first);
+        if row == selected {
+            draw_rounded_rect(frame, row_rect.position(), row_rect.size(), 4.0, palette::NODE_HEADER);
+        }
+
+        // Tag chips, right-aligned on the name line
+        let mut chip_x = row_rect.x + row_rect.width - 8.0;
+        for tag in preset.tags.iter().rev() {
+            let tag: String = tag.chars().take(12).collect();
+            let chip_w = tag.chars().count() as f32 * 5.5 + 10.0;
+            if chip_x - chip_w < row_rect.x + row_rect.width / 2.0 {
+                break;
+            }
+            chip_x -= chip_w;
+            let color = tag_color(&tag);
+            draw_rounded_rect(
+                frame,
+                Point::new(chip_x, row_rect.y + 5.0),
+                Size::new(chip_w, 14.0),
+                7.0,
+                Color { a: 0.25, ..color },
+            );
+            frame.fill_text(Text {
+                content: tag,
+                position: Point::new(chip_x + 5.0, row_rect.y + 7.0),
+                color,
+                size: iced::Pixels(9.0),
+                ..Text::default()
+            });
+            chip_x -= 4.0;
+        }
+
+        let max_chars = ((chip_x - row_rect.x - 10.0) / 7.0).max(4.0) as usize;
+        let mut label: String = preset.name.chars().take(max_chars).collect();
+        if loaded == Some(path.as_path()) {
+            label.push_str("  (loaded)");
+        }
+        frame.fill_text(Text {
+            content: label,
+            position: Point::new(row_rect.x + 10.0, row_rect.y + 5.0),
+            color: if row == selected { palette::TEXT_PRIMARY } else { palette::TEXT_SECONDARY },
+            size: iced::Pixels(13.0),
+            ..Text::default()
+        });
+        if !preset.description.is_empty() {
+            frame.fill_text(Text {
+                content: preset.description.chars().take(56).collect(),
+                position: Point::new(row_rect.x + 10.0, row_rect.y + 23.0),
+                color: palette::TEXT_SECONDARY,
+                size: iced::Pixels(10.0),
+                ..Text::default()
+            });
+        }
+    }
+
+    let (hint, color) = match presets.get(selected) {
+        Some((preset, _)) if delete_pending => (
+            format!("Delete {}? Y or Delete to confirm, Esc to cancel", preset.name),
+            palette::LINK_ERROR,
+        ),
+        _ => (
+            "Enter apply · P preview · Del delete · N new · O open · Esc close".to_string(),
+            Color::from_rgba(1.0, 1.0, 1.0, 0.4),
+        ),
+    };
+    frame.fill_text(Text {
+        content: hint,
+        position: Point::new(rect.x + 20.0, rect.y + rect.height - 25.0),
+        color,
+        size: iced::Pixels(10.0),
+        ..Text::default()
+    });
+}
+
+fn draw_help_overlay(frame: &mut Frame, size: Size, bindings: &HashMap<String, KeyBinding>) {
     // Semi-transparent background
     frame.fill_rectangle(
         Point::ORIGIN,
@@ -2127,23 +6533,55 @@ fn draw_help_overlay(frame: &mut Frame, size: Size) {
         Color::from_rgba(0.0, 0.0, 0.0, 0.75),
     );
 
+    let bound = |action: &str| bindings.get(action).map(KeyBinding::label).unwrap_or_default();
     let shortcuts = [
-        ("L", "Auto-layout"),
-        ("Ctrl+F  /  /", "Search nodes"),
-        ("Ctrl+Z", "Undo"),
-        ("Ctrl+Shift+Z", "Redo"),
-        ("Ctrl+Y", "Redo"),
-        ("?  /  F1", "Toggle help"),
-        ("Esc", "Close overlay"),
-        ("", ""),
-        ("Mouse", ""),
-        ("Drag port", "Connect"),
-        ("Right-click link", "Disconnect"),
-        ("Drag node", "Move"),
-        ("Drag empty", "Pan"),
-        ("Scroll", "Zoom"),
-        ("Ctrl+Right node", "Deactivate device"),
-        ("Ctrl+Right ghost", "Activate device"),
+        (bound("auto_layout"), "Auto-layout"),
+        ("Shift+L".to_string(), "Force-directed layout"),
+        ("Shift+I".to_string(), "Place new nodes only"),
+        ("Ctrl+L".to_string(), "Flip layout direction"),
+        (format!("{}  /  Home", bound("zoom_fit")), "Zoom to fit"),
+        ("G".to_string(), "Toggle grid snap"),
+        (bound("toggle_minimap"), "Toggle minimap"),
+        ("A".to_string(), "Show audio ports only"),
+        ("Shift+M / Shift+V".to_string(), "MIDI / video only"),
+        ("Shift+A".to_string(), "Show all port types"),
+        ("Arrows  /  Tab".to_string(), "Focus next node"),
+        ("Enter".to_string(), "Rename focused node"),
+        (format!("{}  /  /", bound("search")), "Search nodes"),
+        (bound("undo"), "Undo"),
+        (bound("redo"), "Redo"),
+        ("Ctrl+Y".to_string(), "Redo"),
+        ("Ctrl+H".to_string(), "Undo history"),
+        ("Ctrl+P".to_string(), "Preset picker"),
+        ("1 - 9".to_string(), "Load preset slot"),
+        ("Ctrl+1 - 4".to_string(), "Filter by media class"),
+        ("Ctrl+5".to_string(), "Show all media classes"),
+        ("D".to_string(), "Preset changes"),
+        ("Ctrl+A".to_string(), "Select all nodes"),
+        ("Ctrl+Shift+U".to_string(), "Show hidden nodes"),
+        ("Delete".to_string(), "Disconnect selected"),
+        ("Ctrl+D".to_string(), "Disconnect focused node"),
+        ("Ctrl+Shift+S".to_string(), "Save screenshot"),
+        ("Ctrl+E".to_string(), "Export pw-link script"),
+        (format!("{}  /  F1", bound("toggle_help")), "Toggle help"),
+        ("Esc".to_string(), "Close overlay"),
+        ("".to_string(), ""),
+        ("Mouse".to_string(), ""),
+        ("Drag port".to_string(), "Connect"),
+        ("Right-click link".to_string(), "Disconnect"),
+        ("Shift+Right link".to_string(), "Pin / unpin link"),
+        ("Right-click empty".to_string(), "Export menu"),
+        ("Right-click node".to_string(), "Node menu"),
+        ("Drag link end".to_string(), "Reconnect"),
+        ("Drag node".to_string(), "Move"),
+        ("Double-click header".to_string(), "Collapse node"),
+        ("Double-click body".to_string(), "Rename node"),
+        ("Drag empty".to_string(), "Pan"),
+        ("Shift+Drag empty".to_string(), "Select nodes"),
+        ("Middle-drag".to_string(), "Pan"),
+        ("Scroll".to_string(), "Zoom"),
+        ("Ctrl+Right node".to_string(), "Deactivate device"),
+        ("Ctrl+Right ghost".to_string(), "Activate device"),
     ];
 
     let box_width = 280.0;
@@ -2209,7 +6647,7 @@ fn draw_help_overlay(frame: &mut Frame, size: Size) {
     frame.fill_text(hint);
 }
 
-fn draw_search_overlay(frame: &mut Frame, size: Size, query: &str, match_count: usize) {
+fn draw_search_overlay(frame: &mut Frame, size: Size, query: &str, match_count: usize, current: Option<usize>, error: Option<&str>) {
     // Search bar at top center
     let bar_width = 320.0;
     let bar_height = 40.0;
@@ -2276,10 +6714,10 @@ fn draw_search_overlay(frame: &mut Frame, size: Size, query: &str, match_count:
 
     // Match count (right side)
     if !query.is_empty() {
-        let count_text = if match_count == 1 {
-            "1 match".to_string()
-        } else {
-            format!("{} matches", match_count)
+        let count_text = match current {
+            Some(index) if match_count > 0 => format!("{} / {}", index + 1, match_count),
+            _ if match_count == 1 => "1 match".to_string(),
+            _ => format!("{} matches", match_count),
         };
         let count = Text {
             content: count_text,
@@ -2291,15 +6729,52 @@ fn draw_search_overlay(frame: &mut Frame, size: Size, query: &str, match_count:
         frame.fill_text(count);
     }
 
+    // Invalid regex: show why instead of the hint
+    if let Some(error) = error {
+        frame.fill_text(Text {
+            content: error.to_string(),
+            position: Point::new(bar_x + 4.0, bar_y + bar_height + 8.0),
+            color: palette::PORT_MIDI,
+            size: iced::Pixels(11.0),
+            ..Text::default()
+        });
+        return;
+    }
+
     // Hint below
     let hint = Text {
-        content: "Enter to focus • Esc to close".to_string(),
-        position: Point::new(bar_x + (bar_width - 160.0) / 2.0, bar_y + bar_height + 8.0),
+        content: "Enter / Shift+Enter to step through • Esc to close".to_string(),
+        position: Point::new(bar_x + (bar_width - 260.0) / 2.0, bar_y + bar_height + 8.0),
         color: Color::from_rgba(1.0, 1.0, 1.0, 0.4),
         size: iced::Pixels(10.0),
         ..Text::default()
-    };
-    frame.fill_text(hint);
+    };
+    frame.fill_text(hint);
+}
+
+/// A node that was just removed: faded, with a dashed border and no ports
+fn draw_departed_node(frame: &mut Frame, node: &Node, height: f32) {
+    let opacity = 0.4;
+    let dim = |c: Color| Color { a: c.a * opacity, ..c };
+    let Point { x, y } = node.position;
+    let w = NODE_WIDTH;
+
+    draw_rounded_rect(frame, node.position, Size::new(w, height), 8.0, dim(palette::NODE_BG));
+    draw_rounded_rect(frame, node.position, Size::new(w, Graph::node_header_height(node)), 8.0, dim(palette::NODE_HEADER));
+
+    let border_color = dim(palette::TEXT_SECONDARY);
+    draw_dashed_line(frame, Point::new(x, y), Point::new(x + w, y), 6.0, 4.0, border_color, 1.0);
+    draw_dashed_line(frame, Point::new(x, y + height), Point::new(x + w, y + height), 6.0, 4.0, border_color, 1.0);
+    draw_dashed_line(frame, Point::new(x, y), Point::new(x, y + height), 6.0, 4.0, border_color, 1.0);
+    draw_dashed_line(frame, Point::new(x + w, y), Point::new(x + w, y + height), 6.0, 4.0, border_color, 1.0);
+
+    frame.fill_text(Text {
+        content: node.custom_name.as_ref().unwrap_or(&node.name).chars().take(28).collect(),
+        position: Point::new(x + 12.0, y + 8.0),
+        color: dim(palette::TEXT_PRIMARY),
+        size: iced::Pixels(13.0),
+        ..Text::default()
+    });
 }
 
 fn draw_ghost_node(frame: &mut Frame, ghost: &GhostNode) {
@@ -2410,6 +6885,551 @@ fn draw_dashed_line(frame: &mut Frame, start: Point, end: Point, dash: f32, gap:
     }
 }
 
+fn draw_dashed_bezier(frame: &mut Frame, curve: [Point; 4], dash: f32, gap: f32, color: Color, width: f32) {
+    // Walk the curve in small steps, alternating between drawn and skipped runs
+    const STEPS: usize = 64;
+    let [p0, p1, p2, p3] = curve;
+    let mut drawing = true;
+    let mut remaining = dash;
+    let mut prev = p0;
+    let mut run_start = p0;
+    for i in 1..=STEPS {
+        let point = Graph::cubic_bezier(p0, p1, p2, p3, i as f32 / STEPS as f32);
+        remaining -= prev.distance(point);
+        if remaining <= 0.0 || i == STEPS {
+            if drawing {
+                let run_end = point;
+                let path = Path::new(|builder| {
+                    builder.move_to(run_start);
+                    builder.line_to(run_end);
+                });
+                frame.stroke(
+                    &path,
+                    Stroke::default()
+                        .with_color(color)
+                        .with_width(width)
+                        .with_line_cap(canvas::LineCap::Round),
+                );
+            }
+            drawing = !drawing;
+            remaining = if drawing { dash } else { gap };
+            run_start = point;
+        }
+        prev = point;
+    }
+}
+
+/// Bright dot at position t along a link cable, showing data flowing
+fn draw_link_flow_dot(frame: &mut Frame, start: Point, end: Point, port_type: PortType, t: f32) {
+    let (ctrl1, ctrl2) = link_control_points(start, end);
+    let pos = Graph::cubic_bezier(start, ctrl1, ctrl2, end, t);
+    let glow = match port_type {
+        PortType::Audio => palette::PORT_AUDIO_GLOW,
+        PortType::Midi => palette::PORT_MIDI_GLOW,
+        PortType::Video => palette::PORT_VIDEO_GLOW,
+    };
+    frame.fill(&Path::circle(pos, 5.0), glow);
+    frame.fill(&Path::circle(pos, 2.5), Color::from_rgba(1.0, 1.0, 1.0, 0.9));
+}
+
+/// Breathing overlay for a link that hasn't finished negotiating
+fn draw_link_pulse(frame: &mut Frame, start: Point, end: Point, port_type: PortType, phase: f32) {
+    let (ctrl1, ctrl2) = link_control_points(start, end);
+    let path = Path::new(|builder| {
+        builder.move_to(start);
+        builder.bezier_curve_to(ctrl1, ctrl2, end);
+    });
+    let color = match port_type {
+        PortType::Audio => palette::PORT_AUDIO,
+        PortType::Midi => palette::PORT_MIDI,
+        PortType::Video => palette::PORT_VIDEO,
+    };
+    let alpha = 0.15 + 0.25 * (0.5 - 0.5 * (phase * std::f32::consts::TAU).cos());
+    frame.stroke(
+        &path,
+        Stroke::default()
+            .with_color(Color { a: alpha, ..color })
+            .with_width(4.0)
+            .with_line_cap(canvas::LineCap::Round),
+    );
+}
+
+/// Wide translucent stroke drawn under a link to make it stand out
+fn draw_link_highlight(frame: &mut Frame, start: Point, end: Point, color: Color) {
+    let (ctrl1, ctrl2) = link_control_points(start, end);
+    let path = Path::new(|builder| {
+        builder.move_to(start);
+        builder.bezier_curve_to(ctrl1, ctrl2, end);
+    });
+    frame.stroke(
+        &path,
+        Stroke::default()
+            .with_color(Color { a: 0.45, ..color })
+            .with_width(7.0)
+            .with_line_cap(canvas::LineCap::Round),
+    );
+}
+
+const DELTA_PANEL_WIDTH: f32 = 220.0;
+const DELTA_PANEL_MARGIN: f32 = 12.0;
+
+const HISTORY_PANEL_WIDTH: f32 = 300.0;
+const HISTORY_PANEL_MARGIN: f32 = 12.0;
+/// Below the preset slot badge
+const HISTORY_PANEL_TOP: f32 = 48.0;
+const HISTORY_ROW_HEIGHT: f32 = 20.0;
+/// Title area above the first row
+const HISTORY_HEADER_HEIGHT: f32 = 36.0;
+/// Gap holding the line between undo and redo entries
+const HISTORY_SEPARATOR_HEIGHT: f32 = 9.0;
+
+/// How many undo and redo rows fit on screen, undo entries first
+fn history_visible_rows(size: Size, undo_len: usize, redo_len: usize) -> (usize, usize) {
+    let available = size.height - HISTORY_PANEL_TOP - HISTORY_HEADER_HEIGHT - HISTORY_SEPARATOR_HEIGHT - HISTORY_PANEL_MARGIN * 2.0;
+    let max_rows = (available / HISTORY_ROW_HEIGHT).max(1.0) as usize;
+    let undo_rows = undo_len.min(max_rows);
+    (undo_rows, redo_len.min(max_rows - undo_rows))
+}
+
+fn history_panel_rect(size: Size, undo_len: usize, redo_len: usize) -> Rectangle {
+    let (undo_rows, redo_rows) = history_visible_rows(size, undo_len, redo_len);
+    let rows = (undo_rows + redo_rows).max(1);
+    let height = HISTORY_HEADER_HEIGHT + rows as f32 * HISTORY_ROW_HEIGHT + HISTORY_SEPARATOR_HEIGHT + HISTORY_PANEL_MARGIN;
+    Rectangle::new(
+        Point::new(HISTORY_PANEL_MARGIN, HISTORY_PANEL_TOP),
+        Size::new(HISTORY_PANEL_WIDTH, height),
+    )
+}
+
+/// Undo row under `point`, counted from the most recent action
+fn history_undo_row_at(size: Size, undo_len: usize, point: Point) -> Option<usize> {
+    let (undo_rows, _) = history_visible_rows(size, undo_len, 0);
+    let top = HISTORY_PANEL_TOP + HISTORY_HEADER_HEIGHT;
+    if point.y < top || point.x < HISTORY_PANEL_MARGIN || point.x > HISTORY_PANEL_MARGIN + HISTORY_PANEL_WIDTH {
+        return None;
+    }
+    let row = ((point.y - top) / HISTORY_ROW_HEIGHT) as usize;
+    (row < undo_rows).then_some(row)
+}
+
+fn draw_history_panel(frame: &mut Frame, size: Size, undo: &VecDeque<UndoEntry>, redo: &[UndoEntry]) {
+    let rect = history_panel_rect(size, undo.len(), redo.len());
+    let (undo_rows, redo_rows) = history_visible_rows(size, undo.len(), redo.len());
+
+    draw_rounded_rect(frame, rect.position(), rect.size(), 8.0, Color::from_rgba(0.08, 0.08, 0.10, 0.92));
+    stroke_rounded_rect(frame, rect.position(), rect.size(), 8.0, palette::NODE_BORDER, 1.0);
+
+    frame.fill_text(Text {
+        content: "History".to_string(),
+        position: Point::new(rect.x + 12.0, rect.y + 12.0),
+        color: palette::TEXT_PRIMARY,
+        size: iced::Pixels(13.0),
+        ..Text::default()
+    });
+
+    // Most recent first: the top undo row is what Ctrl+Z reverts
+    let max_chars = ((HISTORY_PANEL_WIDTH - 24.0) / 6.5) as usize;
+    let fit = |text: &str| if text.chars().count() > max_chars {
+        format!("{}…", text.chars().take(max_chars.saturating_sub(1)).collect::<String>())
+    } else {
+        text.to_string()
+    };
+    let mut y = rect.y + HISTORY_HEADER_HEIGHT;
+    for entry in undo.iter().rev().take(undo_rows) {
+        frame.fill_text(Text {
+            content: fit(&entry.description),
+            position: Point::new(rect.x + 12.0, y + 4.0),
+            color: palette::TEXT_PRIMARY,
+            size: iced::Pixels(11.0),
+            ..Text::default()
+        });
+        y += HISTORY_ROW_HEIGHT;
+    }
+    if undo.is_empty() && redo.is_empty() {
+        frame.fill_text(Text {
+            content: "Nothing to undo".to_string(),
+            position: Point::new(rect.x + 12.0, y + 4.0),
+            color: palette::TEXT_SECONDARY,
+            size: iced::Pixels(11.0),
+            ..Text::default()
+        });
+    }
+
+    // Redo entries below a divider, dimmed
+    let line_y = y + HISTORY_SEPARATOR_HEIGHT / 2.0;
+    frame.stroke(
+        &Path::line(Point::new(rect.x + 12.0, line_y), Point::new(rect.x + rect.width - 12.0, line_y)),
+        Stroke::default().with_color(palette::NODE_BORDER).with_width(1.0),
+    );
+    y += HISTORY_SEPARATOR_HEIGHT;
+    for entry in redo.iter().rev().take(redo_rows) {
+        frame.fill_text(Text {
+            content: fit(&entry.description),
+            position: Point::new(rect.x + 12.0, y + 4.0),
+            color: Color { a: 0.4, ..palette::TEXT_PRIMARY },
+            size: iced::Pixels(11.0),
+            ..Text::default()
+        });
+        y += HISTORY_ROW_HEIGHT;
+    }
+}
+
+fn preset_delta_button_rect(size: Size) -> Rectangle {
+    let x = size.width - DELTA_PANEL_WIDTH - DELTA_PANEL_MARGIN + 10.0;
+    let y = DELTA_PANEL_MARGIN + 66.0;
+    Rectangle::new(Point::new(x, y), Size::new(DELTA_PANEL_WIDTH - 20.0, 26.0))
+}
+
+fn draw_preset_delta_panel(frame: &mut Frame, size: Size, delta: &PresetDelta, can_save: bool, cursor: Option<Point>) {
+    let box_x = size.width - DELTA_PANEL_WIDTH - DELTA_PANEL_MARGIN;
+    let box_y = DELTA_PANEL_MARGIN;
+    let box_h = if can_save { 102.0 } else { 70.0 };
+
+    draw_rounded_rect(
+        frame,
+        Point::new(box_x, box_y),
+        Size::new(DELTA_PANEL_WIDTH, box_h),
+        6.0,
+        Color::from_rgba(0.08, 0.08, 0.10, 0.92),
+    );
+    stroke_rounded_rect(frame, Point::new(box_x, box_y), Size::new(DELTA_PANEL_WIDTH, box_h), 6.0, palette::NODE_BORDER, 1.0);
+
+    frame.fill_text(Text {
+        content: "Changes since preset".to_string(),
+        position: Point::new(box_x + 10.0, box_y + 10.0),
+        color: palette::TEXT_PRIMARY,
+        size: iced::Pixels(12.0),
+        ..Text::default()
+    });
+    frame.fill_text(Text {
+        content: format!("+{} added", delta.added.len()),
+        position: Point::new(box_x + 10.0, box_y + 30.0),
+        color: palette::ACCENT_INPUT,
+        size: iced::Pixels(11.0),
+        ..Text::default()
+    });
+    frame.fill_text(Text {
+        content: format!("−{} missing", delta.missing.len()),
+        position: Point::new(box_x + 80.0, box_y + 30.0),
+        color: palette::TEXT_SECONDARY,
+        size: iced::Pixels(11.0),
+        ..Text::default()
+    });
+    if delta.unresolved > 0 {
+        frame.fill_text(Text {
+            content: format!("{} not present", delta.unresolved),
+            position: Point::new(box_x + 10.0, box_y + 46.0),
+            color: palette::TEXT_SECONDARY,
+            size: iced::Pixels(10.0),
+            ..Text::default()
+        });
+    }
+
+    if can_save {
+        let button = preset_delta_button_rect(size);
+        let hovered = cursor.is_some_and(|c| button.contains(c));
+        let bg = if hovered { palette::NODE_BORDER_HIGHLIGHT } else { palette::NODE_HEADER };
+        draw_rounded_rect(frame, button.position(), button.size(), 4.0, bg);
+        frame.fill_text(Text {
+            content: "Save as new version".to_string(),
+            position: Point::new(button.x + 10.0, button.y + 6.0),
+            color: palette::TEXT_PRIMARY,
+            size: iced::Pixels(11.0),
+            ..Text::default()
+        });
+    }
+}
+
+const PREVIEW_BAR_WIDTH: f32 = 420.0;
+const PREVIEW_BAR_HEIGHT: f32 = 38.0;
+
+/// Apply and Cancel buttons on the preset preview bar
+fn preset_preview_buttons(size: Size) -> (Rectangle, Rectangle) {
+    let x = (size.width + PREVIEW_BAR_WIDTH) / 2.0;
+    let button = Size::new(62.0, 24.0);
+    (
+        Rectangle::new(Point::new(x - 2.0 * (button.width + 8.0), 19.0), button),
+        Rectangle::new(Point::new(x - (button.width + 8.0), 19.0), button),
+    )
+}
+
+fn draw_preset_preview_bar(frame: &mut Frame, size: Size, preview: &PresetPreview, cursor: Option<Point>) {
+    let x = (size.width - PREVIEW_BAR_WIDTH) / 2.0;
+    let y = 12.0;
+    let bar = Size::new(PREVIEW_BAR_WIDTH, PREVIEW_BAR_HEIGHT);
+    draw_rounded_rect(frame, Point::new(x, y), bar, 8.0, Color::from_rgb(0.12, 0.12, 0.14));
+    stroke_rounded_rect(frame, Point::new(x, y), bar, 8.0, palette::NODE_BORDER_HIGHLIGHT, 1.5);
+
+    let name: String = preview.preset.name.chars().take(22).collect();
+    frame.fill_text(Text {
+        content: format!("Preview: {}", name),
+        position: Point::new(x + 14.0, y + 12.0),
+        color: palette::TEXT_PRIMARY,
+        size: iced::Pixels(12.0),
+        ..Text::default()
+    });
+    frame.fill_text(Text {
+        content: format!("+{}  −{}", preview.adds.len(), preview.removes.len()),
+        position: Point::new(x + 200.0, y + 12.0),
+        color: palette::TEXT_SECONDARY,
+        size: iced::Pixels(11.0),
+        ..Text::default()
+    });
+
+    let (apply, cancel) = preset_preview_buttons(size);
+    for (rect, label) in [(apply, "Apply"), (cancel, "Cancel")] {
+        let hovered = cursor.is_some_and(|c| rect.contains(c));
+        let bg = if hovered { palette::NODE_BORDER_HIGHLIGHT } else { palette::NODE_HEADER };
+        draw_rounded_rect(frame, rect.position(), rect.size(), 4.0, bg);
+        frame.fill_text(Text {
+            content: label.to_string(),
+            position: Point::new(rect.x + 12.0, rect.y + 6.0),
+            color: palette::TEXT_PRIMARY,
+            size: iced::Pixels(11.0),
+            ..Text::default()
+        });
+    }
+}
+
+fn draw_stereo_prompt(frame: &mut Frame, size: Size, seconds_left: u32) {
+    let box_w = 300.0;
+    let box_h = 36.0;
+    let x = (size.width - box_w) / 2.0;
+    let y = size.height - box_h - 24.0;
+
+    draw_rounded_rect(frame, Point::new(x, y), Size::new(box_w, box_h), 8.0, Color::from_rgb(0.12, 0.12, 0.14));
+    stroke_rounded_rect(frame, Point::new(x, y), Size::new(box_w, box_h), 8.0, palette::PORT_AUDIO, 1.5);
+
+    frame.fill_text(Text {
+        content: "Also connect to right channel? [Y/N]".to_string(),
+        position: Point::new(x + 14.0, y + 11.0),
+        color: palette::TEXT_PRIMARY,
+        size: iced::Pixels(12.0),
+        ..Text::default()
+    });
+    frame.fill_text(Text {
+        content: format!("{}s", seconds_left),
+        position: Point::new(x + box_w - 30.0, y + 12.0),
+        color: palette::TEXT_SECONDARY,
+        size: iced::Pixels(11.0),
+        ..Text::default()
+    });
+}
+
+fn draw_disconnected_banner(frame: &mut Frame, size: Size) {
+    let box_w = 340.0;
+    let box_h = 34.0;
+    let x = (size.width - box_w) / 2.0;
+    let y = 12.0;
+
+    draw_rounded_rect(frame, Point::new(x, y), Size::new(box_w, box_h), 8.0, Color::from_rgb(0.12, 0.12, 0.14));
+    stroke_rounded_rect(frame, Point::new(x, y), Size::new(box_w, box_h), 8.0, palette::LINK_ERROR, 1.5);
+    frame.fill_text(Text {
+        content: "PipeWire connection lost — reconnecting…".to_string(),
+        position: Point::new(x + 14.0, y + 10.0),
+        color: palette::TEXT_PRIMARY,
+        size: iced::Pixels(12.0),
+        ..Text::default()
+    });
+}
+
+fn draw_undo_status(frame: &mut Frame, size: Size, description: &str) {
+    let label = format!("Undo: {}", description);
+    let box_w = (label.chars().count() as f32 * 6.0 + 24.0).min(size.width - 24.0);
+    let box_h = 24.0;
+    let origin = Point::new((size.width - box_w) / 2.0, size.height - box_h - 12.0);
+
+    draw_rounded_rect(frame, origin, Size::new(box_w, box_h), 6.0, Color::from_rgba(0.08, 0.08, 0.10, 0.85));
+    frame.fill_text(Text {
+        content: label,
+        position: Point::new(origin.x + 12.0, origin.y + 6.0),
+        color: palette::TEXT_SECONDARY,
+        size: iced::Pixels(11.0),
+        ..Text::default()
+    });
+}
+
+fn draw_node_tooltip(frame: &mut Frame, size: Size, cursor: Point, node: &Node, client: Option<&ClientInfo>) {
+    let none = || "—".to_string();
+    let process = client.map(|c| {
+        let exe = if c.exe.is_empty() { &c.name } else { &c.exe };
+        if c.pid == 0 { exe.clone() } else { format!("{} (PID {})", exe, c.pid) }
+    });
+    let rows = [
+        ("ID", node.id.to_string()),
+        ("Name", node.name.clone()),
+        ("App", node.app_name.clone().unwrap_or_else(none)),
+        ("Serial", node.serial.clone().unwrap_or_else(none)),
+        ("Path", node.object_path.clone().unwrap_or_else(none)),
+        ("Index", node.index.to_string()),
+        ("Ports", format!("{} in / {} out", node.input_ports.len(), node.output_ports.len())),
+        ("Process", process.unwrap_or_else(none)),
+    ];
+
+    let line_height = 16.0;
+    let padding = 10.0;
+    let label_width = 50.0;
+    let max_value = rows.iter().map(|(_, v)| v.chars().count()).max().unwrap_or(0);
+    let box_w = (label_width + max_value as f32 * 6.5 + padding * 2.0).clamp(160.0, 380.0);
+    let box_h = rows.len() as f32 * line_height + padding * 2.0;
+
+    // Prefer below-right of the cursor, flipping to stay inside the canvas
+    let mut x = cursor.x + 16.0;
+    if x + box_w > size.width - 8.0 {
+        x = cursor.x - box_w - 16.0;
+    }
+    let mut y = cursor.y + 16.0;
+    if y + box_h > size.height - 8.0 {
+        y = cursor.y - box_h - 16.0;
+    }
+    let x = x.max(8.0);
+    let y = y.max(8.0);
+
+    draw_rounded_rect(frame, Point::new(x, y), Size::new(box_w, box_h), 6.0, Color::from_rgba(0.08, 0.08, 0.10, 0.95));
+    stroke_rounded_rect(frame, Point::new(x, y), Size::new(box_w, box_h), 6.0, palette::NODE_BORDER_HIGHLIGHT, 1.0);
+
+    let max_chars = ((box_w - label_width - padding * 2.0) / 6.5) as usize;
+    for (i, (label, value)) in rows.iter().enumerate() {
+        let row_y = y + padding + i as f32 * line_height;
+        frame.fill_text(Text {
+            content: label.to_string(),
+            position: Point::new(x + padding, row_y),
+            color: palette::TEXT_SECONDARY,
+            size: iced::Pixels(11.0),
+            ..Text::default()
+        });
+        let value = if value.chars().count() > max_chars {
+            let truncated: String = value.chars().take(max_chars.saturating_sub(1)).collect();
+            format!("{}…", truncated)
+        } else {
+            value.clone()
+        };
+        frame.fill_text(Text {
+            content: value,
+            position: Point::new(x + padding + label_width, row_y),
+            color: palette::TEXT_PRIMARY,
+            size: iced::Pixels(11.0),
+            ..Text::default()
+        });
+    }
+}
+
+/// Pill-shaped tooltip drawn above a port circle (anchor is the port center in screen space)
+fn draw_port_tooltip(frame: &mut Frame, size: Size, anchor: Point, port: &Port) {
+    let direction = match port.direction {
+        PortDirection::Input => "Input",
+        PortDirection::Output => "Output",
+    };
+    let port_type = match port.port_type {
+        PortType::Audio => "Audio",
+        PortType::Midi => "MIDI",
+        PortType::Video => "Video",
+    };
+    let mut text = format!("{}  ·  {}  ·  {}", port.name, direction, port_type);
+    // e.g. "PCM 48000Hz 2ch" or "MIDI"
+    if let Some(format) = &port.format {
+        text.push_str("  ·  ");
+        text.push_str(format);
+        if let Some(rate) = port.sample_rate {
+            text.push_str(&format!(" {}Hz", rate));
+        }
+        if let Some(channels) = port.channels {
+            text.push_str(&format!(" {}ch", channels));
+        }
+    }
+
+    let pill_h = 22.0;
+    let pill_w = text.chars().count() as f32 * 6.2 + 20.0;
+    let x = (anchor.x - pill_w / 2.0).clamp(4.0, (size.width - pill_w - 4.0).max(4.0));
+    let y = (anchor.y - PORT_RADIUS - pill_h - 8.0).max(4.0);
+
+    draw_rounded_rect(frame, Point::new(x, y), Size::new(pill_w, pill_h), pill_h / 2.0, Color::from_rgba(0.08, 0.08, 0.10, 0.95));
+    let accent = match port.port_type {
+        PortType::Audio => palette::PORT_AUDIO,
+        PortType::Midi => palette::PORT_MIDI,
+        PortType::Video => palette::PORT_VIDEO,
+    };
+    stroke_rounded_rect(frame, Point::new(x, y), Size::new(pill_w, pill_h), pill_h / 2.0, accent, 1.0);
+    frame.fill_text(Text {
+        content: text,
+        position: Point::new(x + 10.0, y + 5.0),
+        color: palette::TEXT_PRIMARY,
+        size: iced::Pixels(11.0),
+        ..Text::default()
+    });
+}
+
+fn draw_link_error_tooltip(frame: &mut Frame, size: Size, cursor: Point, message: &str) {
+    let text = format!("Link failed: {}", message);
+    let box_w = (text.len() as f32 * 6.5 + 20.0).min(420.0);
+    let box_h = 26.0;
+    let x = (cursor.x + 14.0).min(size.width - box_w - 8.0).max(8.0);
+    let y = (cursor.y + 18.0).min(size.height - box_h - 8.0).max(8.0);
+
+    draw_rounded_rect(frame, Point::new(x, y), Size::new(box_w, box_h), 6.0, palette::NODE_HEADER);
+    stroke_rounded_rect(frame, Point::new(x, y), Size::new(box_w, box_h), 6.0, palette::LINK_ERROR, 1.0);
+    frame.fill_text(Text {
+        content: text,
+        position: Point::new(x + 10.0, y + 6.0),
+        color: palette::TEXT_PRIMARY,
+        size: iced::Pixels(11.0),
+        ..Text::default()
+    });
+}
+
+const CONTEXT_MENU_MIN_WIDTH: f32 = 160.0;
+const CONTEXT_MENU_ITEM_HEIGHT: f32 = 26.0;
+const CONTEXT_MENU_PADDING: f32 = 4.0;
+
+fn context_menu_width(menu: &ContextMenu) -> f32 {
+    // ~6.5px per char at 11.5px font, plus padding
+    let longest = menu.items.iter().map(|(label, _)| label.chars().count()).max().unwrap_or(0);
+    (longest as f32 * 6.5 + 24.0).clamp(CONTEXT_MENU_MIN_WIDTH, 360.0)
+}
+
+fn context_menu_item_rect(menu: &ContextMenu, index: usize) -> Rectangle {
+    Rectangle::new(
+        Point::new(
+            menu.position.x,
+            menu.position.y + CONTEXT_MENU_PADDING + index as f32 * CONTEXT_MENU_ITEM_HEIGHT,
+        ),
+        Size::new(context_menu_width(menu), CONTEXT_MENU_ITEM_HEIGHT),
+    )
+}
+
+fn context_menu_item_at(menu: &ContextMenu, cursor: Point) -> Option<usize> {
+    (0..menu.items.len()).find(|&i| context_menu_item_rect(menu, i).contains(cursor))
+}
+
+fn draw_context_menu(frame: &mut Frame, menu: &ContextMenu, cursor: Option<Point>) {
+    let box_h = menu.items.len() as f32 * CONTEXT_MENU_ITEM_HEIGHT + CONTEXT_MENU_PADDING * 2.0;
+    let size = Size::new(context_menu_width(menu), box_h);
+    let hovered = cursor.and_then(|c| context_menu_item_at(menu, c));
+
+    draw_rounded_rect(frame, menu.position, size, 6.0, palette::NODE_BG);
+    stroke_rounded_rect(frame, menu.position, size, 6.0, Color::from_rgb(0.25, 0.25, 0.30), 1.0);
+
+    for (i, (label, _)) in menu.items.iter().enumerate() {
+        let rect = context_menu_item_rect(menu, i);
+        if hovered == Some(i) {
+            draw_rounded_rect(
+                frame,
+                Point::new(rect.x + 4.0, rect.y + 1.0),
+                Size::new(rect.width - 8.0, rect.height - 2.0),
+                4.0,
+                Color::from_rgba(1.0, 1.0, 1.0, 0.07),
+            );
+        }
+        frame.fill_text(Text {
+            content: label.clone(),
+            position: Point::new(rect.x + 12.0, rect.y + 7.0),
+            color: if hovered == Some(i) { Color::WHITE } else { palette::TEXT_PRIMARY },
+            size: iced::Pixels(11.5),
+            ..Text::default()
+        });
+    }
+}
+
 const PICKER_ITEM_HEIGHT: f32 = 32.0;
 const PICKER_HEADER_HEIGHT: f32 = 36.0;
 const PICKER_PADDING: f32 = 6.0;
@@ -2433,6 +7453,52 @@ fn picker_screen_rect(picker: &ProfilePickerState, pan_offset: Vector, zoom: f32
     (screen_x, screen_y, box_w, total_height)
 }
 
+fn draw_minimap(frame: &mut Frame, graph: &Graph, size: Size, screen: Rectangle, world: Rectangle, scale: f32) {
+    draw_rounded_rect(frame, screen.position(), screen.size(), 6.0, Color::from_rgba(0.08, 0.08, 0.10, 0.85));
+    stroke_rounded_rect(frame, screen.position(), screen.size(), 6.0, palette::NODE_BORDER, 1.0);
+
+    // World → minimap, with the content centered in the box
+    let offset_x = (MINIMAP_SIZE.width - world.width * scale) / 2.0;
+    let offset_y = (MINIMAP_SIZE.height - world.height * scale) / 2.0;
+    let to_map = |p: Point| Point::new(
+        screen.x + offset_x + (p.x - world.x) * scale,
+        screen.y + offset_y + (p.y - world.y) * scale,
+    );
+
+    for node in graph.nodes.values() {
+        let size = Size::new(NODE_WIDTH * scale, graph.visible_node_height(node) * scale);
+        frame.fill_rectangle(to_map(node.position), size, palette::NODE_HEADER);
+    }
+
+    // Current viewport, clipped to the minimap box
+    let view_top_left = to_map(Point::new(-graph.pan_offset.x / graph.zoom, -graph.pan_offset.y / graph.zoom));
+    let view = Rectangle::new(
+        view_top_left,
+        Size::new(size.width / graph.zoom * scale, size.height / graph.zoom * scale),
+    );
+    if let Some(visible) = view.intersection(&screen) {
+        frame.stroke(
+            &Path::rectangle(visible.position(), visible.size()),
+            Stroke::default().with_color(Color::from_rgba(1.0, 1.0, 1.0, 0.6)).with_width(1.0),
+        );
+    }
+}
+
+fn draw_preset_slot(frame: &mut Frame, slot: usize, name: &str) {
+    let label = format!("{}  {}", slot, name);
+    let box_w = label.chars().count() as f32 * 6.5 + 24.0;
+    let origin = Point::new(12.0, 12.0);
+
+    draw_rounded_rect(frame, origin, Size::new(box_w, 26.0), 6.0, Color::from_rgba(0.08, 0.08, 0.10, 0.85));
+    frame.fill_text(Text {
+        content: label,
+        position: Point::new(origin.x + 12.0, origin.y + 7.0),
+        color: palette::TEXT_SECONDARY,
+        size: iced::Pixels(11.0),
+        ..Text::default()
+    });
+}
+
 fn draw_legend(frame: &mut Frame, size: Size) {
     let categories: &[(Color, &str)] = &[
         (palette::CAT_AUDIO_SOURCE, "Audio Source"),
@@ -2625,3 +7691,108 @@ fn hit_test_profile_picker(cursor: Point, picker: &ProfilePickerState, pan_offse
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_redo_round_trip() {
+        let history = vec![
+            UndoAction::Move { node_id: 7, from: Point::new(0.0, 0.0), to: Point::new(40.0, 80.0) },
+            UndoAction::Connect { output_port: 11, input_port: 12 },
+            UndoAction::MoveGroup {
+                moves: vec![(7, Point::new(40.0, 80.0), Point::new(60.0, 80.0)), (8, Point::ORIGIN, Point::new(20.0, 0.0))],
+            },
+        ];
+
+        // Undo everything, newest first, as the undo stack would
+        let redo: Vec<UndoAction> = history.iter().rev().cloned().map(UndoAction::inverse).collect();
+        assert_eq!(redo[1], UndoAction::Disconnect { output_port: 11, input_port: 12 });
+        assert_eq!(
+            redo[2],
+            UndoAction::Move { node_id: 7, from: Point::new(40.0, 80.0), to: Point::new(0.0, 0.0) },
+        );
+        let UndoAction::MoveGroup { moves } = &redo[0] else {
+            panic!("a group move should invert to a group move");
+        };
+        assert_eq!(moves[1], (8, Point::new(20.0, 0.0), Point::ORIGIN));
+
+        // Redoing in turn restores the original entries
+        let redone: Vec<UndoAction> = redo.into_iter().rev().map(UndoAction::inverse).collect();
+        assert_eq!(redone, history);
+    }
+
+    #[test]
+    fn test_binding_matches() {
+        use iced::keyboard::{key::Named, Key, Modifiers};
+        let binding = |key: &str, ctrl, shift| KeyBinding { key: key.to_string(), ctrl, shift, alt: false };
+        let undo = binding("z", true, false);
+        let redo = binding("z", true, true);
+
+        // Modifiers have to match exactly, and letters ignore case
+        assert!(binding_matches(&undo, &Key::Character("z"), Modifiers::CTRL));
+        assert!(binding_matches(&redo, &Key::Character("Z"), Modifiers::CTRL | Modifiers::SHIFT));
+        assert!(!binding_matches(&undo, &Key::Character("z"), Modifiers::CTRL | Modifiers::SHIFT));
+        assert!(!binding_matches(&undo, &Key::Character("z"), Modifiers::empty()));
+        assert!(!binding_matches(&undo, &Key::Character("z"), Modifiers::CTRL | Modifiers::ALT));
+
+        // Shift is part of typing a symbol, so it isn't compared
+        let help = binding("?", false, false);
+        assert!(binding_matches(&help, &Key::Character("?"), Modifiers::SHIFT));
+        assert!(binding_matches(&binding("F1", false, false), &Key::Named(Named::F1), Modifiers::empty()));
+
+        // Rebinding undo to Ctrl+/ frees Ctrl+Z
+        let rebound = binding("/", true, false);
+        assert!(binding_matches(&rebound, &Key::Character("/"), Modifiers::CTRL));
+        assert!(!binding_matches(&rebound, &Key::Character("z"), Modifiers::CTRL));
+        assert!(!binding_matches(&rebound, &Key::Character("/"), Modifiers::empty()));
+    }
+
+    fn rect(x: f32, y: f32, width: f32, height: f32) -> Rectangle {
+        Rectangle::new(Point::new(x, y), Size::new(width, height))
+    }
+
+    /// A tree split a few levels deep by filler in its top-left corner
+    fn split_quadtree() -> crate::quadtree::QuadTree<u32> {
+        let mut tree = crate::quadtree::QuadTree::new(rect(0.0, 0.0, 1000.0, 1000.0));
+        for i in 0..32 {
+            let offset = (i % 8) as f32 * 10.0;
+            tree.insert(rect(offset, offset, 5.0, 5.0), 1000 + i);
+        }
+        tree
+    }
+
+    #[test]
+    fn test_quadtree_straddling_rects() {
+        let mut tree = split_quadtree();
+        // Spans the centre, so it stays in the root's own items
+        tree.insert(rect(450.0, 450.0, 100.0, 100.0), 1);
+        // Crosses the vertical split of the top-left quadrant only
+        tree.insert(rect(240.0, 100.0, 20.0, 20.0), 2);
+
+        for point in [Point::new(460.0, 460.0), Point::new(540.0, 460.0), Point::new(460.0, 540.0), Point::new(540.0, 540.0)] {
+            assert_eq!(tree.query_point(point), vec![1], "at {:?}", point);
+        }
+        assert_eq!(tree.query_point(Point::new(245.0, 110.0)), vec![2]);
+        assert_eq!(tree.query_point(Point::new(255.0, 110.0)), vec![2]);
+        assert!(tree.query_point(Point::new(300.0, 300.0)).is_empty());
+    }
+
+    #[test]
+    fn test_quadtree_rect_boundaries() {
+        let mut tree = split_quadtree();
+        tree.insert(rect(600.0, 600.0, 50.0, 50.0), 1);
+        // Ends exactly on the root's centre line, so it sits in the top-left child
+        tree.insert(rect(400.0, 100.0, 100.0, 50.0), 2);
+
+        // Rectangles are half-open: the top-left edge is in, the far edge out
+        assert_eq!(tree.query_point(Point::new(600.0, 600.0)), vec![1]);
+        assert_eq!(tree.query_point(Point::new(649.9, 649.9)), vec![1]);
+        assert!(tree.query_point(Point::new(650.0, 620.0)).is_empty());
+        assert_eq!(tree.query_point(Point::new(499.9, 125.0)), vec![2]);
+        assert!(tree.query_point(Point::new(500.0, 125.0)).is_empty());
+        // Outside the tree's bounds there is nothing to find
+        assert!(tree.query_point(Point::new(-1.0, 10.0)).is_empty());
+    }
+}
@@ -51,7 +51,7 @@ pub fn app_icon() -> Option<Icon> {
             blend(&mut r, &mut g, &mut b, 0.92, 0.66, 0.25, glow);
 
             // Connection trace: bezier from (9,16) through (16,10) to (23,16)
-            let trace_d = bezier_dist(fx, fy, 9.0, 16.0, 16.0, 10.0, 23.0, 16.0);
+            let trace_d = bezier_dist((fx, fy), (9.0, 16.0), (16.0, 10.0), (23.0, 16.0));
             let trace = smoothstep(2.0, 1.0, trace_d);
             // Gradient color along x: green -> cyan -> amber
             let t = ((fx - 9.0) / 14.0).clamp(0.0, 1.0);
@@ -117,10 +117,10 @@ fn blend(r: &mut f32, g: &mut f32, b: &mut f32, sr: f32, sg: f32, sb: f32, a: f3
 /// Approximate minimum distance from point (px,py) to a quadratic bezier
 /// defined by control points (x0,y0), (x1,y1), (x2,y2).
 fn bezier_dist(
-    px: f32, py: f32,
-    x0: f32, y0: f32,
-    x1: f32, y1: f32,
-    x2: f32, y2: f32,
+    (px, py): (f32, f32),
+    (x0, y0): (f32, f32),
+    (x1, y1): (f32, f32),
+    (x2, y2): (f32, f32),
 ) -> f32 {
     let steps = 16;
     let mut min_d = f32::MAX;
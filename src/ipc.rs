@@ -0,0 +1,101 @@
+use iced::futures::channel::mpsc;
+use iced::futures::StreamExt;
+use iced::Subscription;
+use serde::Deserialize;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+
+use crate::graph::GraphMessage;
+use crate::Message;
+
+/// One line of JSON sent to the socket
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum Command {
+    LoadPreset { path: PathBuf },
+    ApplySlot { slot: usize },
+    DisconnectAll,
+}
+
+impl Command {
+    fn into_message(self) -> Message {
+        Message::Graph(match self {
+            Command::LoadPreset { path } => GraphMessage::ApplyPreset { path },
+            Command::ApplySlot { slot } => GraphMessage::LoadPresetSlot(slot),
+            Command::DisconnectAll => GraphMessage::DisconnectAll,
+        })
+    }
+}
+
+/// `$XDG_RUNTIME_DIR/solder.sock`
+fn socket_path() -> Option<PathBuf> {
+    std::env::var_os("XDG_RUNTIME_DIR").map(|dir| PathBuf::from(dir).join("solder.sock"))
+}
+
+/// Commands from scripts, e.g.
+/// `echo '{"action":"apply_slot","slot":1}' | socat - UNIX-CONNECT:$XDG_RUNTIME_DIR/solder.sock`
+pub fn listen() -> Subscription<Message> {
+    Subscription::run(|| {
+        iced::stream::channel(10, |mut output: mpsc::Sender<Message>| async move {
+            let (tx, mut rx) = mpsc::channel::<Message>(10);
+            std::thread::spawn(move || run_ipc_server(tx));
+
+            while let Some(message) = rx.next().await {
+                use iced::futures::SinkExt;
+                let _ = output.send(message).await;
+            }
+        })
+    })
+}
+
+/// Accept connections until the socket can't be used; each line read is
+/// answered with "ok" or "error: <reason>"
+pub fn run_ipc_server(tx: mpsc::Sender<Message>) {
+    let Some(path) = socket_path() else {
+        eprintln!("IPC disabled: XDG_RUNTIME_DIR is not set");
+        return;
+    };
+    // A socket that still accepts connections belongs to another instance
+    if UnixStream::connect(&path).is_ok() {
+        eprintln!("IPC disabled: {} is in use", path.display());
+        return;
+    }
+    let _ = std::fs::remove_file(&path);
+    let listener = match UnixListener::bind(&path) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("IPC disabled: could not bind {}: {}", path.display(), e);
+            return;
+        }
+    };
+
+    for stream in listener.incoming().flatten() {
+        let tx = tx.clone();
+        std::thread::spawn(move || handle_client(stream, tx));
+    }
+}
+
+fn handle_client(stream: UnixStream, mut tx: mpsc::Sender<Message>) {
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match serde_json::from_str::<Command>(&line) {
+            Ok(command) => match tx.try_send(command.into_message()) {
+                Ok(()) => "ok".to_string(),
+                Err(_) => "error: busy".to_string(),
+            },
+            Err(e) => format!("error: {}", e),
+        };
+        if writeln!(writer, "{}", reply).is_err() {
+            return;
+        }
+    }
+}
@@ -1,92 +1,363 @@
 mod config;
+mod export;
 mod graph;
 mod icon;
+mod ipc;
 mod layout;
 mod pipewire_client;
 mod preset;
+mod quadtree;
+mod spatial;
+mod status_bar;
+mod toast;
 
-use iced::widget::canvas;
+use iced::widget::{canvas, column, stack};
 use iced::{Element, Length, Subscription, Task, Theme};
+use std::collections::VecDeque;
+use std::io::Write;
 
 use config::Config;
 use graph::{Graph, GraphMessage};
 use pipewire_client::PipewireEvent;
+use toast::Toast;
 
 fn main() -> iced::Result {
-    let mut settings = iced::window::Settings::default();
-    settings.icon = icon::app_icon();
-    settings.platform_specific.application_id = "solder".to_string();
+    // --profile <name> selects the config; otherwise reuse the last one
+    let mut args = std::env::args().skip(1);
+    let mut profile = None;
+    let mut export = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--profile" => profile = args.next(),
+            "--export-connections" | "--export-dot" => export = Some(arg),
+            _ => {}
+        }
+    }
+    let profile = profile
+        .or_else(Config::last_profile)
+        .unwrap_or_else(|| config::DEFAULT_PROFILE.to_string());
+
+    // Print the current graph for scripts instead of opening a window
+    if let Some(export) = export {
+        std::process::exit(export_to_stdout(&profile, &export));
+    }
+
+    let settings = iced::window::Settings {
+        icon: icon::app_icon(),
+        platform_specific: iced::window::settings::PlatformSpecific {
+            application_id: "solder".to_string(),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
 
-    iced::application(init, update, view)
-        .title("Solder")
+    iced::application(move || init(&profile), update, view)
+        .title(title)
         .window(settings)
         .subscription(subscription)
         .theme(theme)
         .antialiasing(true)
+        .exit_on_close_request(false)
         .run()
 }
 
+/// Snapshot the PipeWire graph and print it as a pw-link script or DOT.
+/// Returns the process exit code.
+fn export_to_stdout(profile: &str, flag: &str) -> i32 {
+    let events = match pipewire_client::snapshot() {
+        Ok(events) => events,
+        Err(e) => {
+            eprintln!("PipeWire error: {}", e);
+            return 1;
+        }
+    };
+    // Feed the events through a graph so names resolve as they do in the app
+    let mut config = Config::load_or_default(profile);
+    let mut graph = Graph::new(&config);
+    for event in events {
+        let _ = graph.handle_pipewire_event(event, &mut config);
+    }
+    let output = if flag == "--export-dot" {
+        export::graph_to_dot(&graph.nodes, &graph.links)
+    } else {
+        export::live_connections_to_script(&graph.nodes, &graph.links)
+    };
+    // A closed pipe (`solder --export-dot | head`) is a normal way to stop
+    let mut stdout = std::io::stdout().lock();
+    match write!(stdout, "{}", output).and_then(|()| stdout.flush()) {
+        Ok(()) => 0,
+        Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => 0,
+        Err(e) => {
+            eprintln!("Could not write export: {}", e);
+            1
+        }
+    }
+}
+
 fn theme(_state: &Solder) -> Theme {
     Theme::Dark
 }
 
-fn init() -> (Solder, Task<Message>) {
-    let config = Config::load().unwrap_or_default();
+fn title(state: &Solder) -> String {
+    if state.config.profile == config::DEFAULT_PROFILE {
+        "Solder".to_string()
+    } else {
+        format!("Solder [{}]", state.config.profile)
+    }
+}
+
+fn init(profile: &str) -> (Solder, Task<Message>) {
+    let config = Config::load_or_default(profile);
+    let _ = Config::set_last_profile(profile);
     let graph = Graph::new(&config);
-    (Solder { graph, config }, Task::none())
+    (Solder { graph, config, notifications: VecDeque::new(), save_failed: false }, Task::none())
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     Graph(GraphMessage),
     Pipewire(PipewireEvent),
+    SaveConfig,
+    ConfigReloaded(Box<Config>),
+    ShowNotification(Toast),
+    /// The window is closing; write out pending config changes first
+    CloseRequested(iced::window::Id),
 }
 
 struct Solder {
     graph: Graph,
     config: Config,
+    notifications: VecDeque<Toast>,
+    /// The last debounced save failed; retry less often and don't toast again
+    save_failed: bool,
 }
 
 fn update(state: &mut Solder, message: Message) -> Task<Message> {
     match message {
         Message::Graph(msg) => {
+            if matches!(msg, GraphMessage::AnimationTick) {
+                toast::remove_expired(&mut state.notifications);
+            }
             return state.graph.update(msg, &mut state.config);
         }
+        Message::ShowNotification(toast) => {
+            toast::push(&mut state.notifications, toast);
+        }
         Message::Pipewire(event) => {
-            state.graph.handle_pipewire_event(event, &mut state.config);
+            return state.graph.handle_pipewire_event(event, &mut state.config);
+        }
+        Message::ConfigReloaded(config) => {
+            // The watcher also sees our own saves; those change nothing
+            if config.file_modified.is_some() && config.file_modified == state.config.file_modified {
+                return Task::none();
+            }
+            // Unsaved in-app changes win over the file
+            let previous = state.config.clone();
+            if state.config.dirty {
+                state.config.file_modified = config.file_modified;
+                state.config.merge(*config);
+            } else {
+                state.config = *config;
+            }
+            state.graph.reload_config(&state.config, Some(&previous));
+        }
+        Message::SaveConfig => {
+            if state.config.dirty {
+                if state.config.save_atomic().is_some() {
+                    state.config.dirty = false;
+                    state.save_failed = false;
+                    return toast::notify(Toast::info("Config saved"));
+                } else if !state.save_failed {
+                    state.save_failed = true;
+                    return toast::notify(Toast::error("Could not save config"));
+                }
+            }
+        }
+        Message::CloseRequested(id) => {
+            if state.config.dirty && state.config.save_atomic().is_none() {
+                eprintln!("Could not save config on exit");
+            }
+            return iced::window::close(id);
         }
     }
     Task::none()
 }
 
 fn view(state: &Solder) -> Element<'_, Message> {
-    canvas(&state.graph)
+    let graph = canvas(&state.graph)
         .width(Length::Fill)
-        .height(Length::Fill)
-        .into()
+        .height(Length::Fill);
+    // Toasts are left out of PNG exports
+    let toasts = if state.graph.exporting { const { &VecDeque::new() } } else { &state.notifications };
+    // The overlay stays in the tree even when empty, so the canvas keeps
+    // its place (and its drag state) as toasts come and go
+    let graph = stack![graph, toast::view(toasts)];
+    column![graph, status_bar::view(&state.graph)].into()
 }
 
-fn subscription(_state: &Solder) -> Subscription<Message> {
-    pipewire_client::connect().map(Message::Pipewire)
+fn subscription(state: &Solder) -> Subscription<Message> {
+    let mut subscriptions = vec![
+        pipewire_client::connect().map(Message::Pipewire),
+        watch_config(state.config.profile.clone()),
+        ipc::listen(),
+        iced::window::close_requests().map(Message::CloseRequested),
+    ];
+    if let Some(interval) = state.graph.tick_interval() {
+        subscriptions.push(
+            iced::time::every(interval).map(|_| Message::Graph(GraphMessage::Tick)),
+        );
+    }
+    // Removals wait a moment in case PipeWire reuses the id
+    if state.graph.has_pending_removals() {
+        subscriptions.push(
+            iced::time::every(std::time::Duration::from_millis(50))
+                .map(|_| Message::Graph(GraphMessage::PendingRemovalTick)),
+        );
+    }
+    // Animation frames, capped at the configured rate; they also expire toasts
+    if state.graph.is_animating() || !state.notifications.is_empty() {
+        let frame_ms = 1000 / state.config.max_fps as u64;
+        subscriptions.push(
+            iced::time::every(std::time::Duration::from_millis(frame_ms))
+                .map(|_| Message::Graph(GraphMessage::AnimationTick)),
+        );
+    }
+    // Write pending config changes at most every two seconds, backing off
+    // to every thirty once a save has failed
+    if state.config.dirty {
+        let interval = if state.save_failed { 30 } else { 2 };
+        subscriptions.push(
+            iced::time::every(std::time::Duration::from_secs(interval)).map(|_| Message::SaveConfig),
+        );
+    }
+    // Re-establish name-pinned connections every few seconds
+    if state.graph.has_pinned_specs() {
+        subscriptions.push(
+            iced::time::every(std::time::Duration::from_secs(5))
+                .map(|_| Message::Graph(GraphMessage::ReconnectPinned)),
+        );
+    }
+    Subscription::batch(subscriptions)
 }
 
-/// Connect two ports via pw-link
-pub fn pipewire_connect(output_port: u32, input_port: u32) {
+/// Reload the profile's config whenever its file changes on disk
+fn watch_config(profile: String) -> Subscription<Message> {
+    Subscription::run_with(profile, |profile| {
+        let profile = profile.clone();
+        iced::stream::channel(10, |mut output: iced::futures::channel::mpsc::Sender<Message>| async move {
+            use iced::futures::{SinkExt, StreamExt};
+            use notify::Watcher;
+
+            let Some(path) = Config::config_path_for_profile(&profile) else {
+                return;
+            };
+            let Some(dir) = path.parent().map(|p| p.to_path_buf()) else {
+                return;
+            };
+            let (tx, mut rx) = iced::futures::channel::mpsc::channel::<()>(10);
+            let config_file = path.clone();
+            let watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+                if let Ok(event) = res
+                    && (event.kind.is_create() || event.kind.is_modify())
+                    && event.paths.contains(&config_file)
+                {
+                    let _ = tx.clone().try_send(());
+                }
+            });
+            // Watch the directory: atomic saves replace the file itself
+            let Ok(mut watcher) = watcher else {
+                return;
+            };
+            if std::fs::create_dir_all(&dir).is_err()
+                || watcher.watch(&dir, notify::RecursiveMode::NonRecursive).is_err()
+            {
+                return;
+            }
+
+            while rx.next().await.is_some() {
+                if let Some(config) = Config::load(&profile) {
+                    let _ = output.send(Message::ConfigReloaded(Box::new(config))).await;
+                }
+            }
+        })
+    })
+}
+
+/// Connect two ports via pw-link. If it can't find the ports by id and
+/// `names` holds both as "node:port", try once more by name.
+pub async fn pipewire_connect(output_port: u32, input_port: u32, names: Option<(String, String)>) -> Result<(), String> {
+    let result = run_pw_link(vec![output_port.to_string(), input_port.to_string()]).await;
+    match (result, names) {
+        (Err(error), Some((output, input))) if pw_link_unknown_port(&error) => {
+            // Report the original failure if the names don't work either
+            run_pw_link(vec![output, input]).await.map_err(|_| error)
+        }
+        (result, _) => result,
+    }
+}
+
+/// Disconnect two ports via pw-link -d
+pub async fn pipewire_disconnect(output_port: u32, input_port: u32) -> Result<(), String> {
+    run_pw_link(vec!["-d".to_string(), output_port.to_string(), input_port.to_string()]).await
+}
+
+const PW_LINK_MISSING: &str = "pw-link not installed";
+
+/// Whether a pw-link error means it couldn't resolve the ports it was
+/// given, rather than refusing the link itself ("File exists", ...)
+fn pw_link_unknown_port(error: &str) -> bool {
+    let error = error.to_lowercase();
+    ["no such file", "unknown", "not found"].iter().any(|cause| error.contains(cause))
+}
+
+/// Run pw-link in a background thread, turning a failure into readable text
+async fn run_pw_link(args: Vec<String>) -> Result<(), String> {
+    let (tx, rx) = iced::futures::channel::oneshot::channel();
+
+    std::thread::spawn(move || {
+        let result = match std::process::Command::new("pw-link").args(&args).output() {
+            Ok(output) if output.status.success() => Ok(()),
+            Ok(output) => {
+                let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+                Err(if stderr.is_empty() { format!("pw-link failed ({})", output.status) } else { stderr })
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(PW_LINK_MISSING.to_string()),
+            Err(e) => Err(format!("Could not run pw-link: {}", e)),
+        };
+        let _ = tx.send(result);
+    });
+
+    rx.await.unwrap_or_else(|_| Err("pw-link did not finish".to_string()))
+}
+
+/// Make a node the default sink (or source) via wpctl
+pub fn set_default_node(node_id: u32) {
     std::thread::spawn(move || {
-        let _ = std::process::Command::new("pw-link")
-            .arg(output_port.to_string())
-            .arg(input_port.to_string())
+        let _ = std::process::Command::new("wpctl")
+            .arg("set-default")
+            .arg(node_id.to_string())
             .output();
     });
 }
 
-/// Disconnect two ports via pw-link -d
-pub fn pipewire_disconnect(output_port: u32, input_port: u32) {
+/// Set a node's volume (1.0 = 100%) via wpctl
+pub fn set_node_volume(node_id: u32, volume: f32) {
+    std::thread::spawn(move || {
+        let _ = std::process::Command::new("wpctl")
+            .arg("set-volume")
+            .arg(node_id.to_string())
+            .arg(format!("{:.2}", volume))
+            .output();
+    });
+}
+
+/// Toggle a node's mute via wpctl
+pub fn toggle_node_mute(node_id: u32) {
     std::thread::spawn(move || {
-        let _ = std::process::Command::new("pw-link")
-            .arg("-d")
-            .arg(output_port.to_string())
-            .arg(input_port.to_string())
+        let _ = std::process::Command::new("wpctl")
+            .arg("set-mute")
+            .arg(node_id.to_string())
+            .arg("toggle")
             .output();
     });
 }
@@ -102,6 +373,38 @@ pub fn set_device_profile(device_id: u32, profile_index: u32) {
     });
 }
 
+/// Ask the user for a preset file, starting in the presets directory
+pub async fn pick_preset_file() -> Option<std::path::PathBuf> {
+    let mut dialog = rfd::AsyncFileDialog::new()
+        .set_title("Apply preset")
+        .add_filter("Preset", &["json", "yaml", "yml"]);
+    if let Some(dir) = Config::presets_dir() {
+        dialog = dialog.set_directory(dir);
+    }
+    dialog.pick_file().await.map(|file| file.path().to_path_buf())
+}
+
+/// Ask the user for a file to import
+pub async fn pick_open_file(title: &'static str, extension: &'static str) -> Option<std::path::PathBuf> {
+    rfd::AsyncFileDialog::new()
+        .set_title(title)
+        .add_filter(extension, &[extension])
+        .pick_file()
+        .await
+        .map(|file| file.path().to_path_buf())
+}
+
+/// Ask the user where to save an exported file
+pub async fn pick_save_file(title: &'static str, extension: &'static str, file_name: String) -> Option<std::path::PathBuf> {
+    rfd::AsyncFileDialog::new()
+        .set_title(title)
+        .add_filter(extension, &[extension])
+        .set_file_name(file_name)
+        .save_file()
+        .await
+        .map(|file| file.path().to_path_buf())
+}
+
 /// Load device profiles via pw-dump (async, runs in background thread)
 pub async fn load_device_profiles(device_id: u32) -> Vec<graph::DeviceProfile> {
     let (tx, rx) = iced::futures::channel::oneshot::channel();
@@ -141,24 +444,24 @@ fn parse_device_profiles(device_id: u32) -> Vec<graph::DeviceProfile> {
     let mut profiles = Vec::new();
     if let Some(arr) = json.as_array() {
         for obj in arr {
-            if let Some(enum_profiles) = obj.pointer("/info/params/EnumProfile") {
-                if let Some(profile_arr) = enum_profiles.as_array() {
-                    for p in profile_arr {
-                        let index = p.get("index").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
-                        let name = p.get("name").and_then(|v| v.as_str()).unwrap_or("").to_string();
-                        let description = p.get("description").and_then(|v| v.as_str()).unwrap_or("").to_string();
-
-                        // Skip "Off" profile (index 0) - that's what deactivation uses
-                        if index == 0 {
-                            continue;
-                        }
-
-                        profiles.push(graph::DeviceProfile {
-                            index,
-                            name,
-                            description,
-                        });
+            if let Some(enum_profiles) = obj.pointer("/info/params/EnumProfile")
+                && let Some(profile_arr) = enum_profiles.as_array()
+            {
+                for p in profile_arr {
+                    let index = p.get("index").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+                    let name = p.get("name").and_then(|v| v.as_str()).unwrap_or("").to_string();
+                    let description = p.get("description").and_then(|v| v.as_str()).unwrap_or("").to_string();
+
+                    // Skip "Off" profile (index 0) - that's what deactivation uses
+                    if index == 0 {
+                        continue;
                     }
+
+                    profiles.push(graph::DeviceProfile {
+                        index,
+                        name,
+                        description,
+                    });
                 }
             }
         }
@@ -1,8 +1,8 @@
 use iced::futures::channel::mpsc;
 use iced::futures::StreamExt;
 use iced::Subscription;
-use pipewire::context::ContextBox;
-use pipewire::main_loop::MainLoopBox;
+use pipewire::context::ContextRc;
+use pipewire::main_loop::MainLoopRc;
 use pipewire as pw;
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
@@ -19,6 +19,11 @@ pub enum PipewireEvent {
         serial: Option<String>,
         object_path: Option<String>,
         device_id: Option<u32>,
+        media_class: Option<String>,
+        /// PipeWire node.name; `name` is the friendlier description
+        node_name: Option<String>,
+        /// The client that created the node
+        client_id: Option<u32>,
     },
     NodeRemoved {
         id: u32,
@@ -27,6 +32,8 @@ pub enum PipewireEvent {
         node_id: u32,
         port_id: u32,
         name: String,
+        alias: Option<String>,
+        is_monitor: bool,
         direction: PortDirection,
         port_type: PortType,
     },
@@ -44,6 +51,32 @@ pub enum PipewireEvent {
     LinkRemoved {
         id: u32,
     },
+    LinkStateChanged {
+        id: u32,
+        state: LinkState,
+    },
+    NodeStateChanged {
+        id: u32,
+        state: NodeState,
+    },
+    /// A default.audio.sink / default.audio.source entry of the "default"
+    /// metadata changed; `value` is its raw JSON (empty when removed)
+    MetadataChanged {
+        key: String,
+        value: String,
+    },
+    /// A node's Props param changed; `volume` is on wpctl's scale (1.0 = 100%)
+    NodeVolumeChanged {
+        id: u32,
+        volume: f32,
+        muted: bool,
+    },
+    PortFormatChanged {
+        port_id: u32,
+        format: String,
+        channels: u16,
+        sample_rate: u32,
+    },
     DeviceAdded {
         id: u32,
         name: String,
@@ -53,6 +86,108 @@ pub enum PipewireEvent {
     DeviceRemoved {
         id: u32,
     },
+    /// A client connected; `pid` is 0 and `exe` empty when it doesn't say
+    ClientAdded {
+        id: u32,
+        name: String,
+        pid: u32,
+        exe: String,
+    },
+    ClientRemoved {
+        id: u32,
+    },
+    /// The PipeWire connection was lost (or couldn't be made); retrying
+    Disconnected,
+    /// Connected again after a Disconnected
+    Reconnected,
+}
+
+/// Processing state of a node, as reported by its info events
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeState {
+    Running,
+    Idle,
+    Suspended,
+    Error,
+    Creating,
+}
+
+impl NodeState {
+    fn from_pw(state: pw::node::NodeState<'_>) -> Self {
+        match state {
+            pw::node::NodeState::Running => NodeState::Running,
+            pw::node::NodeState::Idle => NodeState::Idle,
+            pw::node::NodeState::Suspended => NodeState::Suspended,
+            pw::node::NodeState::Error(_) => NodeState::Error,
+            pw::node::NodeState::Creating => NodeState::Creating,
+        }
+    }
+}
+
+/// Longest wait between reconnection attempts
+const MAX_RECONNECT_DELAY_SECS: u64 = 60;
+
+/// Negotiation state of a link, as reported by its info events
+#[derive(Debug, Clone, PartialEq)]
+pub enum LinkState {
+    Active,
+    Paused,
+    Negotiating,
+    Error(String),
+}
+
+impl LinkState {
+    fn from_pw(state: pw::link::LinkState<'_>) -> Self {
+        match state {
+            pw::link::LinkState::Active => LinkState::Active,
+            pw::link::LinkState::Paused => LinkState::Paused,
+            pw::link::LinkState::Error(msg) => LinkState::Error(msg.to_string()),
+            pw::link::LinkState::Unlinked
+            | pw::link::LinkState::Init
+            | pw::link::LinkState::Negotiating
+            | pw::link::LinkState::Allocating => LinkState::Negotiating,
+        }
+    }
+}
+
+/// Describe a negotiated port format as (format, channels, sample_rate)
+fn parse_port_format(pod: &pw::spa::pod::Pod) -> Option<(String, u16, u32)> {
+    use pw::spa::param::format::{MediaSubtype, MediaType};
+
+    let (media_type, media_subtype) = pw::spa::param::format_utils::parse_format(pod).ok()?;
+    match (media_type, media_subtype) {
+        (MediaType::Audio, MediaSubtype::Raw | MediaSubtype::Dsp) => {
+            let mut info = pw::spa::param::audio::AudioInfoRaw::new();
+            info.parse(pod).ok()?;
+            Some(("PCM".to_string(), info.channels() as u16, info.rate()))
+        }
+        (_, MediaSubtype::Midi | MediaSubtype::Control) => Some(("MIDI".to_string(), 0, 0)),
+        _ => None,
+    }
+}
+
+/// Read (volume, muted) from a node's Props param
+fn parse_node_props(pod: &pw::spa::pod::Pod) -> Option<(f32, bool)> {
+    use pw::spa::pod::{deserialize::PodDeserializer, Value, ValueArray};
+
+    let (_, Value::Object(object)) = PodDeserializer::deserialize_any_from(pod.as_bytes()).ok()? else {
+        return None;
+    };
+    let mut volume = None;
+    let mut muted = false;
+    for property in object.properties {
+        match (property.key, property.value) {
+            (pw::spa::sys::SPA_PROP_channelVolumes, Value::ValueArray(ValueArray::Float(volumes)))
+                if !volumes.is_empty() =>
+            {
+                volume = Some(volumes.iter().sum::<f32>() / volumes.len() as f32);
+            }
+            (pw::spa::sys::SPA_PROP_mute, Value::Bool(mute)) => muted = mute,
+            _ => {}
+        }
+    }
+    // Channel volumes are linear; wpctl shows their cube root
+    volume.map(|v| (v.cbrt(), muted))
 }
 
 pub fn connect() -> Subscription<PipewireEvent> {
@@ -61,8 +196,21 @@ pub fn connect() -> Subscription<PipewireEvent> {
             let (tx, mut rx) = mpsc::channel::<PipewireEvent>(100);
 
             std::thread::spawn(move || {
-                if let Err(e) = run_pipewire_loop(tx) {
-                    eprintln!("PipeWire error: {}", e);
+                // Keep reconnecting with exponential backoff (1s, 2s, 4s … 60s)
+                let mut attempt: u32 = 0;
+                loop {
+                    match run_pipewire_loop(tx.clone(), attempt > 0, false) {
+                        Ok(()) => {
+                            eprintln!("PipeWire connection lost");
+                            attempt = 0;
+                        }
+                        Err(e) => eprintln!("PipeWire error: {}", e),
+                    }
+                    let _ = tx.clone().try_send(PipewireEvent::Disconnected);
+
+                    let delay = 2u64.saturating_pow(attempt).min(MAX_RECONNECT_DELAY_SECS);
+                    std::thread::sleep(std::time::Duration::from_secs(delay));
+                    attempt = attempt.saturating_add(1);
                 }
             });
 
@@ -74,17 +222,68 @@ pub fn connect() -> Subscription<PipewireEvent> {
     })
 }
 
-fn run_pipewire_loop(tx: mpsc::Sender<PipewireEvent>) -> Result<(), pw::Error> {
-    let mainloop = MainLoopBox::new(None)?;
-    let context = ContextBox::new(mainloop.loop_(), None)?;
-    let core = context.connect(None)?;
-    let registry = core.get_registry()?;
+/// Room for every event of the initial enumeration, which nobody drains
+/// until the snapshot's loop has finished
+const SNAPSHOT_CAPACITY: usize = 1 << 16;
+
+/// Enumerate the current graph once, for the command-line exports
+pub fn snapshot() -> Result<Vec<PipewireEvent>, pw::Error> {
+    let (tx, mut rx) = mpsc::channel::<PipewireEvent>(SNAPSHOT_CAPACITY);
+    run_pipewire_loop(tx, false, true)?;
+    let mut events = Vec::new();
+    while let Ok(Some(event)) = rx.try_next() {
+        events.push(event);
+    }
+    Ok(events)
+}
+
+/// Run until the daemon goes away (Ok) or the connection fails (Err). With
+/// `until_synced`, stop instead once the existing globals have been announced.
+fn run_pipewire_loop(mut tx: mpsc::Sender<PipewireEvent>, reconnecting: bool, until_synced: bool) -> Result<(), pw::Error> {
+    let mainloop = MainLoopRc::new(None)?;
+    let context = ContextRc::new(&mainloop, None)?;
+    let core = context.connect_rc(None)?;
+    let registry = core.get_registry_rc()?;
+    let registry_weak = registry.downgrade();
+
+    if reconnecting {
+        let _ = tx.try_send(PipewireEvent::Reconnected);
+    }
+
+    // A core error (e.g. the daemon exiting) ends this session
+    let _core_listener = core
+        .add_listener_local()
+        .error({
+            let mainloop = mainloop.downgrade();
+            move |id, _seq, _res, message| {
+                if id == pw::core::PW_ID_CORE {
+                    eprintln!("PipeWire core error: {}", message);
+                    if let Some(mainloop) = mainloop.upgrade() {
+                        mainloop.quit();
+                    }
+                }
+            }
+        })
+        .register();
 
     // Track object types for correct removal
     let port_to_node: Rc<RefCell<HashMap<u32, u32>>> = Rc::new(RefCell::new(HashMap::new()));
     let node_ids: Rc<RefCell<HashSet<u32>>> = Rc::new(RefCell::new(HashSet::new()));
     let link_ids: Rc<RefCell<HashSet<u32>>> = Rc::new(RefCell::new(HashSet::new()));
     let device_ids: Rc<RefCell<HashSet<u32>>> = Rc::new(RefCell::new(HashSet::new()));
+    let client_ids: Rc<RefCell<HashSet<u32>>> = Rc::new(RefCell::new(HashSet::new()));
+    // Bound link proxies, kept alive so their info listeners keep reporting state
+    let link_proxies: Rc<RefCell<HashMap<u32, (pw::link::Link, pw::link::LinkListener)>>> =
+        Rc::new(RefCell::new(HashMap::new()));
+    // Bound port proxies, for format param updates
+    let port_proxies: Rc<RefCell<HashMap<u32, (pw::port::Port, pw::port::PortListener)>>> =
+        Rc::new(RefCell::new(HashMap::new()));
+    // Bound node proxies, for state updates
+    let node_proxies: Rc<RefCell<HashMap<u32, (pw::node::Node, pw::node::NodeListener)>>> =
+        Rc::new(RefCell::new(HashMap::new()));
+    // Bound "default" metadata, for the default sink and source
+    let metadata_proxies: Rc<RefCell<HashMap<u32, (pw::metadata::Metadata, pw::metadata::MetadataListener)>>> =
+        Rc::new(RefCell::new(HashMap::new()));
     let tx = Rc::new(RefCell::new(tx));
 
     let _listener = registry
@@ -95,7 +294,13 @@ fn run_pipewire_loop(tx: mpsc::Sender<PipewireEvent>) -> Result<(), pw::Error> {
             let node_ids = node_ids.clone();
             let link_ids = link_ids.clone();
             let device_ids = device_ids.clone();
+            let client_ids = client_ids.clone();
+            let link_proxies = link_proxies.clone();
+            let port_proxies = port_proxies.clone();
+            let node_proxies = node_proxies.clone();
+            let metadata_proxies = metadata_proxies.clone();
             move |global| {
+                let tx_handle = tx.clone();
                 let mut tx = tx.borrow_mut();
                 match global.type_ {
                     pw::types::ObjectType::Device => {
@@ -124,6 +329,30 @@ fn run_pipewire_loop(tx: mpsc::Sender<PipewireEvent>) -> Result<(), pw::Error> {
                             api,
                         });
                     }
+                    pw::types::ObjectType::Client => {
+                        let props = global.props.as_ref();
+                        let name = props
+                            .and_then(|p| p.get("application.name"))
+                            .unwrap_or("Unknown")
+                            .to_string();
+                        let pid = props
+                            .and_then(|p| p.get("application.process.id"))
+                            .and_then(|s| s.parse().ok())
+                            .unwrap_or(0);
+                        let exe = props
+                            .and_then(|p| p.get("application.process.binary"))
+                            .unwrap_or("")
+                            .to_string();
+
+                        client_ids.borrow_mut().insert(global.id);
+
+                        let _ = tx.try_send(PipewireEvent::ClientAdded {
+                            id: global.id,
+                            name,
+                            pid,
+                            exe,
+                        });
+                    }
                     pw::types::ObjectType::Node => {
                         let props = global.props.as_ref();
                         let name = props
@@ -144,6 +373,15 @@ fn run_pipewire_loop(tx: mpsc::Sender<PipewireEvent>) -> Result<(), pw::Error> {
                         let device_id = props
                             .and_then(|p| p.get("device.id"))
                             .and_then(|s| s.parse().ok());
+                        let media_class = props
+                            .and_then(|p| p.get("media.class"))
+                            .map(String::from);
+                        let node_name = props
+                            .and_then(|p| p.get("node.name"))
+                            .map(String::from);
+                        let client_id = props
+                            .and_then(|p| p.get("client.id"))
+                            .and_then(|s| s.parse().ok());
 
                         node_ids.borrow_mut().insert(global.id);
 
@@ -154,7 +392,39 @@ fn run_pipewire_loop(tx: mpsc::Sender<PipewireEvent>) -> Result<(), pw::Error> {
                             serial,
                             object_path,
                             device_id,
+                            media_class,
+                            node_name,
+                            client_id,
                         });
+
+                        // Follow the node's state (running, idle, suspended…) and volume
+                        if let Some(registry) = registry_weak.upgrade()
+                            && let Ok(node) = registry.bind::<pw::node::Node, _>(global)
+                        {
+                            let id = global.id;
+                            let state_tx = tx_handle.clone();
+                            let props_tx = tx_handle.clone();
+                            let listener = node
+                                .add_listener_local()
+                                .info(move |info| {
+                                    let state = NodeState::from_pw(info.state());
+                                    let _ = state_tx
+                                        .borrow_mut()
+                                        .try_send(PipewireEvent::NodeStateChanged { id, state });
+                                })
+                                .param(move |_seq, param_type, _index, _next, param| {
+                                    if param_type == pw::spa::param::ParamType::Props
+                                        && let Some((volume, muted)) = param.and_then(parse_node_props)
+                                    {
+                                        let _ = props_tx
+                                            .borrow_mut()
+                                            .try_send(PipewireEvent::NodeVolumeChanged { id, volume, muted });
+                                    }
+                                })
+                                .register();
+                            node.subscribe_params(&[pw::spa::param::ParamType::Props]);
+                            node_proxies.borrow_mut().insert(id, (node, listener));
+                        }
                     }
                     pw::types::ObjectType::Port => {
                         let props = global.props.as_ref();
@@ -162,6 +432,12 @@ fn run_pipewire_loop(tx: mpsc::Sender<PipewireEvent>) -> Result<(), pw::Error> {
                             .and_then(|p| p.get("port.name"))
                             .unwrap_or("port")
                             .to_string();
+                        let alias = props
+                            .and_then(|p| p.get("port.alias"))
+                            .map(|s| s.to_string());
+                        let is_monitor = props
+                            .and_then(|p| p.get("port.monitor"))
+                            .is_some_and(|m| m == "1" || m == "true");
                         let node_id: u32 = props
                             .and_then(|p| p.get("node.id"))
                             .and_then(|s| s.parse().ok())
@@ -198,10 +474,38 @@ fn run_pipewire_loop(tx: mpsc::Sender<PipewireEvent>) -> Result<(), pw::Error> {
 
                         port_to_node.borrow_mut().insert(global.id, node_id);
 
+                        // Follow the negotiated format (sample rate, channels)
+                        if let Some(registry) = registry_weak.upgrade()
+                            && let Ok(port) = registry.bind::<pw::port::Port, _>(global)
+                        {
+                            let port_id = global.id;
+                            let tx_handle = tx_handle.clone();
+                            let listener = port
+                                .add_listener_local()
+                                .param(move |_seq, id, _index, _next, param| {
+                                    if id != pw::spa::param::ParamType::Format {
+                                        return;
+                                    }
+                                    if let Some((format, channels, sample_rate)) = param.and_then(parse_port_format) {
+                                        let _ = tx_handle.borrow_mut().try_send(PipewireEvent::PortFormatChanged {
+                                            port_id,
+                                            format,
+                                            channels,
+                                            sample_rate,
+                                        });
+                                    }
+                                })
+                                .register();
+                            port.subscribe_params(&[pw::spa::param::ParamType::Format]);
+                            port_proxies.borrow_mut().insert(port_id, (port, listener));
+                        }
+
                         let _ = tx.try_send(PipewireEvent::PortAdded {
                             node_id,
                             port_id: global.id,
                             name,
+                            alias,
+                            is_monitor,
                             direction,
                             port_type,
                         });
@@ -227,6 +531,23 @@ fn run_pipewire_loop(tx: mpsc::Sender<PipewireEvent>) -> Result<(), pw::Error> {
 
                         link_ids.borrow_mut().insert(global.id);
 
+                        // Bind the link to follow its state (negotiation failures surface here)
+                        if let Some(registry) = registry_weak.upgrade()
+                            && let Ok(link) = registry.bind::<pw::link::Link, _>(global)
+                        {
+                            let id = global.id;
+                            let listener = link
+                                .add_listener_local()
+                                .info(move |info| {
+                                    let state = LinkState::from_pw(info.state());
+                                    let _ = tx_handle
+                                        .borrow_mut()
+                                        .try_send(PipewireEvent::LinkStateChanged { id, state });
+                                })
+                                .register();
+                            link_proxies.borrow_mut().insert(id, (link, listener));
+                        }
+
                         let _ = tx.try_send(PipewireEvent::LinkAdded {
                             id: global.id,
                             output_node,
@@ -235,6 +556,31 @@ fn run_pipewire_loop(tx: mpsc::Sender<PipewireEvent>) -> Result<(), pw::Error> {
                             input_port,
                         });
                     }
+                    pw::types::ObjectType::Metadata => {
+                        // Only the "default" metadata holds the default sink and source
+                        let is_default = global.props.as_ref()
+                            .and_then(|p| p.get("metadata.name"))
+                            .is_some_and(|name| name == "default");
+                        if is_default
+                            && let Some(registry) = registry_weak.upgrade()
+                            && let Ok(metadata) = registry.bind::<pw::metadata::Metadata, _>(global)
+                        {
+                            let tx_handle = tx_handle.clone();
+                            let listener = metadata
+                                .add_listener_local()
+                                .property(move |_subject, key, _type, value| {
+                                    if let Some(key @ ("default.audio.sink" | "default.audio.source")) = key {
+                                        let _ = tx_handle.borrow_mut().try_send(PipewireEvent::MetadataChanged {
+                                            key: key.to_string(),
+                                            value: value.unwrap_or_default().to_string(),
+                                        });
+                                    }
+                                    0
+                                })
+                                .register();
+                            metadata_proxies.borrow_mut().insert(global.id, (metadata, listener));
+                        }
+                    }
                     _ => {}
                 }
             }
@@ -245,24 +591,59 @@ fn run_pipewire_loop(tx: mpsc::Sender<PipewireEvent>) -> Result<(), pw::Error> {
             let node_ids = node_ids.clone();
             let link_ids = link_ids.clone();
             let device_ids = device_ids.clone();
+            let client_ids = client_ids.clone();
+            let link_proxies = link_proxies.clone();
+            let port_proxies = port_proxies.clone();
+            let node_proxies = node_proxies.clone();
+            let metadata_proxies = metadata_proxies.clone();
             move |id| {
                 let mut tx = tx.borrow_mut();
                 if let Some(node_id) = port_to_node.borrow_mut().remove(&id) {
+                    port_proxies.borrow_mut().remove(&id);
                     let _ = tx.try_send(PipewireEvent::PortRemoved {
                         node_id,
                         port_id: id,
                     });
                 } else if node_ids.borrow_mut().remove(&id) {
+                    node_proxies.borrow_mut().remove(&id);
                     let _ = tx.try_send(PipewireEvent::NodeRemoved { id });
                 } else if link_ids.borrow_mut().remove(&id) {
+                    link_proxies.borrow_mut().remove(&id);
                     let _ = tx.try_send(PipewireEvent::LinkRemoved { id });
                 } else if device_ids.borrow_mut().remove(&id) {
                     let _ = tx.try_send(PipewireEvent::DeviceRemoved { id });
+                } else if client_ids.borrow_mut().remove(&id) {
+                    let _ = tx.try_send(PipewireEvent::ClientRemoved { id });
+                } else {
+                    metadata_proxies.borrow_mut().remove(&id);
                 }
             }
         })
         .register();
 
+    // The core answers a sync after everything queued before it, i.e. after
+    // the registry has announced every existing global
+    let _sync_listener = if until_synced {
+        let pending = core.sync(0)?;
+        let listener = core
+            .add_listener_local()
+            .done({
+                let mainloop = mainloop.downgrade();
+                move |id, seq| {
+                    if id == pw::core::PW_ID_CORE
+                        && seq == pending
+                        && let Some(mainloop) = mainloop.upgrade()
+                    {
+                        mainloop.quit();
+                    }
+                }
+            })
+            .register();
+        Some(listener)
+    } else {
+        None
+    };
+
     mainloop.run();
 
     Ok(())
@@ -1,5 +1,9 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::PresetFormat;
 
 /// A saved patchbay preset containing connection configurations
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -11,6 +15,14 @@ pub struct Preset {
     pub node_renames: HashMap<String, String>,
     #[serde(default)]
     pub pinned_connections: Vec<PresetConnection>,
+    /// Disconnect the matched sources' other links first; `None` follows
+    /// the config's exclusive mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclusive: Option<bool>,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 impl Preset {
@@ -21,10 +33,61 @@ impl Preset {
             connections: Vec::new(),
             node_renames: HashMap::new(),
             pinned_connections: Vec::new(),
+            exclusive: None,
+            description: String::new(),
+            tags: Vec::new(),
+        }
+    }
+
+    /// Read a preset from a JSON or YAML file, picked by extension
+    pub fn load(path: &Path) -> Option<Self> {
+        let contents = fs::read_to_string(path).ok()?;
+        match PresetFormat::from_path(path) {
+            PresetFormat::Json => serde_json::from_str(&contents).ok(),
+            PresetFormat::Yaml => serde_yaml_ng::from_str(&contents).ok(),
+        }
+    }
+
+    /// Write the preset as pretty-printed JSON, or as YAML for a
+    /// `.yaml`/`.yml` path
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
         }
+        let contents = match PresetFormat::from_path(path) {
+            PresetFormat::Json => serde_json::to_string_pretty(self)?,
+            PresetFormat::Yaml => serde_yaml_ng::to_string(self).map_err(std::io::Error::other)?,
+        };
+        fs::write(path, &contents)
     }
 }
 
+/// The key a preset file is recorded under in `last_applied`: its canonical
+/// path, so relative and absolute spellings of one file match
+pub fn preset_key(path: &Path) -> String {
+    fs::canonicalize(path).as_deref().unwrap_or(path).to_string_lossy().into_owned()
+}
+
+/// Every preset file in `dir` with its path, most recently applied first
+/// (per `last_applied`, keyed by `preset_key`) and then by name. Files that
+/// don't parse as presets are left out.
+pub fn list_presets(dir: &Path, last_applied: &HashMap<String, u64>) -> Vec<(Preset, PathBuf)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut presets: Vec<(Preset, PathBuf)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json" || ext == "yaml" || ext == "yml"))
+        .filter_map(|path| Some((Preset::load(&path)?, path)))
+        .collect();
+    presets.sort_by_key(|(preset, path)| {
+        let applied = last_applied.get(&preset_key(path)).copied();
+        (std::cmp::Reverse(applied), preset.name.to_lowercase())
+    });
+    presets
+}
+
 /// A connection between two ports, stored by node/port identifiers
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct PresetConnection {
@@ -72,10 +135,10 @@ impl NodeMatcher {
     pub fn matches(&self, name: &str, app_name: Option<&str>, object_path: Option<&str>) -> bool {
         // If object_path is specified and matches, that's the strongest identifier
         if let (Some(matcher_path), Some(node_path)) = (&self.object_path, object_path) {
-            if self.use_regex {
-                if let Ok(re) = regex::Regex::new(matcher_path) {
-                    return re.is_match(node_path);
-                }
+            if self.use_regex
+                && let Ok(re) = regex::Regex::new(matcher_path)
+            {
+                return re.is_match(node_path);
             }
             return matcher_path == node_path;
         }
@@ -105,11 +168,3 @@ impl NodeMatcher {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
-pub enum PortTypeFilter {
-    #[default]
-    All,
-    Audio,
-    Midi,
-    Video,
-}
@@ -0,0 +1,91 @@
+use iced::{Point, Rectangle, Size};
+
+/// Items split a quadrant once it holds more than this many
+const MAX_ITEMS: usize = 8;
+/// Quadrants stop splitting at this depth
+const MAX_DEPTH: usize = 8;
+
+/// Region quadtree of rectangles, for point queries in world space
+#[derive(Debug)]
+pub struct QuadTree<T> {
+    root: Quadrant<T>,
+}
+
+#[derive(Debug)]
+struct Quadrant<T> {
+    bounds: Rectangle,
+    depth: usize,
+    /// Items in this quadrant that don't fit entirely inside one child
+    items: Vec<(Rectangle, T)>,
+    children: Option<Box<[Quadrant<T>; 4]>>,
+}
+
+impl<T: Copy> Quadrant<T> {
+    fn new(bounds: Rectangle, depth: usize) -> Self {
+        Self { bounds, depth, items: Vec::new(), children: None }
+    }
+
+    fn insert(&mut self, rect: Rectangle, item: T) {
+        if let Some(children) = &mut self.children
+            && let Some(child) = children.iter_mut().find(|c| contains_rect(&c.bounds, &rect))
+        {
+            child.insert(rect, item);
+            return;
+        }
+        self.items.push((rect, item));
+        if self.children.is_none() && self.items.len() > MAX_ITEMS && self.depth < MAX_DEPTH {
+            self.split();
+        }
+    }
+
+    fn split(&mut self) {
+        let Rectangle { x, y, width, height } = self.bounds;
+        let half = Size::new(width / 2.0, height / 2.0);
+        let depth = self.depth + 1;
+        self.children = Some(Box::new([
+            Quadrant::new(Rectangle::new(Point::new(x, y), half), depth),
+            Quadrant::new(Rectangle::new(Point::new(x + half.width, y), half), depth),
+            Quadrant::new(Rectangle::new(Point::new(x, y + half.height), half), depth),
+            Quadrant::new(Rectangle::new(Point::new(x + half.width, y + half.height), half), depth),
+        ]));
+        // Push down whatever now fits in a child
+        for (rect, item) in std::mem::take(&mut self.items) {
+            self.insert(rect, item);
+        }
+    }
+
+    fn query_point(&self, point: Point, out: &mut Vec<T>) {
+        out.extend(self.items.iter().filter(|(rect, _)| rect.contains(point)).map(|(_, item)| *item));
+        if let Some(children) = &self.children {
+            for child in children.iter().filter(|c| c.bounds.contains(point)) {
+                child.query_point(point, out);
+            }
+        }
+    }
+}
+
+impl<T: Copy> QuadTree<T> {
+    pub fn new(bounds: Rectangle) -> Self {
+        Self { root: Quadrant::new(bounds, 0) }
+    }
+
+    pub fn insert(&mut self, rect: Rectangle, item: T) {
+        self.root.insert(rect, item);
+    }
+
+    /// Items whose rectangle contains `point`
+    pub fn query_point(&self, point: Point) -> Vec<T> {
+        let mut out = Vec::new();
+        if self.root.bounds.contains(point) {
+            self.root.query_point(point, &mut out);
+        }
+        out
+    }
+}
+
+fn contains_rect(outer: &Rectangle, inner: &Rectangle) -> bool {
+    inner.x >= outer.x
+        && inner.y >= outer.y
+        && inner.x + inner.width <= outer.x + outer.width
+        && inner.y + inner.height <= outer.y + outer.height
+}
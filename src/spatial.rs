@@ -0,0 +1,51 @@
+use iced::{Point, Rectangle, Size};
+use crate::graph::{Graph, NODE_WIDTH};
+use crate::quadtree::QuadTree;
+
+/// Something hit testing can land on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpatialItem {
+    Node(u32),
+    Port { node_id: u32, port_id: u32 },
+}
+
+impl Default for QuadTree<SpatialItem> {
+    fn default() -> Self {
+        Self::new(Rectangle::new(Point::ORIGIN, Size::ZERO))
+    }
+}
+
+impl QuadTree<SpatialItem> {
+    /// Re-index every node body, and every port scrolled into view as a
+    /// square of `port_radius`
+    pub fn rebuild(&mut self, graph: &Graph, port_radius: f32) {
+        let mut entries = Vec::new();
+        for node in graph.nodes.values() {
+            let window = graph.port_window(node.id);
+            let bounds = Rectangle::new(node.position, Size::new(NODE_WIDTH, Graph::node_height(node, window)));
+            entries.push((bounds, SpatialItem::Node(node.id)));
+            if node.collapsed {
+                continue;
+            }
+            for port in node.input_ports.iter().chain(node.output_ports.iter()) {
+                if !Graph::port_shown(node, port, window) {
+                    continue;
+                }
+                let pos = Graph::port_position(node, port, window);
+                let rect = Rectangle::new(
+                    Point::new(pos.x - port_radius, pos.y - port_radius),
+                    Size::new(port_radius * 2.0, port_radius * 2.0),
+                );
+                entries.push((rect, SpatialItem::Port { node_id: node.id, port_id: port.id }));
+            }
+        }
+
+        // Root covers everything that was inserted
+        let bounds = entries.iter().map(|(r, _)| *r).reduce(|a, b| a.union(&b))
+            .unwrap_or(Rectangle::new(Point::ORIGIN, Size::ZERO));
+        *self = Self::new(bounds);
+        for (rect, item) in entries {
+            self.insert(rect, item);
+        }
+    }
+}
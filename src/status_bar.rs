@@ -0,0 +1,49 @@
+use iced::widget::{container, row, text};
+use iced::{Color, Element, Length};
+
+use crate::graph::{palette, Graph, PortType};
+use crate::Message;
+
+/// One-line summary of the graph below the canvas
+pub fn view(graph: &Graph) -> Element<'_, Message> {
+    let mut items = vec![
+        format!("{} nodes", graph.nodes.len()),
+        format!("{} links", graph.links.len()),
+    ];
+    if graph.duplicate_link_count > 0 {
+        items.push(format!("{} duplicate links", graph.duplicate_link_count));
+    }
+    let hidden = graph.hidden_node_count();
+    if hidden > 0 {
+        items.push(format!("{} hidden", hidden));
+    }
+    if !graph.selected_nodes.is_empty() {
+        items.push(format!("{} selected", graph.selected_nodes.len()));
+    }
+    items.push(format!("{:.0}%", graph.zoom * 100.0));
+    if let Some(port_type) = graph.port_type_filter {
+        let name = match port_type {
+            PortType::Audio => "Audio",
+            PortType::Midi => "MIDI",
+            PortType::Video => "Video",
+        };
+        items.push(format!("Filter: {}", name));
+    }
+    if let Some(class) = graph.media_class_filter {
+        items.push(format!("Showing: {}", class.label()));
+    }
+    if graph.search_active && !graph.search_query.is_empty() {
+        items.push(format!("Search: {}", graph.search_query));
+    }
+
+    let mut bar = row(items.into_iter().map(|item| text(item).size(12).color(palette::TEXT_SECONDARY).into()))
+        .spacing(16);
+    if graph.pipewire_disconnected {
+        bar = bar.push(text("PW disconnected").size(12).color(palette::LINK_ERROR));
+    }
+    container(bar)
+        .width(Length::Fill)
+        .padding([4, 12])
+        .style(|_| container::Style::default().background(Color::from_rgb(0.08, 0.08, 0.10)))
+        .into()
+}
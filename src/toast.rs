@@ -0,0 +1,98 @@
+use iced::widget::{column, container, text};
+use iced::{border, Color, Element, Length, Task};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::Message;
+
+/// How long a toast stays on screen
+const TOAST_DURATION: Duration = Duration::from_secs(4);
+/// Older toasts are dropped once this many are showing
+const MAX_TOASTS: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToastLevel {
+    /// Routine things, like a request that turned out to be a no-op
+    Debug,
+    Info,
+    Warning,
+    Error,
+}
+
+impl ToastLevel {
+    fn color(self) -> Color {
+        match self {
+            ToastLevel::Debug => Color::from_rgb(0.30, 0.30, 0.34),
+            ToastLevel::Info => Color::from_rgb(0.18, 0.50, 0.30),
+            ToastLevel::Warning => Color::from_rgb(0.78, 0.48, 0.14),
+            ToastLevel::Error => Color::from_rgb(0.72, 0.22, 0.22),
+        }
+    }
+}
+
+/// A short message shown in the top-right corner
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub message: String,
+    pub level: ToastLevel,
+    pub expires: Instant,
+}
+
+impl Toast {
+    pub fn new(level: ToastLevel, message: impl Into<String>) -> Self {
+        Self { message: message.into(), level, expires: Instant::now() + TOAST_DURATION }
+    }
+
+    pub fn debug(message: impl Into<String>) -> Self {
+        Self::new(ToastLevel::Debug, message)
+    }
+
+    pub fn info(message: impl Into<String>) -> Self {
+        Self::new(ToastLevel::Info, message)
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self::new(ToastLevel::Warning, message)
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::new(ToastLevel::Error, message)
+    }
+}
+
+/// A task that shows `toast`
+pub fn notify(toast: Toast) -> Task<Message> {
+    Task::done(Message::ShowNotification(toast))
+}
+
+pub fn push(toasts: &mut VecDeque<Toast>, toast: Toast) {
+    toasts.push_back(toast);
+    while toasts.len() > MAX_TOASTS {
+        toasts.pop_front();
+    }
+}
+
+pub fn remove_expired(toasts: &mut VecDeque<Toast>) {
+    let now = Instant::now();
+    toasts.retain(|t| t.expires > now);
+}
+
+/// Toasts stacked in the top-right corner, newest at the bottom
+pub fn view(toasts: &VecDeque<Toast>) -> Element<'_, Message> {
+    let pills = toasts.iter().map(|toast| {
+        let color = toast.level.color();
+        container(text(&toast.message).size(12))
+            .padding([6, 14])
+            .style(move |_| {
+                container::Style::default()
+                    .background(color)
+                    .color(Color::WHITE)
+                    .border(border::rounded(12))
+            })
+            .into()
+    });
+    container(column(pills).spacing(8).align_x(iced::alignment::Horizontal::Right))
+        .align_right(Length::Fill)
+        .padding(12)
+        .into()
+}